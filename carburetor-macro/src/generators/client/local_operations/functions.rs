@@ -6,16 +6,17 @@ use crate::{
     generators::{
         client::{
             local_operations::models::{AsLocalInsertModel, AsLocalUpdateModel},
-            models::AsTableMetadata,
+            models::{AsNewId, AsRepairSyncMetadataFunction, AsTableMetadata},
         },
         diesel::{
             models::{AsChangesetModel, AsFullModel, AsInsertModel, AsModelType},
             schema::AsSchemaTable,
         },
+        handle::models::AsSyncGroupHandle,
     },
     parsers::{
         sync_group::{CarburetorSyncGroup, SyncGroupTableConfig},
-        table::column::CarburetorColumnType,
+        table::column::{CarburetorColumnType, IdStrategy, MergeStrategy},
     },
 };
 
@@ -24,21 +25,50 @@ struct AsLocalInsertFunction<'a>(&'a SyncGroupTableConfig);
 impl<'a> ToTokens for AsLocalInsertFunction<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let function_name = self.get_function_name();
+        let with_connection_function_name = self.get_with_connection_function_name();
         let local_insert_model_name = AsLocalInsertModel(self.0).get_model_name();
         let insert_model_name = AsInsertModel(&self.0.reference_table).get_model_name();
         let full_model_name = AsFullModel(&self.0.reference_table).get_model_name();
         let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
+        let client_metadata_model_name = AsTableMetadata(&self.0.reference_table).get_struct_name();
+        let client_metadata_column_name = &self
+            .0
+            .reference_table
+            .sync_metadata_columns
+            .client_column_sync_metadata
+            .ident;
+        // The default strategy is already applied when converting to the insert model
+        let id_override = match self.0.reference_table.sync_metadata_columns.id.id_strategy {
+            IdStrategy::Default => quote!(),
+            _ => {
+                let id_column_name = &self.0.reference_table.sync_metadata_columns.id.ident;
+                let new_id = AsNewId(&self.0.reference_table);
+                quote!(insert_value.#id_column_name = #new_id;)
+            }
+        };
         tokens.extend(quote!(
             pub fn #function_name(insert_value: #local_insert_model_name) -> carburetor::error::Result<#full_model_name> {
-                use diesel::{RunQueryDsl, Connection};
+                carburetor::helpers::with_connection(|conn| #with_connection_function_name(conn, insert_value))
+            }
+
+            pub fn #with_connection_function_name(
+                conn: &mut diesel::SqliteConnection,
+                insert_value: #local_insert_model_name,
+            ) -> carburetor::error::Result<#full_model_name> {
+                use diesel::RunQueryDsl;
+                let mut insert_value = #insert_model_name::from(insert_value);
+                #id_override
+                insert_value.#client_metadata_column_name = carburetor::serde_json::Value::try_from(
+                    carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#client_metadata_model_name> {
+                        insert_time: Some(carburetor::helpers::clock::now(conn)?),
+                        ..Default::default()
+                    },
+                )?;
                 Ok(
                     diesel::insert_into(#table_name::table)
-                        .values(#insert_model_name::from(insert_value))
-                        .get_result(&mut carburetor::helpers::get_connection()?)
-                        .map_err(|e| carburetor::error::Error::Unhandled {
-                            message: "record insertion failed".to_string(),
-                            source: e.into(),
-                    })?
+                        .values(insert_value)
+                        .get_result(conn)
+                        .map_err(|e| carburetor::error::Error::from_diesel("record insertion failed", e))?
                 )
             }
         ));
@@ -49,39 +79,77 @@ impl<'a> AsLocalInsertFunction<'a> {
     fn get_function_name(&self) -> Ident {
         format_ident!("insert_{}", self.0.reference_table.ident)
     }
+
+    fn get_with_connection_function_name(&self) -> Ident {
+        format_ident!("insert_{}_with_connection", self.0.reference_table.ident)
+    }
 }
 
-struct AsLocalUpdateFunction<'a>(&'a SyncGroupTableConfig);
+struct AsLocalUpdateFunction<'a>(&'a CarburetorSyncGroup, &'a SyncGroupTableConfig);
 
 impl<'a> ToTokens for AsLocalUpdateFunction<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let function_name = self.get_function_name();
-        let update_model_name = AsLocalUpdateModel(self.0).get_model_name();
-        let changeset_model_name = AsChangesetModel(&self.0.reference_table).get_model_name();
-        let full_model_name = AsFullModel(&self.0.reference_table).get_model_name();
-        let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
-        let id_column_name = &self.0.reference_table.sync_metadata_columns.id.ident;
+        let with_connection_function_name = self.get_with_connection_function_name();
+        let update_model_name = AsLocalUpdateModel(self.1).get_model_name();
+        let changeset_model_name = AsChangesetModel(&self.1.reference_table).get_model_name();
+        let full_model_name = AsFullModel(&self.1.reference_table).get_model_name();
+        let table_name = AsSchemaTable(&self.1.reference_table).get_table_name();
+        let id_column_name = &self.1.reference_table.sync_metadata_columns.id.ident;
         let dirty_column_name = &self
-            .0
+            .1
             .reference_table
             .sync_metadata_columns
             .dirty_flag
             .ident;
-        let client_metadata_model_name = AsTableMetadata(&self.0.reference_table).get_struct_name();
+        let client_metadata_model_name = AsTableMetadata(&self.1.reference_table).get_struct_name();
+        let sync_group_name = self.0.name.to_string();
+        let repair_function_name =
+            AsRepairSyncMetadataFunction(&self.1.reference_table).get_function_name();
         let client_metadata_column_name = &self
-            .0
+            .1
             .reference_table
             .sync_metadata_columns
             .client_column_sync_metadata
             .ident;
+        let last_synced_at_column_name = &self
+            .1
+            .reference_table
+            .sync_metadata_columns
+            .last_synced_at
+            .ident;
         let check_data_column_change = &self
-            .0
+            .1
             .reference_table
             .columns
             .iter()
             .filter_map(|x| {
                 let column_name = &x.ident;
-                if x.column_type == CarburetorColumnType::Data && !x.is_immutable {
+                if x.merge_strategy == MergeStrategy::Counter {
+                    Some(quote! {
+                        if let Some(value) = changeset.#column_name {
+                            carburetor::helpers::counter::record_increment(
+                                &mut new_metadata.data.get_or_insert_default().#column_name,
+                                existing_item.#column_name,
+                                value,
+                                now,
+                                existing_item.#last_synced_at_column_name,
+                            );
+                        }
+                    })
+                } else if x.merge_strategy == MergeStrategy::JsonKeys {
+                    Some(quote! {
+                        if let Some(ref value) = changeset.#column_name {
+                            carburetor::helpers::json_keys::mark_dirty_keys(
+                                &mut new_metadata.data.get_or_insert_default().#column_name,
+                                &existing_item.#column_name,
+                                value,
+                                now,
+                            )
+                            .map_err(|e| diesel::result::Error::SerializationError(e.into()))?;
+                        }
+                    })
+                } else if x.column_type == CarburetorColumnType::Data && !x.is_immutable {
                     Some(quote! {
                         if changeset.#column_name.is_some() {
                             new_metadata
@@ -89,7 +157,7 @@ impl<'a> ToTokens for AsLocalUpdateFunction<'a> {
                                 .get_or_insert_default()
                                 .#column_name
                                 .get_or_insert_default()
-                                .dirty_at = Some(carburetor::helpers::get_utc_now());
+                                .dirty_at = Some(now);
                         }
                     })
                 } else {
@@ -97,39 +165,61 @@ impl<'a> ToTokens for AsLocalUpdateFunction<'a> {
                 }
             })
             .collect::<Vec<_>>();
+        // Tables without mutable data columns have no dirty_at to record
+        let clock_now = if check_data_column_change.is_empty() {
+            quote! {}
+        } else {
+            quote! { let now = carburetor::helpers::clock::now(conn)?; }
+        };
         tokens.extend(quote!(
             pub fn #function_name(update_value: #update_model_name) -> carburetor::error::Result<#full_model_name> {
+                carburetor::helpers::with_connection(|conn| #with_connection_function_name(conn, update_value))
+            }
+
+            pub fn #with_connection_function_name(
+                conn: &mut diesel::SqliteConnection,
+                update_value: #update_model_name,
+            ) -> carburetor::error::Result<#full_model_name> {
                 use diesel::{RunQueryDsl, Connection, QueryDsl, SelectableHelper};
                 let mut changeset = #changeset_model_name::from(update_value);
                 let changeset_id = changeset.#id_column_name.clone();
-                let mut conn = carburetor::helpers::get_connection()?;
-                Ok(
-                    conn.immediate_transaction(|conn| -> Result<#full_model_name, diesel::result::Error> {
-                        let existing_item = #table_name::table
-                            .select(#full_model_name::as_select())
-                            .find(&changeset.#id_column_name)
-                            .first(conn)?;
-
-                        if existing_item.#dirty_column_name.is_none() {
-                            changeset.#dirty_column_name = Some(
-                                Some(carburetor::helpers::client_sync_metadata::DirtyFlag::Update.to_string())
-                            );
+                let map_error = |e| carburetor::error::Error::from_diesel("error has occurred in diesel while attempting to update record", e);
+                conn.immediate_transaction(|conn| -> carburetor::error::Result<#full_model_name> {
+                    // Read within the transaction, so that changes are recorded in the order
+                    // they are written
+                    #clock_now
+                    let mut existing_item = #table_name::table
+                        .select(#full_model_name::as_select())
+                        .find(&changeset.#id_column_name)
+                        .first(conn)
+                        .map_err(map_error)?;
+                    let mut new_metadata = match carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#client_metadata_model_name>::try_from(existing_item.#client_metadata_column_name.clone()) {
+                        Ok(metadata) => metadata,
+                        Err(_) => {
+                            // The change is recorded on top of the repaired record
+                            #repair_function_name(conn, #sync_group_name, &existing_item, false)?;
+                            existing_item = #table_name::table
+                                .select(#full_model_name::as_select())
+                                .find(&changeset.#id_column_name)
+                                .first(conn)
+                                .map_err(map_error)?;
+                            carburetor::helpers::client_sync_metadata::ClientSyncMetadata::try_from(existing_item.#client_metadata_column_name.clone())?
                         }
+                    };
 
-                        let mut new_metadata: carburetor::helpers::client_sync_metadata::ClientSyncMetadata<#client_metadata_model_name> = carburetor::serde_json::from_value(existing_item.#client_metadata_column_name).unwrap_or_default();
-                        #(#check_data_column_change)*
-                        changeset.#client_metadata_column_name = Some(new_metadata.into());
-                        Ok(
-                            diesel::update(#table_name::table.find(changeset_id))
-                                .set(changeset)
-                                .get_result(conn)?
-                        )
-                    })
-                    .map_err(|e| carburetor::error::Error::Unhandled {
-                        message: "error has occurred in diesel while attempting to update record".to_string(),
-                        source: e.into(),
-                    })?
-                )
+                    if existing_item.#dirty_column_name.is_none() {
+                        changeset.#dirty_column_name = Some(
+                            Some(carburetor::helpers::client_sync_metadata::DirtyFlag::Update.to_string())
+                        );
+                    }
+
+                    #(#check_data_column_change)*
+                    changeset.#client_metadata_column_name = Some(carburetor::serde_json::to_value(new_metadata)?);
+                    diesel::update(#table_name::table.find(changeset_id))
+                        .set(changeset)
+                        .get_result(conn)
+                        .map_err(map_error)
+                })
             }
         ));
     }
@@ -137,18 +227,23 @@ impl<'a> ToTokens for AsLocalUpdateFunction<'a> {
 
 impl<'a> AsLocalUpdateFunction<'a> {
     fn get_function_name(&self) -> Ident {
-        format_ident!("update_{}", self.0.reference_table.ident)
+        format_ident!("update_{}", self.1.reference_table.ident)
+    }
+
+    fn get_with_connection_function_name(&self) -> Ident {
+        format_ident!("update_{}_with_connection", self.1.reference_table.ident)
     }
 }
 
-struct AsLocalDeleteFunction<'a>(&'a SyncGroupTableConfig);
+struct AsLocalDeleteFunction<'a>(&'a CarburetorSyncGroup, &'a SyncGroupTableConfig);
 
 impl<'a> ToTokens for AsLocalDeleteFunction<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let function_name = self.get_function_name();
-        let id_type = AsModelType(&self.0.reference_table.sync_metadata_columns.id.diesel_type);
-        let changeset_model_name = AsChangesetModel(&self.0.reference_table).get_model_name();
-        let changeset_fields = self.0.reference_table.columns.iter().map(|x| {
+        let with_connection_function_name = self.get_with_connection_function_name();
+        let id_type = AsModelType(&self.1.reference_table.sync_metadata_columns.id.diesel_type);
+        let changeset_model_name = AsChangesetModel(&self.1.reference_table).get_model_name();
+        let changeset_fields = self.1.reference_table.columns.iter().map(|x| {
             let field_name = &x.ident;
             match &x.column_type {
                 CarburetorColumnType::Id => quote!(#field_name: delete_id),
@@ -157,70 +252,89 @@ impl<'a> ToTokens for AsLocalDeleteFunction<'a> {
             }
         });
 
-        let full_model_name = AsFullModel(&self.0.reference_table).get_model_name();
-        let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
+        let full_model_name = AsFullModel(&self.1.reference_table).get_model_name();
+        let table_name = AsSchemaTable(&self.1.reference_table).get_table_name();
 
-        let id_column_name = &self.0.reference_table.sync_metadata_columns.id.ident;
+        let id_column_name = &self.1.reference_table.sync_metadata_columns.id.ident;
         let delete_column_name = &self
-            .0
+            .1
             .reference_table
             .sync_metadata_columns
             .is_deleted
             .ident;
         let dirty_column_name = &self
-            .0
+            .1
             .reference_table
             .sync_metadata_columns
             .dirty_flag
             .ident;
-        let client_metadata_model_name = AsTableMetadata(&self.0.reference_table).get_struct_name();
+        let client_metadata_model_name = AsTableMetadata(&self.1.reference_table).get_struct_name();
+        let sync_group_name = self.0.name.to_string();
+        let repair_function_name =
+            AsRepairSyncMetadataFunction(&self.1.reference_table).get_function_name();
         let client_metadata_column_name = &self
-            .0
+            .1
             .reference_table
             .sync_metadata_columns
             .client_column_sync_metadata
             .ident;
         tokens.extend(quote!(
             pub fn #function_name(delete_id: #id_type) -> carburetor::error::Result<#full_model_name> {
+                carburetor::helpers::with_connection(|conn| #with_connection_function_name(conn, delete_id))
+            }
+
+            pub fn #with_connection_function_name(
+                conn: &mut diesel::SqliteConnection,
+                delete_id: #id_type,
+            ) -> carburetor::error::Result<#full_model_name> {
                 use diesel::{RunQueryDsl, Connection, QueryDsl, SelectableHelper};
                 let mut changeset = #changeset_model_name {
                     #(#changeset_fields,)*
                 };
                 let changeset_id = changeset.#id_column_name.clone();
-                let mut conn = carburetor::helpers::get_connection()?;
-                Ok(
-                    conn.immediate_transaction(|conn| -> Result<#full_model_name, diesel::result::Error> {
-                        let existing_item = #table_name::table
-                            .select(#full_model_name::as_select())
-                            .find(&changeset.#id_column_name)
-                            .first(conn)?;
-
-                        if existing_item.#dirty_column_name.is_none() {
-                            changeset.#dirty_column_name = Some(
-                                Some(carburetor::helpers::client_sync_metadata::DirtyFlag::Update.to_string())
-                            );
+                let map_error = |e| carburetor::error::Error::from_diesel("error has occurred in diesel while attempting to delete record", e);
+                conn.immediate_transaction(|conn| -> carburetor::error::Result<#full_model_name> {
+                    // Read within the transaction, so that changes are recorded in the order
+                    // they are written
+                    let now = carburetor::helpers::clock::now(conn)?;
+                    let mut existing_item = #table_name::table
+                        .select(#full_model_name::as_select())
+                        .find(&changeset.#id_column_name)
+                        .first(conn)
+                        .map_err(map_error)?;
+                    let mut new_metadata = match carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#client_metadata_model_name>::try_from(existing_item.#client_metadata_column_name.clone()) {
+                        Ok(metadata) => metadata,
+                        Err(_) => {
+                            // The change is recorded on top of the repaired record
+                            #repair_function_name(conn, #sync_group_name, &existing_item, false)?;
+                            existing_item = #table_name::table
+                                .select(#full_model_name::as_select())
+                                .find(&changeset.#id_column_name)
+                                .first(conn)
+                                .map_err(map_error)?;
+                            carburetor::helpers::client_sync_metadata::ClientSyncMetadata::try_from(existing_item.#client_metadata_column_name.clone())?
                         }
+                    };
 
-                        let mut new_metadata: carburetor::helpers::client_sync_metadata::ClientSyncMetadata<#client_metadata_model_name> = carburetor::serde_json::from_value(existing_item.#client_metadata_column_name).unwrap_or_default();
-                        new_metadata
-                            .data
-                            .get_or_insert_default()
-                            .#delete_column_name
-                            .get_or_insert_default()
-                            .dirty_at = Some(carburetor::helpers::get_utc_now());
-                        changeset.#client_metadata_column_name = Some(new_metadata.into());
-
-                        Ok(
-                            diesel::update(#table_name::table.find(changeset_id))
-                                .set(changeset)
-                                .get_result(conn)?
-                        )
-                    })
-                    .map_err(|e| carburetor::error::Error::Unhandled {
-                        message: "error has occurred in diesel while attempting to delete record".to_string(),
-                        source: e.into(),
-                    })?
-                )
+                    if existing_item.#dirty_column_name.is_none() {
+                        changeset.#dirty_column_name = Some(
+                            Some(carburetor::helpers::client_sync_metadata::DirtyFlag::Update.to_string())
+                        );
+                    }
+
+                    new_metadata
+                        .data
+                        .get_or_insert_default()
+                        .#delete_column_name
+                        .get_or_insert_default()
+                        .dirty_at = Some(now);
+                    changeset.#client_metadata_column_name = Some(carburetor::serde_json::to_value(new_metadata)?);
+
+                    diesel::update(#table_name::table.find(changeset_id))
+                        .set(changeset)
+                        .get_result(conn)
+                        .map_err(map_error)
+                })
             }
         ));
     }
@@ -228,7 +342,96 @@ impl<'a> ToTokens for AsLocalDeleteFunction<'a> {
 
 impl<'a> AsLocalDeleteFunction<'a> {
     fn get_function_name(&self) -> Ident {
-        format_ident!("delete_{}", self.0.reference_table.ident)
+        format_ident!("delete_{}", self.1.reference_table.ident)
+    }
+
+    fn get_with_connection_function_name(&self) -> Ident {
+        format_ident!("delete_{}_with_connection", self.1.reference_table.ident)
+    }
+}
+
+struct AsLocalPurgeDeletedFunction<'a>(&'a SyncGroupTableConfig);
+
+impl<'a> ToTokens for AsLocalPurgeDeletedFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let function_name = self.get_function_name();
+        let with_connection_function_name = self.get_with_connection_function_name();
+        let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
+        let sync_metadata_columns = &self.0.reference_table.sync_metadata_columns;
+        let is_deleted_column_name = &sync_metadata_columns.is_deleted.ident;
+        let dirty_column_name = &sync_metadata_columns.dirty_flag.ident;
+        let last_synced_at_column_name = &sync_metadata_columns.last_synced_at.ident;
+        tokens.extend(quote!(
+            /// Physically removes deleted rows whose deletion has been acknowledged by the backend,
+            /// and returns the number of rows removed.
+            pub fn #function_name() -> carburetor::error::Result<usize> {
+                carburetor::helpers::with_connection(#with_connection_function_name)
+            }
+
+            pub fn #with_connection_function_name(
+                conn: &mut diesel::SqliteConnection,
+            ) -> carburetor::error::Result<usize> {
+                use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+                diesel::delete(
+                    #table_name::table
+                        .filter(#table_name::#is_deleted_column_name.eq(true))
+                        .filter(#table_name::#dirty_column_name.is_null())
+                        .filter(#table_name::#last_synced_at_column_name.is_not_null()),
+                )
+                .execute(conn)
+                .map_err(|e| carburetor::error::Error::from_diesel("error has occurred in diesel while attempting to purge deleted records", e))
+            }
+        ));
+    }
+}
+
+impl<'a> AsLocalPurgeDeletedFunction<'a> {
+    fn get_function_name(&self) -> Ident {
+        format_ident!("purge_deleted_{}", self.0.reference_table.plural_ident)
+    }
+
+    fn get_with_connection_function_name(&self) -> Ident {
+        format_ident!(
+            "purge_deleted_{}_with_connection",
+            self.0.reference_table.plural_ident
+        )
+    }
+}
+
+struct AsPurgeSyncedDeletionsFunction<'a>(&'a CarburetorSyncGroup);
+
+impl<'a> ToTokens for AsPurgeSyncedDeletionsFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+        let purge_table_function_names = self
+            .0
+            .table_configs
+            .iter()
+            .map(|x| AsLocalPurgeDeletedFunction(x).get_with_connection_function_name())
+            .collect::<Vec<_>>();
+        tokens.extend(quote!(
+            /// Purges the acknowledged deletions of every table in the group, and returns the number
+            /// of rows removed.
+            pub fn purge_synced_deletions() -> carburetor::error::Result<usize> {
+                carburetor::helpers::with_connection(purge_synced_deletions_with_connection)
+            }
+
+            pub fn purge_synced_deletions_with_connection(
+                conn: &mut diesel::SqliteConnection,
+            ) -> carburetor::error::Result<usize> {
+                use diesel::Connection;
+                conn.immediate_transaction(|conn| {
+                    Ok(0 #(+ #purge_table_function_names(conn)?)*)
+                })
+            }
+
+            impl #handle_name {
+                pub fn purge_synced_deletions(&self) -> carburetor::error::Result<usize> {
+                    self.carburetor
+                        .with_connection(purge_synced_deletions_with_connection)
+                }
+            }
+        ));
     }
 }
 
@@ -261,14 +464,88 @@ impl<'a> AsActiveTableFunction<'a> {
     }
 }
 
+/// Methods of the sync group handle wrapping the insert/update/delete/purge functions of one table.
+struct AsLocalOperationMethods<'a>(&'a CarburetorSyncGroup, &'a SyncGroupTableConfig);
+
+impl<'a> ToTokens for AsLocalOperationMethods<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let insert_function = AsLocalInsertFunction(self.1);
+        let insert_function_name = insert_function.get_function_name();
+        let insert_with_connection_function_name =
+            insert_function.get_with_connection_function_name();
+        let update_function = AsLocalUpdateFunction(self.0, self.1);
+        let update_function_name = update_function.get_function_name();
+        let update_with_connection_function_name =
+            update_function.get_with_connection_function_name();
+        let delete_function = AsLocalDeleteFunction(self.0, self.1);
+        let delete_function_name = delete_function.get_function_name();
+        let delete_with_connection_function_name =
+            delete_function.get_with_connection_function_name();
+        let purge_function = AsLocalPurgeDeletedFunction(self.1);
+        let purge_function_name = purge_function.get_function_name();
+        let purge_with_connection_function_name =
+            purge_function.get_with_connection_function_name();
+
+        let local_insert_model_name = AsLocalInsertModel(self.1).get_model_name();
+        let update_model_name = AsLocalUpdateModel(self.1).get_model_name();
+        let full_model_name = AsFullModel(&self.1.reference_table).get_model_name();
+        let id_type = AsModelType(&self.1.reference_table.sync_metadata_columns.id.diesel_type);
+
+        tokens.extend(quote! {
+            pub fn #insert_function_name(
+                &self,
+                insert_value: #local_insert_model_name,
+            ) -> carburetor::error::Result<#full_model_name> {
+                self.carburetor
+                    .with_connection(|conn| #insert_with_connection_function_name(conn, insert_value))
+            }
+
+            pub fn #update_function_name(
+                &self,
+                update_value: #update_model_name,
+            ) -> carburetor::error::Result<#full_model_name> {
+                self.carburetor
+                    .with_connection(|conn| #update_with_connection_function_name(conn, update_value))
+            }
+
+            pub fn #delete_function_name(
+                &self,
+                delete_id: #id_type,
+            ) -> carburetor::error::Result<#full_model_name> {
+                self.carburetor
+                    .with_connection(|conn| #delete_with_connection_function_name(conn, delete_id))
+            }
+
+            pub fn #purge_function_name(&self) -> carburetor::error::Result<usize> {
+                self.carburetor
+                    .with_connection(#purge_with_connection_function_name)
+            }
+        });
+    }
+}
+
 pub fn generate_local_operation_functions(
     tokens: &mut TokenStream,
     sync_group: &CarburetorSyncGroup,
 ) {
     sync_group.table_configs.iter().for_each(|x| {
         tokens.extend(AsLocalInsertFunction(x).to_token_stream());
-        tokens.extend(AsLocalUpdateFunction(x).to_token_stream());
-        tokens.extend(AsLocalDeleteFunction(x).to_token_stream());
+        tokens.extend(AsLocalUpdateFunction(sync_group, x).to_token_stream());
+        tokens.extend(AsLocalDeleteFunction(sync_group, x).to_token_stream());
+        tokens.extend(AsLocalPurgeDeletedFunction(x).to_token_stream());
         tokens.extend(AsActiveTableFunction(x).to_token_stream());
-    })
+    });
+    tokens.extend(AsPurgeSyncedDeletionsFunction(sync_group).to_token_stream());
+
+    let handle_name = AsSyncGroupHandle(sync_group).get_model_name();
+    let methods = sync_group
+        .table_configs
+        .iter()
+        .map(|x| AsLocalOperationMethods(sync_group, x))
+        .collect::<Vec<_>>();
+    tokens.extend(quote! {
+        impl #handle_name {
+            #(#methods)*
+        }
+    });
 }
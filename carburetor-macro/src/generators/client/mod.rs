@@ -1,3 +1,4 @@
 pub(crate) mod local_operations;
 pub(crate) mod models;
+pub(crate) mod sync_driver;
 pub(crate) mod sync_local_db;
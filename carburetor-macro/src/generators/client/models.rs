@@ -1,16 +1,66 @@
+use std::rc::Rc;
+
 use heck::ToUpperCamelCase;
 use proc_macro2::TokenStream;
-use quote::{ToTokens, quote};
+use quote::{ToTokens, format_ident, quote};
 use syn::Ident;
 
+use crate::generators::diesel::{
+    models::{AsChangesetModel, AsFullModel, AsInsertModel},
+    schema::AsSchemaTable,
+};
 use crate::parsers::{
-    sync_group::CarburetorSyncGroup,
+    sync_group::{CarburetorSyncGroup, SyncGroupTableConfig},
     table::{
         CarburetorTable,
-        column::{CarburetorColumn, CarburetorColumnType, ColumnScope},
+        column::{CarburetorColumn, CarburetorColumnType, ColumnScope, IdStrategy},
     },
 };
 
+/// Expression generating a new ID for a record of the table, following the `#[id(...)]` strategy.
+/// It may use `conn` and `?`, so it has to be placed where a connection is in scope and a
+/// `carburetor::error::Result` is returned.
+pub struct AsNewId<'a>(pub &'a CarburetorTable);
+
+impl<'a> ToTokens for AsNewId<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match &self.0.sync_metadata_columns.id.id_strategy {
+            IdStrategy::Default => {
+                let prefix = self.0.ident.to_string();
+                quote!(carburetor::helpers::generate_id(#prefix.to_string()))
+            }
+            IdStrategy::UuidV4 => quote!(carburetor::helpers::id::uuid_v4()),
+            IdStrategy::UuidV7 => quote!(carburetor::helpers::id::uuid_v7()),
+            IdStrategy::Ulid => quote!(carburetor::helpers::id::ulid()),
+            IdStrategy::DeviceCounter => quote!(carburetor::helpers::id::device_counter(conn)?),
+            IdStrategy::Custom(path) => quote!(#path()),
+        });
+    }
+}
+
+/// `carburetor::helpers::carburetor_offset::OffsetKey` of a table in the sync group.
+pub struct AsOffsetKey<'a> {
+    pub sync_group: &'a CarburetorSyncGroup,
+    pub table_config: &'a SyncGroupTableConfig,
+    /// `&str` expression of the restriction fingerprint returned by the backend.
+    pub restriction: TokenStream,
+}
+
+impl<'a> ToTokens for AsOffsetKey<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let sync_group = self.sync_group.name.to_string();
+        let table_name = self.table_config.reference_table.plural_ident.to_string();
+        let restriction = &self.restriction;
+        tokens.extend(quote! {
+            carburetor::helpers::carburetor_offset::OffsetKey {
+                sync_group: #sync_group,
+                table_name: #table_name,
+                restriction: #restriction,
+            }
+        });
+    }
+}
+
 struct AsTableMetadataField<'a>(&'a CarburetorColumn);
 
 impl<'a> ToTokens for AsTableMetadataField<'a> {
@@ -70,13 +120,119 @@ impl<'a> AsTableMetadata<'a> {
     }
 }
 
-pub(crate) fn generate_client_models(tokens: &mut TokenStream, sync_group: &CarburetorSyncGroup) {
-    let table_metadata = sync_group
-        .table_configs
+/// `repair_<table>_sync_metadata(conn, sync_group, record, is_on_backend)` resetting the sync
+/// metadata of a record once it cannot be read anymore.
+pub struct AsRepairSyncMetadataFunction<'a>(pub &'a CarburetorTable);
+
+impl<'a> AsRepairSyncMetadataFunction<'a> {
+    pub fn get_function_name(&self) -> Ident {
+        format_ident!("repair_{}_sync_metadata", self.0.ident)
+    }
+}
+
+impl<'a> ToTokens for AsRepairSyncMetadataFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let function_name = self.get_function_name();
+        let table_name = AsSchemaTable(self.0).get_table_name();
+        let table_name_str = self.0.plural_ident.to_string();
+        let full_model_name = AsFullModel(self.0).get_model_name();
+        let metadata_name = AsTableMetadata(self.0).get_struct_name();
+        let columns = &self.0.sync_metadata_columns;
+        let id_column = &columns.id.ident;
+        let dirty_flag_column = &columns.dirty_flag.ident;
+        let last_synced_at_column = &columns.last_synced_at.ident;
+        let client_metadata_column = &columns.client_column_sync_metadata.ident;
+
+        tokens.extend(quote! {
+            /// Resets the sync metadata of `record` once it cannot be read, e.g. after the database
+            /// file got corrupted. Without it, local changes cannot be told apart from the values of
+            /// the backend, so they are given up:
+            /// - a record on the backend, either known by `is_on_backend` or told by its dirty flag,
+            ///   is marked clean and never synced, and `sync_group` downloads its table again so
+            ///   that the record is replaced by the one of the backend
+            /// - an insert the backend does not have yet is uploaded again as a whole
+            pub fn #function_name(
+                conn: &mut diesel::SqliteConnection,
+                sync_group: &str,
+                record: &#full_model_name,
+                is_on_backend: bool,
+            ) -> carburetor::error::Result<()> {
+                use carburetor::helpers::client_sync_metadata::{ClientSyncMetadata, DirtyFlag};
+                use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+                let is_local_insert = record.#dirty_flag_column == Some(DirtyFlag::Insert.to_string())
+                    || record.#dirty_flag_column == Some(DirtyFlag::Rejected.to_string());
+                if !is_on_backend && record.#last_synced_at_column.is_none() && is_local_insert {
+                    let metadata = ClientSyncMetadata::<#metadata_name> {
+                        insert_time: Some(carburetor::helpers::clock::now(conn)?),
+                        ..Default::default()
+                    };
+                    diesel::update(#table_name::table.find(&record.#id_column))
+                        .set(#table_name::#client_metadata_column.eq(carburetor::serde_json::Value::try_from(metadata)?))
+                        .execute(conn)?;
+                    return Ok(());
+                }
+
+                diesel::update(#table_name::table.find(&record.#id_column))
+                    .set((
+                        #table_name::#client_metadata_column.eq(carburetor::serde_json::Value::try_from(
+                            ClientSyncMetadata::<#metadata_name>::default(),
+                        )?),
+                        #table_name::#dirty_flag_column.eq(None::<String>),
+                        #table_name::#last_synced_at_column.eq(None::<carburetor::chrono::DateTimeUtc>),
+                    ))
+                    .execute(conn)?;
+                carburetor::helpers::carburetor_offset::reset_offset(conn, sync_group, #table_name_str)
+            }
+        });
+    }
+}
+
+pub(crate) fn generate_client_models(tokens: &mut TokenStream, tables: &[Rc<CarburetorTable>]) {
+    let table_metadata = tables
+        .iter()
+        .map(|x| AsTableMetadata(x))
+        .collect::<Vec<_>>();
+    let repair_functions = tables
         .iter()
-        .map(|x| AsTableMetadata(&x.reference_table))
+        .map(|x| AsRepairSyncMetadataFunction(x))
         .collect::<Vec<_>>();
     tokens.extend(quote! {
         #(#table_metadata)*
+        #(#repair_functions)*
+    })
+}
+
+/// Brings the schema and models of the tables of the sync group into its module. They are shared
+/// by every group syncing the table, so that the rows are stored once.
+pub(crate) fn generate_shared_table_imports(
+    tokens: &mut TokenStream,
+    sync_group: &CarburetorSyncGroup,
+) {
+    let imports = sync_group
+        .table_configs
+        .iter()
+        .map(|x| {
+            let table = &x.reference_table;
+            let table_name = AsSchemaTable(table).get_table_name();
+            let full_model_name = AsFullModel(table).get_model_name();
+            let changeset_model_name = AsChangesetModel(table).get_model_name();
+            let insert_model_name = AsInsertModel(table).get_model_name();
+            let metadata_name = AsTableMetadata(table).get_struct_name();
+            let repair_function_name = AsRepairSyncMetadataFunction(table).get_function_name();
+            quote! {
+                pub use super::{
+                    #table_name,
+                    #full_model_name,
+                    #changeset_model_name,
+                    #insert_model_name,
+                    #metadata_name,
+                    #repair_function_name,
+                };
+            }
+        })
+        .collect::<Vec<_>>();
+    tokens.extend(quote! {
+        #(#imports)*
     })
 }
@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+
+use crate::{
+    generators::{
+        download::models::{AsDownloadRequestModel, AsDownloadResponseModel},
+        handle::models::AsSyncGroupHandle,
+        upload::models::{AsUploadRequest, AsUploadResponseModel},
+    },
+    parsers::sync_group::CarburetorSyncGroup,
+};
+
+/// `sync(transport)` running a full sync of the group: the local changes are uploaded first, so
+/// that the download which follows already carries the rows as applied by the backend.
+struct AsSyncFunction<'a>(&'a CarburetorSyncGroup);
+
+impl<'a> ToTokens for AsSyncFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let sync_group_name = self.0.name.to_string();
+        let download_request_model_name = AsDownloadRequestModel(self.0).get_model_name();
+        let download_response_model_name = AsDownloadResponseModel(self.0).get_model_name();
+        let upload_request_model_name = AsUploadRequest(self.0).get_model_name();
+        let upload_response_model_name = AsUploadResponseModel(self.0).get_model_name();
+        let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+
+        let fields = self
+            .0
+            .table_configs
+            .iter()
+            .map(|x| &x.reference_table.ident)
+            .collect::<Vec<_>>();
+
+        let sync_body = |retrieve_upload: TokenStream,
+                         store_upload: TokenStream,
+                         download_until_caught_up: TokenStream| {
+            quote! {
+                let mut summary = carburetor::client::SyncSummary::default();
+
+                let (cutoff_at, upload_request) = #retrieve_upload?;
+                #(summary.sent += upload_request.#fields.len();)*
+                if summary.sent > 0 {
+                    let upload_response = transport
+                        .upload::<#upload_request_model_name, #upload_response_model_name>(
+                            #sync_group_name,
+                            &upload_request,
+                        )
+                        .await?;
+                    #(summary.rejected += upload_response.#fields.iter().filter(|x| x.is_err()).count();)*
+                    #store_upload?;
+                }
+
+                let received = std::cell::Cell::new(0);
+                let fetch = |request: #download_request_model_name| {
+                    let received = &received;
+                    async move {
+                        let download_response = transport
+                            .download::<#download_request_model_name, #download_response_model_name>(
+                                #sync_group_name,
+                                &Some(request),
+                            )
+                            .await?;
+                        received.set(received.get() #(+ download_response.#fields.data.len())*);
+                        Ok(download_response)
+                    }
+                };
+                #download_until_caught_up.await?;
+                summary.received = received.get();
+                Ok(summary)
+            }
+        };
+        let global_body = sync_body(
+            quote!(retrieve_upload_request()),
+            quote!(store_upload_response(cutoff_at, upload_response)),
+            quote!(download_until_caught_up(None, fetch)),
+        );
+        let with_connection_body = sync_body(
+            quote!(retrieve_upload_request_with_connection(conn, retry_policy)),
+            quote!(store_upload_response_with_connection(
+                conn,
+                cutoff_at,
+                upload_response
+            )),
+            quote!(download_until_caught_up_with_connection(conn, None, fetch)),
+        );
+        let handle_body = sync_body(
+            quote!(self.retrieve_upload_request()),
+            quote!(self.store_upload_response(cutoff_at, upload_response)),
+            quote!(self.download_until_caught_up(None, fetch)),
+        );
+
+        tokens.extend(quote! {
+            /// Uploads the local changes of the group with `transport`, then downloads with
+            /// `download_until_caught_up`. The upload is skipped when there is nothing to send.
+            pub async fn sync(
+                transport: &impl carburetor::client::Transport,
+            ) -> carburetor::error::Result<carburetor::client::SyncSummary> {
+                #global_body
+            }
+
+            pub async fn sync_with_connection(
+                conn: &mut diesel::SqliteConnection,
+                retry_policy: &carburetor::helpers::upload_failure::UploadRetryPolicy,
+                transport: &impl carburetor::client::Transport,
+            ) -> carburetor::error::Result<carburetor::client::SyncSummary> {
+                #with_connection_body
+            }
+
+            impl #handle_name {
+                pub async fn sync(
+                    &self,
+                    transport: &impl carburetor::client::Transport,
+                ) -> carburetor::error::Result<carburetor::client::SyncSummary> {
+                    #handle_body
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn generate_sync_function(tokens: &mut TokenStream, sync_group: &CarburetorSyncGroup) {
+    tokens.extend(AsSyncFunction(sync_group).to_token_stream());
+}
@@ -4,18 +4,19 @@ use syn::Ident;
 
 use crate::{
     generators::{
-        client::models::AsTableMetadata,
+        client::models::{AsOffsetKey, AsRepairSyncMetadataFunction, AsTableMetadata},
         diesel::{
             models::{AsChangesetModel, AsFullModel, AsInsertModel},
             schema::AsSchemaTable,
         },
         download::models::{AsDownloadResponseModel, AsDownloadResponseTableModel},
+        handle::models::AsSyncGroupHandle,
     },
     parsers::{
         sync_group::CarburetorSyncGroup,
         table::{
             CarburetorTable,
-            column::{CarburetorColumnType, ColumnScope},
+            column::{CarburetorColumnType, ColumnScope, MergeStrategy},
         },
     },
 };
@@ -45,6 +46,7 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
         let changeset_model_name = AsChangesetModel(self.table).get_model_name();
         let id_column_name = &self.table.sync_metadata_columns.id.ident;
         let last_synced_at_column_name = &self.table.sync_metadata_columns.last_synced_at.ident;
+        let is_deleted_column_name = &self.table.sync_metadata_columns.is_deleted.ident;
         let column_sync_metadata_column_name = &self
             .table
             .sync_metadata_columns
@@ -52,6 +54,8 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
             .ident;
 
         let table_metadata_model_name = AsTableMetadata(self.table).get_struct_name();
+        let sync_group_name = self.sync_group.name.to_string();
+        let repair_function_name = AsRepairSyncMetadataFunction(self.table).get_function_name();
 
         let check_dirty_columns = {
             let columns = self.table.columns.clone();
@@ -63,6 +67,37 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
                     //
                     // Non-data columns (id, last_synced_at, etc.) are used to ensure syncing work,
                     // and client-only data will never need to be synced to the server.
+                    (CarburetorColumnType::Data, ColumnScope::Both)
+                        if x.merge_strategy == MergeStrategy::Counter =>
+                    {
+                        // The increments the backend does not have yet are added back
+                        let column_name = &x.ident;
+                        quote! {
+                            if let Some(downloaded) = update_model.#column_name.take() {
+                                update_model.#column_name = Some(carburetor::helpers::counter::merge_download(
+                                    downloaded,
+                                    existing_metadata.data.as_ref().and_then(|x| x.#column_name.as_ref()),
+                                    update_model.#last_synced_at_column_name.flatten(),
+                                ));
+                            }
+                        }
+                    }
+                    (CarburetorColumnType::Data, ColumnScope::Both)
+                        if x.merge_strategy == MergeStrategy::JsonKeys =>
+                    {
+                        // Only the dirty keys keep their local value
+                        let column_name = &x.ident;
+                        quote! {
+                            if let Some(downloaded) = update_model.#column_name.take() {
+                                update_model.#column_name = Some(carburetor::helpers::json_keys::merge_download(
+                                    &existing_item.#column_name,
+                                    downloaded,
+                                    existing_metadata.data.as_ref().and_then(|x| x.#column_name.as_ref()),
+                                    existing_item.#last_synced_at_column_name,
+                                ));
+                            }
+                        }
+                    }
                     (CarburetorColumnType::Data, ColumnScope::Both | ColumnScope::ModOnBackendOnly) => {
                         let column_name = &x.ident;
                         quote! {
@@ -92,14 +127,13 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
             ) -> carburetor::error::Result<()> {
                 use diesel::prelude::*;
                 use carburetor::helpers::client_sync_metadata::ClientSyncMetadata;
-                use carburetor::serde_json::from_value;
 
                 data
                 .into_iter()
                 .map(|data_item| -> carburetor::error::Result<()> {
                     let carburetor::models::DownloadTableResponseData::Update(update_item) = data_item;
                     let table = #table_name::table;
-                    conn.immediate_transaction(|conn| {
+                    conn.immediate_transaction(|conn| -> carburetor::error::Result<()> {
                         let maybe_existing_item = table
                             .select(#full_model_name::as_select())
                             .find(&update_item.#id_column_name)
@@ -112,8 +146,14 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
                                     .#last_synced_at_column_name
                                     .is_some_and(|x| x > existing_item.#last_synced_at_column_name)
                                 {
-                                    let existing_metadata: ClientSyncMetadata<#table_metadata_model_name>;
-                                    existing_metadata = from_value(existing_item.#column_sync_metadata_column_name).unwrap_or_default();
+                                    let existing_metadata = match ClientSyncMetadata::<#table_metadata_model_name>::try_from(existing_item.#column_sync_metadata_column_name.clone()) {
+                                        Ok(existing_metadata) => existing_metadata,
+                                        Err(_) => {
+                                            // The downloaded record replaces the local one as a whole
+                                            #repair_function_name(conn, #sync_group_name, &existing_item, true)?;
+                                            ClientSyncMetadata::default()
+                                        }
+                                    };
 
                                     #(#check_dirty_columns)*
 
@@ -122,6 +162,9 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
                                         .execute(conn)?;
                                 }
                             }
+                            // Deleted rows the client does not have (e.g. already purged) are
+                            // not stored, so that purged rows are not brought back as tombstones
+                            None if update_item.#is_deleted_column_name => {}
                             None => {
                                 diesel::insert_into(table)
                                     .values(#insert_model_name::from(update_item.clone()))
@@ -130,11 +173,6 @@ impl<'a> ToTokens for AsSyncTableToLocalDbFunction<'a> {
                         }
                         Ok(())
                     })
-                    .map_err(|e: diesel::result::Error| carburetor::error::Error::Unhandled {
-                        message: "Diesel error has occurred".to_string(),
-                        source: e.into(),
-                    })?;
-                    Ok(())
                 })
                 .collect::<carburetor::error::Result<Vec<_>>>()?;
 
@@ -149,6 +187,11 @@ pub(crate) fn generate_store_download_response_function(
     sync_group: &CarburetorSyncGroup,
 ) {
     let function_name = Ident::new("store_download_response", sync_group.name.span());
+    let with_connection_function_name = Ident::new(
+        "store_download_response_with_connection",
+        sync_group.name.span(),
+    );
+    let handle_name = AsSyncGroupHandle(sync_group).get_model_name();
     let download_response_model = AsDownloadResponseModel(sync_group);
     let download_response_model_name = download_response_model.get_model_name();
     let sync_table_functions_decl = sync_group
@@ -161,19 +204,66 @@ pub(crate) fn generate_store_download_response_function(
         .collect::<Vec<_>>();
     let call_sync_table_function = sync_table_functions_decl
         .iter()
-        .map(|x| {
+        .zip(&sync_group.table_configs)
+        .map(|(x, table_config)| {
             let call_name = x.get_function_name();
             let field = download_response_model
                 .get_response_field_by_table(x.table)
                 .get_field_name();
-            let table_name = AsSchemaTable(x.table).get_table_name().to_string();
+            let table = AsSchemaTable(x.table).get_table_name();
+            let table_name = table.to_string();
+            let offset_key = AsOffsetKey {
+                sync_group,
+                table_config,
+                restriction: quote!(&restriction),
+            };
+            let dirty_flag_column_name = &x.table.sync_metadata_columns.dirty_flag.ident;
+            let last_synced_at_column_name = &x.table.sync_metadata_columns.last_synced_at.ident;
             quote! {
-                #call_name(&mut conn, download_response.#field.data)?;
-                carburetor::helpers::carburetor_offset::upsert_offset(
-                    &mut conn,
-                    #table_name,
-                    download_response.#field.cutoff_at,
-                )?;
+                if download_response.#field.reset {
+                    // Rows that are not modified locally are downloaded again, which drops those
+                    // deleted on the backend since the last download. Rows last synced after the
+                    // compaction are kept, their deletion is still part of the download.
+                    {
+                        use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
+                        let not_dirty =
+                            #table::table.filter(#table::dsl::#dirty_flag_column_name.is_null());
+                        match download_response.#field.reset_before {
+                            Some(reset_before) => diesel::delete(not_dirty.filter(
+                                #table::dsl::#last_synced_at_column_name
+                                    .lt(reset_before)
+                                    .or(#table::dsl::#last_synced_at_column_name.is_null()),
+                            ))
+                            .execute(conn)?,
+                            None => diesel::delete(not_dirty).execute(conn)?,
+                        };
+                    }
+                    // Every group syncing the table downloads the rows it had again
+                    carburetor::helpers::carburetor_offset::delete_offsets(conn, #table_name)?;
+                    carburetor::helpers::carburetor_offset::delete_cursors(conn, #table_name)?;
+                }
+                carburetor::helpers::clock::observe(conn, download_response.#field.cutoff_at)?;
+                #call_name(conn, download_response.#field.data)?;
+                let restriction = download_response.#field.restriction.unwrap_or_default();
+                // The offset only moves to the cutoff once every page of the table is stored
+                match download_response.#field.cursor {
+                    Some(cursor) if download_response.#field.has_more => {
+                        carburetor::helpers::carburetor_offset::upsert_cursor(
+                            conn,
+                            #offset_key,
+                            cursor,
+                        )?;
+                    }
+                    _ => {
+                        carburetor::helpers::carburetor_offset::upsert_offset_with_xmin(
+                            conn,
+                            #offset_key,
+                            download_response.#field.cutoff_at,
+                            download_response.#field.xmin,
+                        )?;
+                        carburetor::helpers::carburetor_offset::delete_cursor(conn, #offset_key)?;
+                    }
+                }
             }
         })
         .collect::<Vec<_>>();
@@ -181,11 +271,30 @@ pub(crate) fn generate_store_download_response_function(
     tokens.extend(quote! {
         pub fn #function_name(
             download_response: #download_response_model_name,
+        ) -> carburetor::error::Result<()> {
+            carburetor::helpers::with_connection(|conn| {
+                #with_connection_function_name(conn, download_response)
+            })
+        }
+
+        pub fn #with_connection_function_name(
+            conn: &mut diesel::SqliteConnection,
+            download_response: #download_response_model_name,
         ) -> carburetor::error::Result<()> {
             #(#sync_table_functions_decl)*
-            let mut conn = carburetor::helpers::get_connection()?;
             #(#call_sync_table_function)*
             Ok(())
         }
+
+        impl #handle_name {
+            pub fn #function_name(
+                &self,
+                download_response: #download_response_model_name,
+            ) -> carburetor::error::Result<()> {
+                self.carburetor.with_connection(|conn| {
+                    #with_connection_function_name(conn, download_response)
+                })
+            }
+        }
     });
 }
@@ -0,0 +1,166 @@
+use std::rc::Rc;
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+
+use crate::{
+    generators::{
+        diesel::{models::AsModelType, schema::AsSchemaTable},
+        handle::models::AsSyncGroupHandle,
+    },
+    helpers::ExecutionMode,
+    parsers::{sync_group::CarburetorSyncGroup, table::CarburetorTable},
+};
+
+struct AsCompactTombstonesFunction<'a>(&'a [Rc<CarburetorTable>], ExecutionMode);
+
+impl<'a> ToTokens for AsCompactTombstonesFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mode = self.1;
+        let function_name = mode.function_name("compact_tombstones");
+        let with_connection_function_name =
+            mode.function_name("compact_tombstones_with_connection");
+        let asyncness = mode.asyncness();
+        let await_point = mode.await_point();
+        let connection_type = mode.pg_connection_type();
+        let run_query_dsl = mode.run_query_dsl();
+        let get_db_utc_now = mode.function_name("get_db_utc_now");
+        let record_compaction = mode.function_name("record_compaction");
+        let delete_column_edits = mode.function_name("delete_column_edits");
+        let delete_counter_increments = mode.function_name("delete_counter_increments");
+
+        let compact_tables = self
+            .0
+            .iter()
+            .map(|table| {
+                let table_name = AsSchemaTable(table).get_table_name();
+                let table_name_str = table_name.to_string();
+                let is_deleted_column_name = &table.sync_metadata_columns.is_deleted.ident;
+                let last_synced_at_column_name = &table.sync_metadata_columns.last_synced_at.ident;
+                let id_column_name = &table.sync_metadata_columns.id.ident;
+                let id_type = AsModelType(&table.sync_metadata_columns.id.diesel_type);
+                quote! {
+                    let compacted_ids: Vec<#id_type> = diesel::delete(
+                        #table_name::table
+                            .filter(#table_name::dsl::#is_deleted_column_name.eq(true))
+                            .filter(#table_name::dsl::#last_synced_at_column_name.lt(compacted_before)),
+                    )
+                    .returning(#table_name::dsl::#id_column_name)
+                    .get_results(conn)
+                    #await_point?;
+                    compacted += compacted_ids.len();
+                    let compacted_row_ids = compacted_ids.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                    carburetor::helpers::column_edit::#delete_column_edits(
+                        conn,
+                        #table_name_str,
+                        &compacted_row_ids,
+                    )#await_point?;
+                    carburetor::helpers::counter_increment::#delete_counter_increments(
+                        conn,
+                        #table_name_str,
+                        &compacted_row_ids,
+                    )#await_point?;
+                    carburetor::helpers::tombstone_compaction::#record_compaction(
+                        conn,
+                        #table_name_str,
+                        compacted_before,
+                    )#await_point?;
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let compact_in_transaction = mode.transaction(
+            quote!(conn),
+            quote!(),
+            quote! {
+                let compacted_before = carburetor::helpers::tombstone_compaction::compacted_before(
+                    carburetor::helpers::#get_db_utc_now(conn)#await_point?,
+                    older_than,
+                )?;
+                let mut compacted = 0;
+                #(#compact_tables)*
+                Ok(compacted)
+            },
+        );
+
+        let global_body = mode.with_connection(
+            quote!(carburetor::helpers::),
+            quote!(conn),
+            quote!(#with_connection_function_name(conn, older_than)#await_point),
+        );
+
+        tokens.extend(quote! {
+            /// Physically removes deleted rows whose last sync is older than `older_than`, along
+            /// with their column edit times, and returns the number of rows removed. Clients that
+            /// have not downloaded since then are told to download again from the start.
+            pub #asyncness fn #function_name(
+                older_than: std::time::Duration,
+            ) -> carburetor::error::Result<usize> {
+                #global_body
+            }
+
+            pub #asyncness fn #with_connection_function_name(
+                conn: &mut #connection_type,
+                older_than: std::time::Duration,
+            ) -> carburetor::error::Result<usize> {
+                use diesel::{ExpressionMethods, QueryDsl};
+                use #run_query_dsl;
+
+                #compact_in_transaction
+            }
+        });
+    }
+}
+
+/// `SyncGroup::compact_tombstones(older_than)`, so that a backend with a handle per database can
+/// compact each of them. Generated in the module of the group.
+struct AsCompactTombstonesMethod<'a>(&'a CarburetorSyncGroup, ExecutionMode);
+
+impl<'a> ToTokens for AsCompactTombstonesMethod<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mode = self.1;
+        let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+        let function_name = mode.function_name("compact_tombstones");
+        let with_connection_function_name =
+            mode.function_name("compact_tombstones_with_connection");
+        let asyncness = mode.asyncness();
+        let await_point = mode.await_point();
+        let handle_body = mode.with_connection(
+            quote!(self.carburetor.),
+            quote!(conn),
+            quote!(super::#with_connection_function_name(conn, older_than)#await_point),
+        );
+
+        tokens.extend(quote! {
+            impl #handle_name {
+                /// Same as the `compact_tombstones` function of the schema, on the database of
+                /// this handle. Every table of the schema is compacted, not only the ones of the
+                /// group.
+                pub #asyncness fn #function_name(
+                    &self,
+                    older_than: std::time::Duration,
+                ) -> carburetor::error::Result<usize> {
+                    #handle_body
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn generate_compact_tombstones_function(
+    tokens: &mut TokenStream,
+    tables: &[Rc<CarburetorTable>],
+) {
+    ExecutionMode::all().into_iter().for_each(|mode| {
+        tokens.extend(AsCompactTombstonesFunction(tables, mode).to_token_stream())
+    });
+}
+
+pub(crate) fn generate_compact_tombstones_methods(
+    tokens: &mut TokenStream,
+    sync_group: &CarburetorSyncGroup,
+) {
+    ExecutionMode::all().into_iter().for_each(|mode| {
+        tokens.extend(AsCompactTombstonesMethod(sync_group, mode).to_token_stream())
+    });
+}
@@ -3,12 +3,14 @@ use std::rc::Rc;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 
+use crate::generators::client::models::AsOffsetKey;
 use crate::helpers::{TargetType, get_target_type};
-use crate::parsers::table::CarburetorTable;
+use crate::parsers::sync_group::CarburetorSyncGroup;
 use crate::parsers::table::column::{
     CarburetorColumn, CarburetorColumnType, ColumnScope, DefaultValue, SqlDefault,
 };
 use crate::parsers::table::postgres_type::DieselPostgresType;
+use crate::parsers::table::{CarburetorTable, ChangeTracking};
 
 struct AsColumnDef<'a>(&'a Rc<CarburetorColumn>);
 
@@ -20,12 +22,12 @@ impl<'a> ToTokens for AsColumnDef<'a> {
 
         let (sql_type, null) = match get_target_type() {
             TargetType::Backend => (
-                column.diesel_type.get_sql_type_string().to_string(),
-                matches!(&column.diesel_type, DieselPostgresType::Generic1(_, _)),
+                column.diesel_type.get_sql_type_string(),
+                column.diesel_type.is_nullable(),
             ),
             TargetType::Client => (
                 column.diesel_type.get_sqlite_ddl_type_string().to_string(),
-                matches!(&column.diesel_type, DieselPostgresType::Generic1(_, _))
+                column.diesel_type.is_nullable()
                     || matches!(column.column_scope, ColumnScope::ModOnBackendOnly),
             ),
         };
@@ -50,6 +52,15 @@ impl<'a> ToTokens for AsColumnDef<'a> {
             None => quote! { None },
         };
 
+        // The client only keeps what the backend accepted, so the check is left to the backend
+        let allowed_values_tokens = match (get_target_type(), column.diesel_type.unwrap_nullable())
+        {
+            (TargetType::Backend, DieselPostgresType::Enum(path)) => {
+                quote! { Some(<#path as carburetor::types::SyncEnum>::VARIANTS) }
+            }
+            _ => quote! { None },
+        };
+
         tokens.extend(quote! {
             carburetor::helpers::migration::ColumnDef {
                 name: #name,
@@ -57,6 +68,7 @@ impl<'a> ToTokens for AsColumnDef<'a> {
                 primary_key: #primary_key,
                 null: #null,
                 default: #default_tokens,
+                allowed_values: #allowed_values_tokens,
             }
         });
     }
@@ -68,7 +80,10 @@ fn sql_default_to_postgres_ddl(
 ) -> String {
     match sql_default {
         SqlDefault::Null => "NULL".to_string(),
-        SqlDefault::EmptyJson => "'{}'::jsonb".to_string(),
+        SqlDefault::EmptyJson => match diesel_type.unwrap_nullable() {
+            DieselPostgresType::Json => "'{}'::json".to_string(),
+            _ => "'{}'::jsonb".to_string(),
+        },
         SqlDefault::Text(s) => format!("'{}'", s.replace("'", "''")),
         SqlDefault::Number(n) => n.clone(),
         SqlDefault::Now => match diesel_type.unwrap_nullable() {
@@ -97,7 +112,11 @@ fn sql_default_to_sqlite_ddl(sql_default: &SqlDefault, diesel_type: &DieselPostg
     }
 }
 
-pub(crate) fn generate_run_migrations(tokens: &mut TokenStream, tables: &[Rc<CarburetorTable>]) {
+pub(crate) fn generate_run_migrations(
+    tokens: &mut TokenStream,
+    tables: &[Rc<CarburetorTable>],
+    sync_groups: &[CarburetorSyncGroup],
+) {
     let is_client = get_target_type() == TargetType::Client;
 
     let conn_type = if is_client {
@@ -110,12 +129,32 @@ pub(crate) fn generate_run_migrations(tokens: &mut TokenStream, tables: &[Rc<Car
         .iter()
         .map(|table| {
             let table_name_str = table.plural_ident.to_string();
-            let column_defs: Vec<TokenStream> = table
+            let mut column_defs: Vec<TokenStream> = table
                 .columns
                 .iter()
                 .filter(|c| is_client || !matches!(c.column_scope, ColumnScope::ClientOnly))
                 .map(|c| AsColumnDef(c).to_token_stream())
                 .collect();
+            let is_xid_tracked = !is_client && table.change_tracking == ChangeTracking::Xid;
+            let install_xid_trigger = if is_xid_tracked {
+                // Set by a trigger, so it is not part of the diesel schema
+                column_defs.push(quote! {
+                    carburetor::helpers::migration::ColumnDef {
+                        name: "carburetor_xid",
+                        sql_type: "BIGINT",
+                        primary_key: false,
+                        null: true,
+                        default: None,
+                        allowed_values: None,
+                    }
+                });
+                let id_column_str = table.sync_metadata_columns.id.ident.to_string();
+                quote! {
+                    carburetor::helpers::change_tracking::install_xid_trigger(conn, #table_name_str, #id_column_str)?;
+                }
+            } else {
+                quote!()
+            };
             let column_count = column_defs.len();
 
             quote! {
@@ -127,12 +166,98 @@ pub(crate) fn generate_run_migrations(tokens: &mut TokenStream, tables: &[Rc<Car
                     } else {
                         carburetor::helpers::migration::alter_table(conn, #table_name_str, &columns)?;
                     }
+                    #install_xid_trigger
                 }
             }
         })
         .collect();
 
     if is_client {
+        let offset_keys = sync_groups.iter().flat_map(|sync_group| {
+            sync_group
+                .table_configs
+                .iter()
+                .map(move |table_config| AsOffsetKey {
+                    sync_group,
+                    table_config,
+                    // Unknown until the next download, which sends no fingerprint back
+                    restriction: quote!(""),
+                })
+        });
+        table_migrations.insert(
+            0,
+            quote! {
+                carburetor::helpers::carburetor_offset::migrate_offset_tables(conn, &[#(#offset_keys),*])?;
+                {
+                    let columns: [carburetor::helpers::migration::ColumnDef; 2] = [
+                        carburetor::helpers::migration::ColumnDef {
+                            name: "id",
+                            sql_type: "INTEGER",
+                            primary_key: true,
+                            null: false,
+                            default: None,
+                            allowed_values: None,
+                        },
+                        carburetor::helpers::migration::ColumnDef {
+                            name: "last_at",
+                            sql_type: "TIMESTAMPTZ",
+                            primary_key: false,
+                            null: false,
+                            default: None,
+                            allowed_values: None,
+                        },
+                    ];
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_clock")?;
+                    if !exists {
+                        carburetor::helpers::migration::create_table(conn, "carburetor_clock", &columns)?;
+                    }
+                }
+                {
+                    let columns: [carburetor::helpers::migration::ColumnDef; 3] = [
+                        carburetor::helpers::migration::ColumnDef {
+                            name: "id",
+                            sql_type: "INTEGER",
+                            primary_key: true,
+                            null: false,
+                            default: None,
+                            allowed_values: None,
+                        },
+                        carburetor::helpers::migration::ColumnDef {
+                            name: "device_id",
+                            sql_type: "TEXT",
+                            primary_key: false,
+                            null: false,
+                            default: None,
+                            allowed_values: None,
+                        },
+                        carburetor::helpers::migration::ColumnDef {
+                            name: "counter",
+                            sql_type: "BIGINT",
+                            primary_key: false,
+                            null: false,
+                            default: None,
+                            allowed_values: None,
+                        },
+                    ];
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_device")?;
+                    if !exists {
+                        carburetor::helpers::migration::create_table(conn, "carburetor_device", &columns)?;
+                    }
+                }
+                {
+                    // The composite primary key is not supported by `create_table`
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_upload_failures")?;
+                    if !exists {
+                        diesel::connection::SimpleConnection::batch_execute(
+                            conn,
+                            carburetor::helpers::upload_failure::CREATE_UPLOAD_FAILURES_SQL,
+                        )
+                        .map_err(|e| carburetor::error::Error::from_diesel("Failed to create table 'carburetor_upload_failures'", e))?;
+                    }
+                }
+            },
+        );
+    } else {
         table_migrations.insert(
             0,
             quote! {
@@ -144,18 +269,41 @@ pub(crate) fn generate_run_migrations(tokens: &mut TokenStream, tables: &[Rc<Car
                             primary_key: true,
                             null: false,
                             default: None,
+                            allowed_values: None,
                         },
                         carburetor::helpers::migration::ColumnDef {
-                            name: "cutoff_at",
+                            name: "compacted_before",
                             sql_type: "TIMESTAMPTZ",
                             primary_key: false,
                             null: false,
                             default: None,
+                            allowed_values: None,
                         },
                     ];
-                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_offsets")?;
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_tombstone_compactions")?;
+                    if !exists {
+                        carburetor::helpers::migration::create_table(conn, "carburetor_tombstone_compactions", &columns)?;
+                    }
+                }
+                {
+                    // The composite primary key is not supported by `create_table`
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_column_edits")?;
+                    if !exists {
+                        diesel::connection::SimpleConnection::batch_execute(
+                            conn,
+                            carburetor::helpers::column_edit::CREATE_COLUMN_EDITS_SQL,
+                        )
+                        .map_err(|e| carburetor::error::Error::from_diesel("Failed to create table 'carburetor_column_edits'", e))?;
+                    }
+                }
+                {
+                    let exists = carburetor::helpers::migration::check_table_exists(conn, "carburetor_counter_increments")?;
                     if !exists {
-                        carburetor::helpers::migration::create_table(conn, "carburetor_offsets", &columns)?;
+                        diesel::connection::SimpleConnection::batch_execute(
+                            conn,
+                            carburetor::helpers::counter_increment::CREATE_COUNTER_INCREMENTS_SQL,
+                        )
+                        .map_err(|e| carburetor::error::Error::from_diesel("Failed to create table 'carburetor_counter_increments'", e))?;
                     }
                 }
             },
@@ -165,15 +313,10 @@ pub(crate) fn generate_run_migrations(tokens: &mut TokenStream, tables: &[Rc<Car
     tokens.extend(quote! {
         pub fn run_migrations(conn: &mut #conn_type) -> Result<(), carburetor::error::Error> {
             use diesel::Connection;
-            conn.transaction(|conn| {
+            conn.transaction(|conn| -> Result<(), carburetor::error::Error> {
                 #(#table_migrations)*
                 Ok(())
-            }).map_err(|e|
-                carburetor::error::Error::Unhandled {
-                    message: "Migration error".to_string(),
-                    source: e,
-                }
-            )
+            })
         }
     });
 }
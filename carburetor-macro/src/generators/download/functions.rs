@@ -10,8 +10,11 @@ mod client {
     use quote::{ToTokens, format_ident, quote};
 
     use crate::{
-        generators::{diesel::schema::AsSchemaTable, download::models::AsDownloadRequestModel},
-        parsers::sync_group::CarburetorSyncGroup,
+        generators::{
+            download::models::{AsDownloadRequestModel, AsDownloadResponseModel},
+            handle::models::AsSyncGroupHandle,
+        },
+        parsers::{sync_group::CarburetorSyncGroup, table::ChangeTracking},
     };
 
     pub struct AsRetrieveDownloadRequestFunction<'a>(pub &'a CarburetorSyncGroup);
@@ -19,34 +22,207 @@ mod client {
     impl<'a> ToTokens for AsRetrieveDownloadRequestFunction<'a> {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             let download_request_model_name = AsDownloadRequestModel(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
 
+            let table_retrievals = self
+                .0
+                .table_configs
+                .iter()
+                .map(|x| {
+                    let offset_var = format_ident!("{}_offset", x.reference_table.ident);
+                    let cursor_var = format_ident!("{}_cursor", x.reference_table.ident);
+                    let restriction_var =
+                        format_ident!("{}_restriction", x.reference_table.ident);
+                    let sync_group = self.0.name.to_string();
+                    let table_name = x.reference_table.plural_ident.to_string();
+                    quote! {
+                        let #offset_var = carburetor::helpers::carburetor_offset::retrieve_offset(conn, #sync_group, #table_name)?;
+                        let #cursor_var = carburetor::helpers::carburetor_offset::retrieve_cursor(conn, #sync_group, #table_name)?;
+                        // Sent back so that the backend resets the download when the value bound
+                        // to the restriction has changed since
+                        let #restriction_var = #cursor_var
+                            .as_ref()
+                            .map(|x| &x.restriction)
+                            .or(#offset_var.as_ref().map(|x| &x.restriction))
+                            .filter(|x| !x.is_empty())
+                            .cloned();
+                    }
+                })
+                .collect::<Vec<_>>();
+            let is_first_download = self
+                .0
+                .table_configs
+                .iter()
+                .map(|x| {
+                    let offset_var = format_ident!("{}_offset", x.reference_table.ident);
+                    let cursor_var = format_ident!("{}_cursor", x.reference_table.ident);
+                    quote!(#offset_var.is_none() && #cursor_var.is_none())
+                })
+                .collect::<Vec<_>>();
             let field_assignments = self
                 .0
                 .table_configs
                 .iter()
                 .map(|x| {
                     let field_name = format_ident!("{}_offset", x.reference_table.ident);
-                    let table_name_str = AsSchemaTable(&x.reference_table)
-                        .get_table_name()
-                        .to_string();
-                    quote!(#field_name: offsets.get(#table_name_str).cloned())
+                    let cursor_field_name = format_ident!("{}_cursor", x.reference_table.ident);
+                    let xmin_assignment =
+                        if x.reference_table.change_tracking == ChangeTracking::Xid {
+                            let xmin_field_name = format_ident!("{}_xmin", x.reference_table.ident);
+                            quote!(#xmin_field_name: #field_name.as_ref().and_then(|x| x.xmin),)
+                        } else {
+                            quote!()
+                        };
+                    let restriction_field_name =
+                        format_ident!("{}_restriction", x.reference_table.ident);
+                    quote! {
+                        #xmin_assignment
+                        #field_name: #field_name.map(|x| x.cutoff_at),
+                        #cursor_field_name: #cursor_field_name.map(Into::into),
+                        #restriction_field_name
+                    }
                 })
                 .collect::<Vec<_>>();
 
             tokens.extend(quote! {
                 pub fn retrieve_download_request()
                 -> carburetor::error::Result<Option<#download_request_model_name>> {
-                    let mut conn = carburetor::helpers::get_connection()?;
-                    let offsets = carburetor::helpers::carburetor_offset::retrieve_offsets(&mut conn)?;
+                    carburetor::helpers::with_connection(retrieve_download_request_with_connection)
+                }
+
+                pub fn retrieve_download_request_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                ) -> carburetor::error::Result<Option<#download_request_model_name>> {
+                    #(#table_retrievals)*
 
-                    Ok(if offsets.is_empty() {
+                    Ok(if #(#is_first_download)&&* {
                         None
                     } else {
                         Some(#download_request_model_name {
                             #(#field_assignments,)*
+                            ..Default::default()
                         })
                     })
                 }
+
+                impl #handle_name {
+                    pub fn retrieve_download_request(
+                        &self,
+                    ) -> carburetor::error::Result<Option<#download_request_model_name>> {
+                        self.carburetor.with_connection(retrieve_download_request_with_connection)
+                    }
+                }
+            });
+        }
+    }
+
+    pub struct AsDownloadUntilCaughtUpFunction<'a>(pub &'a CarburetorSyncGroup);
+
+    impl<'a> ToTokens for AsDownloadUntilCaughtUpFunction<'a> {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            let download_request_model_name = AsDownloadRequestModel(self.0).get_model_name();
+            let download_response_model_name = AsDownloadResponseModel(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+            let sync_group_name = self.0.name.to_string();
+
+            let limit_assignments = self
+                .0
+                .table_configs
+                .iter()
+                .map(|x| {
+                    let field_name = format_ident!("{}_limit", x.reference_table.ident);
+                    quote!(request.#field_name = limit;)
+                })
+                .collect::<Vec<_>>();
+            let has_more = self
+                .0
+                .table_configs
+                .iter()
+                .map(|x| {
+                    let field_name = &x.reference_table.ident;
+                    quote!(response.#field_name.has_more)
+                })
+                .collect::<Vec<_>>();
+
+            let download_loop = |retrieve: TokenStream, store: TokenStream| {
+                quote! {
+                    let mut previous_request = None;
+                    loop {
+                        let mut request = #retrieve?.unwrap_or_default();
+                        #(#limit_assignments)*
+                        // A page with more to come always moves the cursor of the request
+                        if previous_request.as_ref() == Some(&request) {
+                            return Err(carburetor::error::Error::DownloadStalled(format!(
+                                "Download of sync group '{}' did not move past its last page",
+                                #sync_group_name
+                            )));
+                        }
+                        let response = fetch(request.clone()).await?;
+                        let has_more = false #(|| #has_more)*;
+                        #store?;
+                        if !has_more {
+                            return Ok(());
+                        }
+                        previous_request = Some(request);
+                    }
+                }
+            };
+            let global_body = download_loop(
+                quote!(retrieve_download_request()),
+                quote!(store_download_response(response)),
+            );
+            let with_connection_body = download_loop(
+                quote!(retrieve_download_request_with_connection(conn)),
+                quote!(store_download_response_with_connection(conn, response)),
+            );
+            let handle_body = download_loop(
+                quote!(self.retrieve_download_request()),
+                quote!(self.store_download_response(response)),
+            );
+
+            let generics = quote!(<F, Fut>);
+            let where_clause = quote! {
+                where
+                    F: FnMut(#download_request_model_name) -> Fut,
+                    Fut: std::future::Future<
+                        Output = carburetor::error::Result<#download_response_model_name>,
+                    >,
+            };
+
+            tokens.extend(quote! {
+                /// Downloads pages of at most `limit` rows per table with `fetch` until every table
+                /// is caught up. Progress is stored after each page, so an interrupted download
+                /// resumes from the last stored page.
+                pub async fn download_until_caught_up #generics(
+                    limit: Option<i64>,
+                    mut fetch: F,
+                ) -> carburetor::error::Result<()>
+                #where_clause
+                {
+                    #global_body
+                }
+
+                pub async fn download_until_caught_up_with_connection #generics(
+                    conn: &mut diesel::SqliteConnection,
+                    limit: Option<i64>,
+                    mut fetch: F,
+                ) -> carburetor::error::Result<()>
+                #where_clause
+                {
+                    #with_connection_body
+                }
+
+                impl #handle_name {
+                    pub async fn download_until_caught_up #generics(
+                        &self,
+                        limit: Option<i64>,
+                        mut fetch: F,
+                    ) -> carburetor::error::Result<()>
+                    #where_clause
+                    {
+                        #handle_body
+                    }
+                }
             });
         }
     }
@@ -60,32 +236,62 @@ mod backend {
     use crate::{
         generators::{
             context::models::AsSyncContext,
-            diesel::schema::AsSchemaTable,
+            diesel::{models::AsModelType, schema::AsSchemaTable},
             download::models::{
                 AsDownloadRequestModel, AsDownloadResponseModel, AsDownloadResponseTableModel,
             },
+            handle::models::AsSyncGroupHandle,
         },
-        parsers::sync_group::CarburetorSyncGroup,
+        helpers::ExecutionMode,
+        parsers::{sync_group::CarburetorSyncGroup, table::ChangeTracking},
     };
 
-    struct AsResponseFieldValue<'a>(&'a crate::parsers::sync_group::SyncGroupTableConfig);
+    struct AsResponseFieldValue<'a>(
+        &'a crate::parsers::sync_group::SyncGroupTableConfig,
+        ExecutionMode,
+    );
 
     impl<'a> ToTokens for AsResponseFieldValue<'a> {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             let table = &self.0.reference_table;
             let field_name = table.ident.clone();
             let function_name = parse_str::<Ident>(&format!("download_{}", &table.ident)).unwrap();
-            let function_argument =
+            let offset_argument =
                 parse_str::<ExprField>(&format!("request.{}_offset", &table.ident)).unwrap();
+            let cursor_argument =
+                parse_str::<ExprField>(&format!("request.{}_cursor", &table.ident)).unwrap();
+            let limit_argument =
+                parse_str::<ExprField>(&format!("request.{}_limit", &table.ident)).unwrap();
+            let await_point = self.1.await_point();
+            let table_name_str = AsSchemaTable(table).get_table_name().to_string();
 
             let context_arg = if self.0.restrict_to.is_some() {
-                quote!(context,)
+                let restriction_argument =
+                    parse_str::<ExprField>(&format!("request.{}_restriction", &table.ident))
+                        .unwrap();
+                quote!(#restriction_argument, context,)
+            } else {
+                quote!()
+            };
+            let xmin_arg = if table.change_tracking == ChangeTracking::Xid {
+                let xmin_argument =
+                    parse_str::<ExprField>(&format!("request.{}_xmin", &table.ident)).unwrap();
+                quote!(#xmin_argument, snapshot_xmin,)
             } else {
                 quote!()
             };
 
             tokens.extend(quote! {
-                #field_name: #function_name(#function_argument, clean_download, #context_arg)?
+                #field_name: #function_name(
+                    conn,
+                    #offset_argument,
+                    #cursor_argument,
+                    #limit_argument,
+                    compactions.get(#table_name_str).cloned(),
+                    cutoff_at,
+                    #xmin_arg
+                    #context_arg
+                )#await_point?
             });
         }
     }
@@ -93,6 +299,7 @@ mod backend {
     struct AsDownloadFunction<'a>(
         &'a CarburetorSyncGroup,
         &'a crate::parsers::sync_group::SyncGroupTableConfig,
+        ExecutionMode,
     );
 
     impl<'a> ToTokens for AsDownloadFunction<'a> {
@@ -103,7 +310,13 @@ mod backend {
             let table_name = AsSchemaTable(table).get_table_name_with_prefix("super");
             let last_synced_at_column_name =
                 table.sync_metadata_columns.last_synced_at.ident.clone();
+            let id_column_name = table.sync_metadata_columns.id.ident.clone();
+            let id_type = AsModelType(&table.sync_metadata_columns.id.diesel_type);
             let is_deleted_column_name = table.sync_metadata_columns.is_deleted.ident.clone();
+            let asyncness = self.2.asyncness();
+            let await_point = self.2.await_point();
+            let connection_type = self.2.pg_connection_type();
+            let run_query_dsl = self.2.run_query_dsl();
 
             let download_sync_response: Path =
                 parse_quote! {carburetor::models::DownloadTableResponse};
@@ -112,109 +325,298 @@ mod backend {
 
             let return_type: Type = parse_quote! {
                 carburetor::error::Result<
-                    #download_sync_response<#model_name>
+                    #download_sync_response<#model_name, #id_type>
                 >
             };
 
-            let (context_param, restrict_filter) = if let Some(ref restrict) = self.1.restrict_to {
-                let context_var = parse_str::<Ident>(&restrict.context_variable).unwrap();
-                let restrict_col = &restrict.column_reference.ident;
+            let (context_param, restriction_check, restrict_filter) =
+                if let Some(ref restrict) = self.1.restrict_to {
+                    let context_var = parse_str::<Ident>(&restrict.context_variable).unwrap();
+                    let restrict_col = &restrict.column_reference.ident;
+                    let restriction = self.1.restriction();
+                    (
+                        quote!(restriction: Option<String>, context: &SyncContext,),
+                        quote! {
+                            let current_restriction = carburetor::helpers::restriction_fingerprint(
+                                #restriction,
+                                &context.#context_var,
+                            )?;
+                            // The rows downloaded before belong to another value of the restriction,
+                            // e.g. to another user signed in on the same device, so all of them
+                            // are dropped
+                            let restriction_changed = (offset.is_some() || cursor.is_some())
+                                && restriction.is_some_and(|x| x != current_restriction);
+                            let reset = reset || restriction_changed;
+                            let reset_before = reset_before.filter(|_| !restriction_changed);
+                            let current_restriction = Some(current_restriction);
+                        },
+                        quote! {
+                            query = query.filter(
+                                #table_name::dsl::#restrict_col.eq(&context.#context_var)
+                            );
+                        },
+                    )
+                } else {
+                    (quote!(), quote!(let current_restriction = None;), quote!())
+                };
+
+            let is_xid_tracked = table.change_tracking == ChangeTracking::Xid;
+            let (xmin_param, cutoff_filter, offset_filter, page_xmin) = if is_xid_tracked {
+                let xid_filter_sql = format!(
+                    "{}.carburetor_xid >= ",
+                    AsSchemaTable(table).get_table_name()
+                );
                 (
-                    quote!(context: &SyncContext,),
+                    quote!(xmin: Option<i64>, snapshot_xmin: i64,),
+                    // Rows committed before the snapshot are all visible, so there is no need for
+                    // a cutoff. Such a row may have a `last_synced_at` after `cutoff_at`, and be
+                    // older than the next `xmin`.
+                    quote!(),
                     quote! {
-                        query = query.filter(
-                            #table_name::dsl::#restrict_col.eq(&context.#context_var)
+                        let xmin = if reset { None } else { xmin };
+                        // Transactions from `xmin` onward might not have been committed at the
+                        // last download, whatever their `last_synced_at`
+                        if let Some(xmin) = xmin {
+                            query = query.filter(
+                                diesel::dsl::sql::<diesel::sql_types::Bool>(#xid_filter_sql)
+                                    .bind::<diesel::sql_types::BigInt, _>(xmin)
+                            );
+                        } else if let Some(offset) = offset {
+                            query = query.filter(#table_name::dsl::#last_synced_at_column_name.gt(offset));
+                        } else if cursor.is_none() && reset_before.is_none() {
+                            // Deleted rows are only needed by clients that have downloaded part
+                            // of the table before, including the previous pages and the rows kept
+                            // by a reset
+                            query = query.filter(#table_name::dsl::#is_deleted_column_name.eq(false));
+                        }
+                        // Rows committed while the pages are downloaded are only known from the
+                        // snapshot of the first page
+                        let page_xmin = Some(
+                            cursor.as_ref().and_then(|x| x.xmin).unwrap_or(snapshot_xmin),
                         );
                     },
+                    quote!(page_xmin),
                 )
             } else {
-                (quote!(), quote!())
+                (
+                    quote!(),
+                    quote! {
+                        .filter(#table_name::dsl::#last_synced_at_column_name.le(cutoff_at))
+                    },
+                    quote! {
+                        if let Some(offset) = offset {
+                            query = query.filter(#table_name::dsl::#last_synced_at_column_name.gt(offset));
+                        } else if cursor.is_none() && reset_before.is_none() {
+                            // Deleted rows are only needed by clients that have downloaded part
+                            // of the table before, including the previous pages and the rows kept
+                            // by a reset
+                            query = query.filter(#table_name::dsl::#is_deleted_column_name.eq(false));
+                        }
+                    },
+                    quote!(None),
+                )
             };
 
             tokens.extend(quote! {
-                fn #function_name(
+                #asyncness fn #function_name(
+                    conn: &mut #connection_type,
                     offset: Option<carburetor::chrono::DateTimeUtc>,
-                    clean_download: bool,
+                    cursor: Option<carburetor::models::DownloadCursor<#id_type>>,
+                    limit: Option<i64>,
+                    compacted_before: Option<carburetor::chrono::DateTimeUtc>,
+                    cutoff_at: carburetor::chrono::DateTimeUtc,
+                    #xmin_param
                     #context_param
                 ) -> #return_type
                 {
-                    use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
-                    let mut conn = carburetor::helpers::get_connection()?;
+                    use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper};
+                    use #run_query_dsl;
 
-                    let process_time = carburetor::helpers::get_db_utc_now(&mut conn)?;
                     let mut query = #table_name::table
                         .select(#model_name::as_select())
-                        .filter(#table_name::dsl::#last_synced_at_column_name.le(process_time))
+                        #cutoff_filter
                         .into_boxed();
 
-                    if let Some(offset) = offset {
-                        query = query.filter(#table_name::dsl::#last_synced_at_column_name.gt(offset));
+                    // Deletions older than the last compaction are gone, so a client that has not
+                    // caught up with them needs to start over
+                    let reset = compacted_before.is_some_and(|compacted_before| {
+                        cursor
+                            .as_ref()
+                            .map(|x| x.last_synced_at)
+                            .or(offset)
+                            .is_some_and(|position| position < compacted_before)
+                    });
+                    // Rows last synced from the compaction onward cannot have lost their deletion
+                    let reset_before = compacted_before.filter(|_| reset);
+                    #restriction_check
+                    let (offset, cursor) = if reset { (None, None) } else { (offset, cursor) };
+
+                    #offset_filter
+
+                    if let Some(cursor) = cursor {
+                        query = query.filter(
+                            #table_name::dsl::#last_synced_at_column_name.gt(cursor.last_synced_at).or(
+                                #table_name::dsl::#last_synced_at_column_name.eq(cursor.last_synced_at)
+                                    .and(#table_name::dsl::#id_column_name.gt(cursor.id))
+                            )
+                        );
+                    }
+
+                    #restrict_filter
+
+                    query = query.order((
+                        #table_name::dsl::#last_synced_at_column_name.asc(),
+                        #table_name::dsl::#id_column_name.asc(),
+                    ));
+
+                    let limit = limit.map(|x| x.max(1));
+                    if let Some(limit) = limit {
+                        // One extra row tells whether there is another page
+                        query = query.limit(limit + 1);
                     }
 
-                    if clean_download {
-                        query = query.filter(#table_name::dsl::#is_deleted_column_name.eq(false));
+                    let mut rows = query
+                        .load::<#model_name>(conn)
+                        #await_point
+                        .map_err(|e| carburetor::error::Error::from_diesel("Query execution failed", e))?;
+
+                    let has_more = limit.is_some_and(|limit| rows.len() as i64 > limit);
+                    if let Some(limit) = limit {
+                        rows.truncate(limit as usize);
                     }
 
-                    #restrict_filter
+                    let xmin = #page_xmin;
+                    let cursor = rows.last().map(|x| carburetor::models::DownloadCursor {
+                        last_synced_at: x.#last_synced_at_column_name,
+                        id: x.#id_column_name.clone(),
+                        xmin,
+                    });
 
                     Ok(#download_sync_response {
-                        cutoff_at: process_time,
-                        data: query
-                            .load(&mut conn)
-                            .map_err(|e| carburetor::error::Error::Unhandled {
-                                message: "Query execution failed".to_string(),
-                                source: e.into(),
-                            })?
+                        cutoff_at,
+                        data: rows
                             .into_iter()
                             .map(|x| { #download_sync_response_data::Update(x) })
                             .collect::<Vec<_>>(),
+                        cursor,
+                        has_more,
+                        reset,
+                        reset_before,
+                        xmin,
+                        restriction: current_restriction,
                     })
                 }
             });
         }
     }
 
-    pub struct AsProcessDownloadRequestFunction<'a>(pub &'a CarburetorSyncGroup);
+    pub struct AsProcessDownloadRequestFunction<'a>(pub &'a CarburetorSyncGroup, pub ExecutionMode);
 
     impl<'a> ToTokens for AsProcessDownloadRequestFunction<'a> {
         fn to_tokens(&self, token: &mut TokenStream) {
-            let function_name = parse_str::<Ident>("process_download_request").unwrap();
+            let mode = self.1;
+            let function_name = mode.function_name("process_download_request");
+            let with_connection_function_name =
+                mode.function_name("process_download_request_with_connection");
             let request_model_name = AsDownloadRequestModel(self.0).get_model_name();
             let response_model_name = AsDownloadResponseModel(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+            let asyncness = mode.asyncness();
+            let await_point = mode.await_point();
+            let connection_type = mode.pg_connection_type();
+            let get_db_utc_now = mode.function_name("get_db_utc_now");
+            let retrieve_compactions = mode.function_name("retrieve_compactions");
+            let get_snapshot_xmin = mode.function_name("get_snapshot_xmin");
+            let snapshot_xmin = if self
+                .0
+                .table_configs
+                .iter()
+                .any(|x| x.reference_table.change_tracking == ChangeTracking::Xid)
+            {
+                quote! {
+                    let snapshot_xmin =
+                        carburetor::helpers::change_tracking::#get_snapshot_xmin(conn)#await_point?;
+                }
+            } else {
+                quote!()
+            };
 
             let table_download_function = self
                 .0
                 .table_configs
                 .iter()
-                .map(|x| AsDownloadFunction(&self.0, x))
+                .map(|x| AsDownloadFunction(&self.0, x, mode))
                 .collect::<Vec<_>>();
 
             let table_response_field_values = self
                 .0
                 .table_configs
                 .iter()
-                .map(|x| AsResponseFieldValue(x))
+                .map(|x| AsResponseFieldValue(x, mode))
                 .collect::<Vec<_>>();
 
             let has_context = AsSyncContext(self.0).has_context();
-            let context_param = if has_context {
+            let (context_param, context_arg) = if has_context {
                 let sync_context_name = AsSyncContext(self.0).get_model_name();
-                quote!(context: &#sync_context_name,)
+                (quote!(context: &#sync_context_name,), quote!(context,))
             } else {
-                quote!()
+                (quote!(), quote!())
+            };
+
+            // All tables are read from the same snapshot and share a single cutoff, so that rows
+            // related across tables are always consistent on the client.
+            let download_in_transaction = mode.transaction(
+                quote!(conn),
+                quote!(.repeatable_read().read_only()),
+                quote! {
+                    let cutoff_at = carburetor::helpers::#get_db_utc_now(conn)#await_point?;
+                    #snapshot_xmin
+                    let compactions =
+                        carburetor::helpers::tombstone_compaction::#retrieve_compactions(conn)#await_point?;
+                    Ok(#response_model_name {
+                        #(#table_response_field_values,)*
+                    })
+                },
+            );
+
+            let call_with_connection = quote! {
+                #with_connection_function_name(conn, request, #context_arg)#await_point
             };
+            let global_body = mode.with_connection(
+                quote!(carburetor::helpers::),
+                quote!(conn),
+                call_with_connection.clone(),
+            );
+            let handle_body =
+                mode.with_connection(quote!(self.carburetor.), quote!(conn), call_with_connection);
 
             token.extend(quote! {
-                pub fn #function_name(
+                pub #asyncness fn #function_name(
+                    request: Option<#request_model_name>,
+                    #context_param
+                ) -> carburetor::error::Result<#response_model_name> {
+                    #global_body
+                }
+
+                pub #asyncness fn #with_connection_function_name(
+                    conn: &mut #connection_type,
                     request: Option<#request_model_name>,
                     #context_param
                 ) -> carburetor::error::Result<#response_model_name> {
-                    let clean_download = request.is_none();
-                    let request = request.unwrap_or_default();
                     #(#table_download_function)*
-                    Ok(#response_model_name {
-                        #(#table_response_field_values,)*
-                    })
+
+                    let request = request.unwrap_or_default();
+                    #download_in_transaction
+                }
+
+                impl #handle_name {
+                    pub #asyncness fn #function_name(
+                        &self,
+                        request: Option<#request_model_name>,
+                        #context_param
+                    ) -> carburetor::error::Result<#response_model_name> {
+                        #handle_body
+                    }
                 }
             })
         }
@@ -226,16 +628,23 @@ pub fn generate_download_sync_group_functions(
     sync_group: &CarburetorSyncGroup,
 ) {
     if get_target_type() == TargetType::Client {
-        use crate::generators::download::functions::client::AsRetrieveDownloadRequestFunction;
+        use crate::generators::download::functions::client::{
+            AsDownloadUntilCaughtUpFunction, AsRetrieveDownloadRequestFunction,
+        };
         use quote::ToTokens;
 
         tokens.extend(AsRetrieveDownloadRequestFunction(sync_group).to_token_stream());
+        tokens.extend(AsDownloadUntilCaughtUpFunction(sync_group).to_token_stream());
     }
 
     if get_target_type() == TargetType::Backend {
         use crate::generators::download::functions::backend::AsProcessDownloadRequestFunction;
         use quote::ToTokens;
 
-        tokens.extend(AsProcessDownloadRequestFunction(sync_group).to_token_stream());
+        crate::helpers::ExecutionMode::all()
+            .into_iter()
+            .for_each(|mode| {
+                tokens.extend(AsProcessDownloadRequestFunction(sync_group, mode).to_token_stream())
+            });
     }
 }
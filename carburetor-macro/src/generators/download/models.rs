@@ -10,7 +10,7 @@ use crate::{
     helpers::{TargetType, get_target_type},
     parsers::{
         sync_group::CarburetorSyncGroup,
-        table::{CarburetorTable, column::ColumnScope},
+        table::{CarburetorTable, ChangeTracking, column::ColumnScope},
     },
 };
 
@@ -20,8 +20,31 @@ impl<'a> ToTokens for AsRequestField<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let field_name =
             parse_str::<Type>(&format!("{}_offset", self.0.ident.to_string())).unwrap();
+        let cursor_field_name = parse_str::<Type>(&format!("{}_cursor", self.0.ident)).unwrap();
+        let limit_field_name = parse_str::<Type>(&format!("{}_limit", self.0.ident)).unwrap();
+        let restriction_field_name =
+            parse_str::<Type>(&format!("{}_restriction", self.0.ident)).unwrap();
+        let id_type = AsModelType(&self.0.sync_metadata_columns.id.diesel_type);
+        if self.0.change_tracking == ChangeTracking::Xid {
+            let xmin_field_name = parse_str::<Type>(&format!("{}_xmin", self.0.ident)).unwrap();
+            tokens.extend(quote! {
+                /// Position returned along with the offset, see `DownloadTableResponse::xmin`.
+                #[serde(default)]
+                pub #xmin_field_name: Option<i64>,
+            });
+        }
         tokens.extend(quote! {
-            pub #field_name: Option<carburetor::chrono::DateTimeUtc>
+            pub #field_name: Option<carburetor::chrono::DateTimeUtc>,
+            /// Resumes a download that was cut short by the page limit.
+            #[serde(default)]
+            pub #cursor_field_name: Option<carburetor::models::DownloadCursor<#id_type>>,
+            /// Maximum number of rows in the response. Every row is returned when not set.
+            #[serde(default)]
+            pub #limit_field_name: Option<i64>,
+            /// Fingerprint returned along with the offset, see
+            /// `DownloadTableResponse::restriction`.
+            #[serde(default)]
+            pub #restriction_field_name: Option<String>
         });
     }
 }
@@ -38,9 +61,10 @@ impl<'a> ToTokens for AsResponseField<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let field_name = self.get_field_name();
         let model_name = AsDownloadResponseTableModel(self.0, self.1).get_model_name();
+        let id_type = AsModelType(&self.1.sync_metadata_columns.id.diesel_type);
 
         tokens.extend(quote! {
-            pub #field_name: carburetor::models::DownloadTableResponse<#model_name>
+            pub #field_name: carburetor::models::DownloadTableResponse<#model_name, #id_type>
         });
     }
 }
@@ -297,7 +321,7 @@ impl<'a> ToTokens for AsDownloadRequestModel<'a> {
             .map(|x| AsRequestField(&x.reference_table))
             .collect::<Vec<_>>();
         tokens.extend(quote! {
-            #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+            #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
             pub struct #request_model_name {
                 #(#request_fields,)*
             }
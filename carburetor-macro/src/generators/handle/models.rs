@@ -0,0 +1,50 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::Ident;
+
+use crate::parsers::sync_group::CarburetorSyncGroup;
+
+/// Per-group struct that exposes the generated functions as methods bound to a
+/// `carburetor::config::Carburetor` handle. The methods themselves are generated next to the
+/// functions they wrap.
+#[derive(Debug, Clone)]
+pub(crate) struct AsSyncGroupHandle<'a>(pub(crate) &'a CarburetorSyncGroup);
+
+impl<'a> AsSyncGroupHandle<'a> {
+    pub(crate) fn get_model_name(&self) -> Ident {
+        Ident::new("SyncGroup", self.0.name.span())
+    }
+}
+
+impl<'a> ToTokens for AsSyncGroupHandle<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let model_name = self.get_model_name();
+
+        tokens.extend(quote! {
+            #[derive(Debug, Clone)]
+            pub struct #model_name {
+                carburetor: carburetor::config::Carburetor,
+            }
+
+            impl #model_name {
+                pub fn new(carburetor: carburetor::config::Carburetor) -> Self {
+                    Self { carburetor }
+                }
+
+                pub fn carburetor(&self) -> &carburetor::config::Carburetor {
+                    &self.carburetor
+                }
+            }
+
+            impl From<carburetor::config::Carburetor> for #model_name {
+                fn from(carburetor: carburetor::config::Carburetor) -> Self {
+                    Self::new(carburetor)
+                }
+            }
+        });
+    }
+}
+
+pub fn generate_sync_group_handle(tokens: &mut TokenStream, sync_group: &CarburetorSyncGroup) {
+    tokens.extend(AsSyncGroupHandle(sync_group).to_token_stream());
+}
@@ -1,7 +1,12 @@
 pub(crate) mod client;
+pub(crate) mod compaction;
 pub(crate) mod context;
 pub(crate) mod diesel;
 pub(crate) mod download;
+pub(crate) mod handle;
+#[cfg(feature = "axum")]
+pub(crate) mod router;
+pub(crate) mod sync_enum;
 pub(crate) mod upload;
 
 use proc_macro2::TokenStream;
@@ -9,8 +14,10 @@ use quote::quote;
 
 use crate::{
     generators::{
+        compaction::functions::generate_compact_tombstones_function,
         diesel::{models::generate_diesel_model, schema::generate_diesel_table_schema},
         download::models::generate_download_sync_group_models,
+        handle::models::generate_sync_group_handle,
         upload::{
             functions::generate_upload_sync_group_functions,
             models::generate_upload_sync_group_models,
@@ -28,18 +35,24 @@ pub(crate) fn generate_carburetor_sync_config(
     tokens: &mut TokenStream,
     sync_config: CarburetorSyncConfig,
 ) {
+    // On the client, a table synced by several groups is stored once, so the groups share its
+    // schema and models
+    sync_config.tables.iter().for_each(|x| {
+        generate_diesel_table_schema(tokens, &x);
+        generate_diesel_model(tokens, &x);
+    });
     if get_target_type() == TargetType::Backend {
-        sync_config.tables.iter().for_each(|x| {
-            generate_diesel_table_schema(tokens, &x);
-            generate_diesel_model(tokens, &x);
-        });
+        generate_compact_tombstones_function(tokens, &sync_config.tables);
+    } else {
+        crate::generators::client::models::generate_client_models(tokens, &sync_config.tables);
     }
 
     #[cfg(feature = "migration")]
-    generate_run_migrations(tokens, &sync_config.tables);
+    generate_run_migrations(tokens, &sync_config.tables, &sync_config.sync_groups);
 
     sync_config.sync_groups.iter().for_each(|x| {
         let mut mod_tokens = TokenStream::new();
+        generate_sync_group_handle(&mut mod_tokens, x);
         generate_download_sync_group_models(&mut mod_tokens, &x);
         generate_upload_sync_group_models(&mut mod_tokens, x);
         generate_upload_sync_group_functions(&mut mod_tokens, x);
@@ -49,8 +62,15 @@ pub(crate) fn generate_carburetor_sync_config(
         );
 
         if get_target_type() == TargetType::Backend {
-            use crate::generators::context::models::generate_context_models;
+            use crate::generators::{
+                compaction::functions::generate_compact_tombstones_methods,
+                context::models::generate_context_models,
+            };
             generate_context_models(&mut mod_tokens, x);
+            generate_compact_tombstones_methods(&mut mod_tokens, x);
+
+            #[cfg(feature = "axum")]
+            crate::generators::router::functions::generate_router_function(&mut mod_tokens, x);
         }
 
         if get_target_type() == TargetType::Client {
@@ -59,17 +79,14 @@ pub(crate) fn generate_carburetor_sync_config(
                     functions::generate_local_operation_functions,
                     models::generate_local_operation_models,
                 },
-                models::generate_client_models,
+                models::generate_shared_table_imports,
+                sync_driver::functions::generate_sync_function,
                 sync_local_db::functions::generate_store_download_response_function,
             };
 
-            x.table_configs.iter().for_each(|config| {
-                generate_diesel_table_schema(&mut mod_tokens, &config.reference_table);
-                generate_diesel_model(&mut mod_tokens, &config.reference_table);
-            });
-
-            generate_client_models(&mut mod_tokens, &x);
+            generate_shared_table_imports(&mut mod_tokens, &x);
             generate_store_download_response_function(&mut mod_tokens, &x);
+            generate_sync_function(&mut mod_tokens, x);
 
             generate_local_operation_functions(&mut mod_tokens, &x);
             generate_local_operation_models(&mut mod_tokens, &x);
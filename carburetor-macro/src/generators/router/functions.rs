@@ -0,0 +1,108 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+
+use crate::{
+    generators::{
+        context::models::AsSyncContext,
+        download::models::{AsDownloadRequestModel, AsDownloadResponseModel},
+        handle::models::AsSyncGroupHandle,
+        upload::models::{AsUploadRequest, AsUploadResponseModel},
+    },
+    parsers::sync_group::CarburetorSyncGroup,
+};
+
+/// `router()` serving the async functions of the sync group at `POST /download` and
+/// `POST /upload`. When the group has context variables, the `SyncContext` is produced by an
+/// extractor chosen by the user, e.g. one reading the user from an authorization header.
+struct AsRouterFunction<'a>(&'a CarburetorSyncGroup);
+
+impl<'a> ToTokens for AsRouterFunction<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let download_request_model_name = AsDownloadRequestModel(self.0).get_model_name();
+        let download_response_model_name = AsDownloadResponseModel(self.0).get_model_name();
+        let upload_request_model_name = AsUploadRequest(self.0).get_model_name();
+        let upload_response_model_name = AsUploadResponseModel(self.0).get_model_name();
+        let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+
+        let has_context = AsSyncContext(self.0).has_context();
+        let (generics, extractor_bound, context_param, context_conversion, context_arg) =
+            if has_context {
+                let sync_context_name = AsSyncContext(self.0).get_model_name();
+                (
+                    quote!(<E, S>),
+                    quote! {
+                        E: carburetor::axum::extract::FromRequestParts<S>
+                            + Into<#sync_context_name>
+                            + Send
+                            + 'static,
+                    },
+                    quote!(context: E,),
+                    quote!(let context: #sync_context_name = context.into();),
+                    quote!(&context),
+                )
+            } else {
+                (quote!(<S>), quote!(), quote!(), quote!(), quote!())
+            };
+
+        // Handlers calling the global functions, or the methods of a cloned handle
+        let handlers = |handle: Option<TokenStream>| {
+            let (clone_handle, receiver) = match handle {
+                Some(handle) => (quote!(let handle = #handle.clone();), quote!(handle.)),
+                None => (quote!(), quote!()),
+            };
+            quote! {
+                carburetor::axum::Router::new()
+                    .route(
+                        "/download",
+                        carburetor::axum::routing::post({
+                            #clone_handle
+                            move |#context_param carburetor::axum::Json(request): carburetor::axum::Json<Option<#download_request_model_name>>| async move {
+                                #context_conversion
+                                #receiver process_download_request_async(request, #context_arg)
+                                    .await
+                                    .map(carburetor::axum::Json::<#download_response_model_name>)
+                            }
+                        }),
+                    )
+                    .route(
+                        "/upload",
+                        carburetor::axum::routing::post({
+                            #clone_handle
+                            move |#context_param carburetor::axum::Json(request): carburetor::axum::Json<#upload_request_model_name>| async move {
+                                #context_conversion
+                                #receiver process_upload_request_async(request, #context_arg)
+                                    .await
+                                    .map(carburetor::axum::Json::<#upload_response_model_name>)
+                            }
+                        }),
+                    )
+            }
+        };
+        let global_handlers = handlers(None);
+        let handle_handlers = handlers(Some(quote!(self)));
+
+        tokens.extend(quote! {
+            pub fn router #generics() -> carburetor::axum::Router<S>
+            where
+                #extractor_bound
+                S: Clone + Send + Sync + 'static,
+            {
+                #global_handlers
+            }
+
+            impl #handle_name {
+                pub fn router #generics(&self) -> carburetor::axum::Router<S>
+                where
+                    #extractor_bound
+                    S: Clone + Send + Sync + 'static,
+                {
+                    #handle_handlers
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn generate_router_function(tokens: &mut TokenStream, sync_group: &CarburetorSyncGroup) {
+    tokens.extend(AsRouterFunction(sync_group).to_token_stream());
+}
@@ -0,0 +1,81 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+
+use crate::{
+    helpers::{TargetType, get_target_type},
+    parsers::sync_enum::CarburetorSyncEnum,
+};
+
+pub(crate) struct AsSyncEnum<'a>(pub &'a CarburetorSyncEnum);
+
+impl<'a> ToTokens for AsSyncEnum<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let item = &self.0.item;
+        let ident = &item.ident;
+        let variant_idents = self.0.variants.iter().map(|x| &x.ident).collect::<Vec<_>>();
+        let values = self.0.variants.iter().map(|x| &x.value).collect::<Vec<_>>();
+        let backend = match get_target_type() {
+            TargetType::Backend => quote!(diesel::pg::Pg),
+            TargetType::Client => quote!(diesel::sqlite::Sqlite),
+        };
+
+        tokens.extend(quote! {
+            #[derive(diesel::AsExpression, diesel::FromSqlRow)]
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            #item
+
+            impl carburetor::types::SyncEnum for #ident {
+                const VARIANTS: &'static [&'static str] = &[#(#values),*];
+
+                fn as_str(&self) -> &'static str {
+                    match self {
+                        #(Self::#variant_idents => #values,)*
+                    }
+                }
+
+                fn from_str(value: &str) -> Result<Self, carburetor::types::UnknownVariant> {
+                    match value {
+                        #(#values => Ok(Self::#variant_idents),)*
+                        _ => Err(carburetor::types::UnknownVariant::new::<Self>(value)),
+                    }
+                }
+            }
+
+            impl serde::Serialize for #ident {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    carburetor::types::serialize_sync_enum(self, serializer)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #ident {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    carburetor::types::deserialize_sync_enum(deserializer)
+                }
+            }
+
+            impl diesel::serialize::ToSql<diesel::sql_types::Text, #backend> for #ident {
+                fn to_sql<'b>(
+                    &'b self,
+                    out: &mut diesel::serialize::Output<'b, '_, #backend>,
+                ) -> diesel::serialize::Result {
+                    <str as diesel::serialize::ToSql<diesel::sql_types::Text, #backend>>::to_sql(
+                        carburetor::types::SyncEnum::as_str(self),
+                        out,
+                    )
+                }
+            }
+
+            impl diesel::deserialize::FromSql<diesel::sql_types::Text, #backend> for #ident {
+                fn from_sql(
+                    bytes: <#backend as diesel::backend::Backend>::RawValue<'_>,
+                ) -> diesel::deserialize::Result<Self> {
+                    let value = <String as diesel::deserialize::FromSql<
+                        diesel::sql_types::Text,
+                        #backend,
+                    >>::from_sql(bytes)?;
+                    Ok(<Self as carburetor::types::SyncEnum>::from_str(&value)?)
+                }
+            }
+        });
+    }
+}
@@ -12,8 +12,12 @@ mod client {
 
     use crate::{
         generators::{
-            client::models::AsTableMetadata,
-            diesel::{models::AsFullModel, schema::AsSchemaTable},
+            client::models::{AsNewId, AsRepairSyncMetadataFunction, AsTableMetadata},
+            diesel::{
+                models::{AsFullModel, AsModelType},
+                schema::AsSchemaTable,
+            },
+            handle::models::AsSyncGroupHandle,
             upload::models::{
                 AsUploadRequest, AsUploadRequestTable, AsUploadResponseModel,
                 client::AsFromFullToTable,
@@ -21,34 +25,34 @@ mod client {
         },
         parsers::{
             sync_group::{CarburetorSyncGroup, SyncGroupTableConfig},
-            table::column::{CarburetorColumnType, ColumnScope},
+            table::column::{CarburetorColumnType, ColumnScope, MergeStrategy},
         },
     };
 
-    struct AsRetrieveTableUploadFunction<'a>(&'a SyncGroupTableConfig);
+    struct AsRetrieveTableUploadFunction<'a>(&'a CarburetorSyncGroup, &'a SyncGroupTableConfig);
 
     impl<'a> AsRetrieveTableUploadFunction<'a> {
         fn get_function_name(&self) -> Ident {
-            format_ident!("retrieve_{}_upload_data", self.0.reference_table.ident)
+            format_ident!("retrieve_{}_upload_data", self.1.reference_table.ident)
         }
     }
 
     impl<'a> ToTokens for AsRetrieveTableUploadFunction<'a> {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             let function_name = self.get_function_name();
-            let upload_table_model_name = AsUploadRequestTable(self.0).get_model_name();
-            let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
-            let full_model_name = AsFullModel(&self.0.reference_table).get_model_name();
+            let upload_table_model_name = AsUploadRequestTable(self.1).get_model_name();
+            let table_name = AsSchemaTable(&self.1.reference_table).get_table_name();
+            let full_model_name = AsFullModel(&self.1.reference_table).get_model_name();
             let fail_execution_message =
                 format!("fail to retrieve dirty {} data from database", table_name);
             let dirty_flag_col_name = &self
-                .0
+                .1
                 .reference_table
                 .sync_metadata_columns
                 .dirty_flag
                 .ident;
             let client_metadata_col_name = &self
-                .0
+                .1
                 .reference_table
                 .sync_metadata_columns
                 .client_column_sync_metadata
@@ -59,7 +63,7 @@ mod client {
                 table_name, client_metadata_col_name, max_sqlite_date
             );
             let dirty_update_filters = self
-                .0
+                .1
                 .reference_table
                 .columns
                 .iter()
@@ -80,15 +84,38 @@ mod client {
                         }
                     },
                 );
-            let into_upload_request_function_name = AsFromFullToTable(self.0).get_function_name();
+            let into_upload_request = AsFromFullToTable(self.1);
+            let into_upload_request_function_name = into_upload_request.get_function_name();
+            let (device_id, device_id_arg) = if into_upload_request.has_counter_columns() {
+                (
+                    quote!(let device_id = carburetor::helpers::id::device_id(connection)?;),
+                    quote!(&device_id,),
+                )
+            } else {
+                (quote!(), quote!())
+            };
+            let table_name_str = table_name.to_string();
+            let id_col_name = &self.1.reference_table.sync_metadata_columns.id.ident;
+            let sync_metadata_model_name =
+                AsTableMetadata(&self.1.reference_table).get_struct_name();
+            let sync_group_name = self.0.name.to_string();
+            let repair_function_name =
+                AsRepairSyncMetadataFunction(&self.1.reference_table).get_function_name();
 
             tokens.extend(quote! {
                 fn #function_name(
+                    connection: &mut diesel::SqliteConnection,
                     cutoff_time: carburetor::chrono::DateTimeUtc,
+                    retry_policy: &carburetor::helpers::upload_failure::UploadRetryPolicy,
                 ) -> carburetor::error::Result<Vec<#upload_table_model_name>> {
-                    let mut connection = carburetor::helpers::get_connection()?;
                     let cutoff_time_rfc3339 = cutoff_time.to_rfc3339();
-                    Ok(#table_name::table
+                    let held_back_ids = carburetor::helpers::upload_failure::retrieve_held_back_ids(
+                        connection,
+                        #table_name_str,
+                        retry_policy,
+                        cutoff_time,
+                    )?;
+                    let records = #table_name::table
                         .select(#full_model_name::as_select())
                         .filter(
                             #table_name::dsl::#dirty_flag_col_name
@@ -107,14 +134,32 @@ mod client {
                                     #dirty_update_filters
                                 )),
                         )
-                        .load::<#full_model_name>(&mut connection)
-                        .map_err(|e| carburetor::error::Error::Unhandled {
-                            message: #fail_execution_message.to_string(),
-                            source: e.into(),
-                        })?
-                    .into_iter()
-                        .filter_map(|x| x.#into_upload_request_function_name(cutoff_time))
-                        .collect::<Vec<_>>())
+                        .load::<#full_model_name>(connection)
+                        .map_err(|e| carburetor::error::Error::from_diesel(#fail_execution_message.to_string(), e))?;
+
+                    #device_id
+                    let mut upload_data = Vec::new();
+                    for record in records {
+                        if held_back_ids.contains(&record.#id_col_name) {
+                            continue;
+                        }
+                        let sync_metadata = match carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#sync_metadata_model_name>::try_from(record.#client_metadata_col_name.clone()) {
+                            Ok(sync_metadata) => sync_metadata,
+                            Err(_) => {
+                                // The dirty columns are unknown, so nothing of the record is
+                                // uploaded this time
+                                #repair_function_name(connection, #sync_group_name, &record, false)?;
+                                continue;
+                            }
+                        };
+                        upload_data.extend(#into_upload_request_function_name(
+                            record,
+                            sync_metadata,
+                            #device_id_arg
+                            cutoff_time,
+                        ));
+                    }
+                    Ok(upload_data)
                 }
             });
         }
@@ -128,29 +173,42 @@ mod client {
                 .0
                 .table_configs
                 .iter()
-                .map(|x| AsRetrieveTableUploadFunction(x))
+                .map(|x| AsRetrieveTableUploadFunction(self.0, x))
                 .collect::<Vec<_>>();
 
             let upload_request_model_name = AsUploadRequest(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
             let upload_request_fields = self
                 .0
                 .table_configs
                 .iter()
                 .map(|x| {
                     let field = &x.reference_table.ident;
-                    let function_name = AsRetrieveTableUploadFunction(x).get_function_name();
-                    quote!(#field: #function_name(cutoff_time)?)
+                    let function_name =
+                        AsRetrieveTableUploadFunction(self.0, x).get_function_name();
+                    quote!(#field: #function_name(conn, cutoff_time, retry_policy)?)
                 })
                 .collect::<Vec<_>>();
 
             tokens.extend(quote! {
                 pub fn retrieve_upload_request()
                 -> carburetor::error::Result<(carburetor::chrono::DateTimeUtc, #upload_request_model_name)> {
+                    let retry_policy = carburetor::helpers::upload_failure::global_upload_retry_policy();
+                    carburetor::helpers::with_connection(|conn| {
+                        retrieve_upload_request_with_connection(conn, &retry_policy)
+                    })
+                }
+
+                /// Rows that failed to upload are left out as long as `retry_policy` holds them back.
+                pub fn retrieve_upload_request_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                    retry_policy: &carburetor::helpers::upload_failure::UploadRetryPolicy,
+                ) -> carburetor::error::Result<(carburetor::chrono::DateTimeUtc, #upload_request_model_name)> {
                     use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
 
                     #(#table_upload_functions)*
 
-                    let cutoff_time = carburetor::helpers::get_utc_now();
+                    let cutoff_time = carburetor::helpers::clock::now(conn)?;
                     Ok((
                         cutoff_time,
                         #upload_request_model_name {
@@ -158,42 +216,71 @@ mod client {
                         },
                     ))
                 }
+
+                impl #handle_name {
+                    pub fn retrieve_upload_request(
+                        &self,
+                    ) -> carburetor::error::Result<(carburetor::chrono::DateTimeUtc, #upload_request_model_name)> {
+                        self.carburetor.with_connection(|conn| {
+                            retrieve_upload_request_with_connection(
+                                conn,
+                                &self.carburetor.config().upload_retry_policy,
+                            )
+                        })
+                    }
+                }
             });
         }
     }
 
-    struct AsProcessTableUploadResponseFunction<'a>(&'a SyncGroupTableConfig);
+    struct AsProcessTableUploadResponseFunction<'a>(
+        &'a CarburetorSyncGroup,
+        &'a SyncGroupTableConfig,
+    );
 
     impl<'a> AsProcessTableUploadResponseFunction<'a> {
         fn get_function_name(&self) -> Ident {
-            format_ident!("process_{}_upload_response", self.0.reference_table.ident)
+            format_ident!("process_{}_upload_response", self.1.reference_table.ident)
         }
     }
 
     impl<'a> ToTokens for AsProcessTableUploadResponseFunction<'a> {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             let function_name = self.get_function_name();
-            let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
-            let full_model_name = AsFullModel(&self.0.reference_table).get_model_name();
+            let sync_group_name = self.0.name.to_string();
+            let repair_function_name =
+                AsRepairSyncMetadataFunction(&self.1.reference_table).get_function_name();
+            let table_name = AsSchemaTable(&self.1.reference_table).get_table_name();
+            let full_model_name = AsFullModel(&self.1.reference_table).get_model_name();
             let sync_metadata_model_name =
-                AsTableMetadata(&self.0.reference_table).get_struct_name();
+                AsTableMetadata(&self.1.reference_table).get_struct_name();
 
-            let id_column = &self.0.reference_table.sync_metadata_columns.id.ident;
+            let id_column = &self.1.reference_table.sync_metadata_columns.id.ident;
             let dirty_flag_column = &self
-                .0
+                .1
                 .reference_table
                 .sync_metadata_columns
                 .dirty_flag
                 .ident;
             let client_metadata_column = &self
-                .0
+                .1
                 .reference_table
                 .sync_metadata_columns
                 .client_column_sync_metadata
                 .ident;
 
+            let last_synced_at_column = &self
+                .1
+                .reference_table
+                .sync_metadata_columns
+                .last_synced_at
+                .ident;
+            let new_id = AsNewId(&self.1.reference_table);
+            let id_type = AsModelType(&self.1.reference_table.sync_metadata_columns.id.diesel_type);
+            let table_name_str = table_name.to_string();
+
             let column_dirty_clearing = self
-                .0
+                .1
                 .reference_table
                 .columns
                 .iter()
@@ -204,13 +291,30 @@ mod client {
                 })
                 .map(|x| {
                     let col_ident = &x.ident;
+                    let key_dirty_clearing = if x.merge_strategy == MergeStrategy::JsonKeys {
+                        quote! {
+                            carburetor::helpers::json_keys::mark_keys_synced(
+                                data.#col_ident.get_or_insert_default(),
+                                cutoff_at,
+                                res.last_synced_at,
+                            );
+                        }
+                    } else {
+                        quote!()
+                    };
+                    let col_name = col_ident.to_string();
                     quote! {
                         if let Some(carburetor::helpers::client_sync_metadata::Metadata {
                             dirty_at: Some(dirty_at), ..
                         }) = data.#col_ident {
                             if dirty_at < cutoff_at {
+                                #key_dirty_clearing
                                 data.#col_ident.get_or_insert_default().dirty_at = None;
-                                data.#col_ident.get_or_insert_default().column_last_synced_at = Some(res.last_synced_at);
+                                // A rejected change is overwritten by the newer value of the
+                                // backend on the next download
+                                if !res.rejected_columns.iter().any(|x| x == #col_name) {
+                                    data.#col_ident.get_or_insert_default().column_last_synced_at = Some(res.last_synced_at);
+                                }
                             } else {
                                 flag = Some(carburetor::helpers::client_sync_metadata::DirtyFlag::Update.to_string());
                             }
@@ -218,17 +322,42 @@ mod client {
                     }
                 })
                 .collect::<Vec<_>>();
+            // Increments are uploaded regardless of the dirty time of the column, so they are
+            // marked as accepted on their own
+            let counter_synced_marking = self
+                .1
+                .reference_table
+                .columns
+                .iter()
+                .filter(|x| x.merge_strategy == MergeStrategy::Counter)
+                .map(|x| {
+                    let col_ident = &x.ident;
+                    quote! {
+                        if let Some(ref mut column_metadata) = data.#col_ident {
+                            carburetor::helpers::counter::mark_increments_synced(
+                                column_metadata,
+                                cutoff_at,
+                                res.last_synced_at,
+                                record.#last_synced_at_column,
+                            );
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
 
             tokens.extend(quote! {
                 fn #function_name(
                     cutoff_at: carburetor::chrono::DateTimeUtc,
-                    responses: Vec<Result<carburetor::models::UploadTableResponseData, carburetor::models::UploadTableResponseError>>,
+                    responses: Vec<Result<carburetor::models::UploadTableResponseData<#id_type>, carburetor::models::UploadTableResponseError<#id_type>>>,
                     conn: &mut diesel::SqliteConnection,
                 ) -> carburetor::error::Result<()> {
                     for response in responses {
                         match response {
                             Ok(res) => {
-                                conn.immediate_transaction(|conn| {
+                                conn.immediate_transaction(|conn| -> carburetor::error::Result<()> {
+                                    carburetor::helpers::clock::observe(conn, res.last_synced_at)?;
+                                    carburetor::helpers::upload_failure::delete_failure(conn, #table_name_str, &res.id)?;
+
                                     let maybe_record = #table_name::table
                                         .select(#full_model_name::as_select())
                                         .find(res.id.clone())
@@ -236,7 +365,15 @@ mod client {
                                         .optional()?;
 
                                     if let Some(record) = maybe_record {
-                                        let mut metadata = carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#sync_metadata_model_name>::from(record.#client_metadata_column);
+                                        let mut metadata = match carburetor::helpers::client_sync_metadata::ClientSyncMetadata::<#sync_metadata_model_name>::try_from(record.#client_metadata_column.clone()) {
+                                            Ok(metadata) => metadata,
+                                            Err(_) => {
+                                                // The backend has the record, so the local one is replaced by
+                                                // the download
+                                                #repair_function_name(conn, #sync_group_name, &record, true)?;
+                                                return Ok(());
+                                            }
+                                        };
 
                                         match record.#dirty_flag_column {
                                             Some(ref f) if
@@ -250,12 +387,13 @@ mod client {
 
                                                     if let Some(mut data) = metadata.data {
                                                         #(#column_dirty_clearing)*
+                                                        #(#counter_synced_marking)*
                                                         metadata.data = Some(data);
                                                     }
 
                                                     diesel::update(#table_name::table.find(record.#id_column))
                                                         .set((
-                                                            #table_name::dsl::#client_metadata_column.eq(carburetor::serde_json::Value::from(metadata)),
+                                                            #table_name::dsl::#client_metadata_column.eq(carburetor::serde_json::Value::try_from(metadata)?),
                                                             #table_name::dsl::#dirty_flag_column.eq(flag)
                                                         ))
                                                         .execute(conn)?;
@@ -264,34 +402,84 @@ mod client {
                                         }
                                     }
                                     Ok(())
-                                })
-                                .map_err(|e: diesel::result::Error| {
-                                    carburetor::error::Error::Unhandled {
-                                        message: "error while processing upload response".to_string(),
-                                        source: e.into(),
-                                    }
                                 })?;
                             }
                             Err(e) => {
-                                match e.code {
-                                    carburetor::models::UploadTableResponseErrorType::RecordAlreadyExists => {
-                                        // TODO: handle by changing the ID. Note that this might cause complication when
-                                        // foreign key feature is introduced
-                                    }
-                                    carburetor::models::UploadTableResponseErrorType::RecordNotFound => {
-                                        // TODO: handle by creating the record as insert record instead
-                                    }
-                                    carburetor::models::UploadTableResponseErrorType::InsufficientPermission => {
-                                        // TODO: handle by creating the record as insert record instead
-                                        //       we probably can safely assume that this is
-                                        //       happening because of update error, insert errors
-                                        //       are to be handled by the developer for mismatch
-                                        //       context between the backend and client
-                                    }
-                                    carburetor::models::UploadTableResponseErrorType::Unknown => {
-                                        // Nothing to do because we don't know what's happening
+                                conn.immediate_transaction(|conn| -> carburetor::error::Result<()> {
+                                    use carburetor::helpers::client_sync_metadata::DirtyFlag;
+
+                                    let maybe_record = #table_name::table
+                                        .select(#full_model_name::as_select())
+                                        .find(e.id.clone())
+                                        .first(conn)
+                                        .optional()?;
+                                    let Some(record) = maybe_record else {
+                                        carburetor::helpers::upload_failure::delete_failure(conn, #table_name_str, &e.id)?;
+                                        return Ok(());
+                                    };
+                                    let is_dirty_insert = record.#dirty_flag_column == Some(DirtyFlag::Insert.to_string());
+                                    let is_dirty_update = record.#dirty_flag_column == Some(DirtyFlag::Update.to_string());
+
+                                    match e.code {
+                                        carburetor::models::UploadTableResponseErrorType::RecordAlreadyExists => {
+                                            // The ID is taken by a record of someone else on the backend,
+                                            // so the record is inserted again under a new ID on the next
+                                            // upload. Note that this might cause complication when foreign
+                                            // key feature is introduced
+                                            if is_dirty_insert {
+                                                diesel::update(#table_name::table.find(record.#id_column))
+                                                    .set(#table_name::dsl::#id_column.eq(#new_id))
+                                                    .execute(conn)?;
+                                                carburetor::helpers::upload_failure::delete_failure(conn, #table_name_str, &e.id)?;
+                                            }
+                                        }
+                                        carburetor::models::UploadTableResponseErrorType::RecordNotFound => {
+                                            carburetor::helpers::upload_failure::delete_failure(conn, #table_name_str, &e.id)?;
+                                            if is_dirty_update {
+                                                // Records are only removed from the backend by a tombstone
+                                                // compaction, so the local record is deleted as well
+                                                diesel::delete(#table_name::table.find(record.#id_column))
+                                                    .execute(conn)?;
+                                            }
+                                        }
+                                        carburetor::models::UploadTableResponseErrorType::InsufficientPermission => {
+                                            // Retrying cannot succeed, so the record is kept locally but
+                                            // no longer uploaded
+                                            if is_dirty_insert || is_dirty_update {
+                                                diesel::update(#table_name::table.find(record.#id_column))
+                                                    .set(#table_name::dsl::#dirty_flag_column.eq(Some(DirtyFlag::Rejected.to_string())))
+                                                    .execute(conn)?;
+                                                let attempted_at = carburetor::helpers::clock::now(conn)?;
+                                                carburetor::helpers::upload_failure::record_failure(
+                                                    conn,
+                                                    #table_name_str,
+                                                    &e.id,
+                                                    &e.code,
+                                                    attempted_at,
+                                                )?;
+                                            }
+                                        }
+                                        carburetor::models::UploadTableResponseErrorType::Unknown => {
+                                            // The record stays dirty and is uploaded again once the
+                                            // retry policy allows it
+                                            if is_dirty_insert || is_dirty_update {
+                                                let attempted_at = carburetor::helpers::clock::now(conn)?;
+                                                carburetor::helpers::upload_failure::record_failure(
+                                                    conn,
+                                                    #table_name_str,
+                                                    &e.id,
+                                                    &e.code,
+                                                    attempted_at,
+                                                )?;
+                                            }
+                                        }
+                                        carburetor::models::UploadTableResponseErrorType::Aborted => {
+                                            // Nothing was applied, the record stays dirty and is
+                                            // uploaded again on the next attempt
+                                        }
                                     }
-                                }
+                                    Ok(())
+                                })?;
                             }
                         }
                     }
@@ -309,10 +497,11 @@ mod client {
                 .0
                 .table_configs
                 .iter()
-                .map(|x| AsProcessTableUploadResponseFunction(x))
+                .map(|x| AsProcessTableUploadResponseFunction(self.0, x))
                 .collect::<Vec<_>>();
 
             let upload_response_model_name = AsUploadResponseModel(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
 
             let table_process_calls = self
                 .0
@@ -320,8 +509,9 @@ mod client {
                 .iter()
                 .map(|x| {
                     let field = &x.reference_table.ident;
-                    let function_name = AsProcessTableUploadResponseFunction(x).get_function_name();
-                    quote!(#function_name(cutoff_at, upload_response.#field, &mut conn)?)
+                    let function_name =
+                        AsProcessTableUploadResponseFunction(self.0, x).get_function_name();
+                    quote!(#function_name(cutoff_at, upload_response.#field, conn)?)
                 })
                 .collect::<Vec<_>>();
 
@@ -329,15 +519,204 @@ mod client {
                 pub fn store_upload_response(
                     cutoff_at: carburetor::chrono::DateTimeUtc,
                     upload_response: #upload_response_model_name,
+                ) -> carburetor::error::Result<()> {
+                    carburetor::helpers::with_connection(|conn| {
+                        store_upload_response_with_connection(conn, cutoff_at, upload_response)
+                    })
+                }
+
+                pub fn store_upload_response_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                    cutoff_at: carburetor::chrono::DateTimeUtc,
+                    upload_response: #upload_response_model_name,
                 ) -> carburetor::error::Result<()> {
                     use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
 
                     #(#table_process_functions)*
 
-                    let mut conn = carburetor::helpers::get_connection()?;
                     #(#table_process_calls;)*
                     Ok(())
                 }
+
+                impl #handle_name {
+                    pub fn store_upload_response(
+                        &self,
+                        cutoff_at: carburetor::chrono::DateTimeUtc,
+                        upload_response: #upload_response_model_name,
+                    ) -> carburetor::error::Result<()> {
+                        self.carburetor.with_connection(|conn| {
+                            store_upload_response_with_connection(conn, cutoff_at, upload_response)
+                        })
+                    }
+                }
+            });
+        }
+    }
+
+    pub struct AsUploadFailureFunctions<'a>(pub &'a CarburetorSyncGroup);
+
+    impl<'a> ToTokens for AsUploadFailureFunctions<'a> {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
+            let table_names = self
+                .0
+                .table_configs
+                .iter()
+                .map(|x| {
+                    AsSchemaTable(&x.reference_table)
+                        .get_table_name()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+
+            let retry_arms = self.0.table_configs.iter().map(|x| {
+                let table_name = AsSchemaTable(&x.reference_table).get_table_name();
+                let table_name_str = table_name.to_string();
+                let full_model_name = AsFullModel(&x.reference_table).get_model_name();
+                let columns = &x.reference_table.sync_metadata_columns;
+                let id_column = &columns.id.ident;
+                let dirty_flag_column = &columns.dirty_flag.ident;
+                let last_synced_at_column = &columns.last_synced_at.ident;
+                quote! {
+                    #table_name_str => {
+                        let maybe_record = #table_name::table
+                            .select(#full_model_name::as_select())
+                            .find(failure.id.clone())
+                            .first(conn)
+                            .optional()?;
+                        match maybe_record {
+                            Some(record) if record.#dirty_flag_column == Some(DirtyFlag::Rejected.to_string()) => {
+                                let flag = if record.#last_synced_at_column.is_some() {
+                                    DirtyFlag::Update
+                                } else {
+                                    DirtyFlag::Insert
+                                };
+                                diesel::update(#table_name::table.find(record.#id_column))
+                                    .set(#table_name::dsl::#dirty_flag_column.eq(Some(flag.to_string())))
+                                    .execute(conn)?;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            });
+
+            let discard_arms = self.0.table_configs.iter().map(|x| {
+                let table_name = AsSchemaTable(&x.reference_table).get_table_name();
+                let table_name_str = table_name.to_string();
+                let full_model_name = AsFullModel(&x.reference_table).get_model_name();
+                let columns = &x.reference_table.sync_metadata_columns;
+                let id_column = &columns.id.ident;
+                let last_synced_at_column = &columns.last_synced_at.ident;
+                quote! {
+                    #table_name_str => {
+                        let maybe_record = #table_name::table
+                            .select(#full_model_name::as_select())
+                            .find(failure.id.clone())
+                            .first(conn)
+                            .optional()?;
+                        if let Some(record) = maybe_record {
+                            diesel::delete(#table_name::table.find(record.#id_column))
+                                .execute(conn)?;
+                            // The backend version of the record is brought back by downloading
+                            // the table again
+                            if record.#last_synced_at_column.is_some() {
+                                carburetor::helpers::carburetor_offset::delete_offsets(conn, #table_name_str)?;
+                                carburetor::helpers::carburetor_offset::delete_cursors(conn, #table_name_str)?;
+                            }
+                        }
+                    }
+                }
+            });
+
+            tokens.extend(quote! {
+                pub fn list_upload_failures()
+                -> carburetor::error::Result<Vec<carburetor::helpers::upload_failure::UploadFailure>> {
+                    carburetor::helpers::with_connection(list_upload_failures_with_connection)
+                }
+
+                pub fn list_upload_failures_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                ) -> carburetor::error::Result<Vec<carburetor::helpers::upload_failure::UploadFailure>> {
+                    carburetor::helpers::upload_failure::retrieve_failures(conn, &[#(#table_names),*])
+                }
+
+                /// Uploads the record of `failure` again on the next upload, regardless of the
+                /// retry policy.
+                pub fn retry_upload_failure(
+                    failure: &carburetor::helpers::upload_failure::UploadFailure,
+                ) -> carburetor::error::Result<()> {
+                    carburetor::helpers::with_connection(|conn| {
+                        retry_upload_failure_with_connection(conn, failure)
+                    })
+                }
+
+                pub fn retry_upload_failure_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                    failure: &carburetor::helpers::upload_failure::UploadFailure,
+                ) -> carburetor::error::Result<()> {
+                    use carburetor::helpers::client_sync_metadata::DirtyFlag;
+                    use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
+
+                    conn.immediate_transaction(|conn| -> carburetor::error::Result<()> {
+                        match failure.table_name.as_str() {
+                            #(#retry_arms)*
+                            _ => return Err(carburetor::helpers::upload_failure::unknown_table_error(failure)),
+                        }
+                        carburetor::helpers::upload_failure::delete_failure(conn, &failure.table_name, &failure.id)
+                    })
+                }
+
+                /// Drops the local changes of the record of `failure`. A record that was synced
+                /// before is restored from the backend on the next download.
+                pub fn discard_upload_failure(
+                    failure: &carburetor::helpers::upload_failure::UploadFailure,
+                ) -> carburetor::error::Result<()> {
+                    carburetor::helpers::with_connection(|conn| {
+                        discard_upload_failure_with_connection(conn, failure)
+                    })
+                }
+
+                pub fn discard_upload_failure_with_connection(
+                    conn: &mut diesel::SqliteConnection,
+                    failure: &carburetor::helpers::upload_failure::UploadFailure,
+                ) -> carburetor::error::Result<()> {
+                    use diesel::{Connection, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
+
+                    conn.immediate_transaction(|conn| -> carburetor::error::Result<()> {
+                        match failure.table_name.as_str() {
+                            #(#discard_arms)*
+                            _ => return Err(carburetor::helpers::upload_failure::unknown_table_error(failure)),
+                        }
+                        carburetor::helpers::upload_failure::delete_failure(conn, &failure.table_name, &failure.id)
+                    })
+                }
+
+                impl #handle_name {
+                    pub fn list_upload_failures(
+                        &self,
+                    ) -> carburetor::error::Result<Vec<carburetor::helpers::upload_failure::UploadFailure>> {
+                        self.carburetor.with_connection(list_upload_failures_with_connection)
+                    }
+
+                    pub fn retry_upload_failure(
+                        &self,
+                        failure: &carburetor::helpers::upload_failure::UploadFailure,
+                    ) -> carburetor::error::Result<()> {
+                        self.carburetor.with_connection(|conn| {
+                            retry_upload_failure_with_connection(conn, failure)
+                        })
+                    }
+
+                    pub fn discard_upload_failure(
+                        &self,
+                        failure: &carburetor::helpers::upload_failure::UploadFailure,
+                    ) -> carburetor::error::Result<()> {
+                        self.carburetor.with_connection(|conn| {
+                            discard_upload_failure_with_connection(conn, failure)
+                        })
+                    }
+                }
             });
         }
     }
@@ -352,18 +731,22 @@ mod backend {
         generators::{
             context::models::AsSyncContext,
             diesel::{
-                models::{AsChangesetModel, AsFullModel, AsInsertModel},
+                models::{AsChangesetModel, AsFullModel, AsInsertModel, AsModelType},
                 schema::AsSchemaTable,
             },
-            upload::models::{AsUploadRequest, AsUploadRequestTable, AsUploadResponseModel},
+            handle::models::AsSyncGroupHandle,
+            upload::models::{
+                AsUploadRequest, AsUploadRequestTable, AsUploadResponseModel, AsUploadUpdateTable,
+            },
         },
+        helpers::ExecutionMode,
         parsers::{
             sync_group::{CarburetorSyncGroup, SyncGroupTableConfig},
-            table::column::{ColumnScope, DefaultValue},
+            table::column::{CarburetorColumnType, ColumnScope, DefaultValue, MergeStrategy},
         },
     };
 
-    struct AsProcessTableUploadFunction<'a>(&'a SyncGroupTableConfig);
+    struct AsProcessTableUploadFunction<'a>(&'a SyncGroupTableConfig, ExecutionMode, bool);
 
     impl<'a> AsProcessTableUploadFunction<'a> {
         fn get_function_name(&self) -> Ident {
@@ -375,7 +758,12 @@ mod backend {
         fn to_tokens(&self, tokens: &mut TokenStream) {
             let function_name = self.get_function_name();
             let upload_request_table_name = AsUploadRequestTable(self.0).get_model_name();
+            let upload_update_table_name = AsUploadUpdateTable(self.0).get_model_name();
             let table_name = AsSchemaTable(&self.0.reference_table).get_table_name();
+            let asyncness = self.1.asyncness();
+            let await_point = self.1.await_point();
+            let connection_type = self.1.pg_connection_type();
+            let run_query_dsl = self.1.run_query_dsl();
             let full_model_name =
                 AsFullModel(&self.0.reference_table).get_model_name_with_prefix("super");
             let insert_model_name =
@@ -383,6 +771,7 @@ mod backend {
             let changeset_model_name =
                 AsChangesetModel(&self.0.reference_table).get_model_name_with_prefix("super");
             let id_column = &self.0.reference_table.sync_metadata_columns.id.ident;
+            let id_type = AsModelType(&self.0.reference_table.sync_metadata_columns.id.diesel_type);
             let last_synced_at_column = &self
                 .0
                 .reference_table
@@ -409,172 +798,536 @@ mod backend {
                     Some(quote!(super::#table_name::#column_name.eq(#value)))
                 })
                 .collect::<Vec<_>>();
+            // Patches of `#[merge(json_keys)]` columns are applied over the stored object in the
+            // same statement, so that keys uploaded by other clients in between are not lost
+            let json_keys_columns = self
+                .0
+                .reference_table
+                .columns
+                .iter()
+                .filter(|x| x.merge_strategy == MergeStrategy::JsonKeys)
+                .map(|x| &x.ident)
+                .collect::<Vec<_>>();
+            let json_keys_patch_vars = json_keys_columns
+                .iter()
+                .map(|x| format_ident!("{}_patch", x))
+                .collect::<Vec<_>>();
+            // Increments of `#[crdt(counter)]` columns are added to the stored value, each of them
+            // only once, as an upload can be received again
+            let counter_columns = self
+                .0
+                .reference_table
+                .columns
+                .iter()
+                .filter(|x| x.merge_strategy == MergeStrategy::Counter)
+                .collect::<Vec<_>>();
+            let counter_column_names = counter_columns
+                .iter()
+                .map(|x| x.ident.to_string())
+                .collect::<Vec<_>>();
+            let counter_types = counter_columns
+                .iter()
+                .map(|x| AsModelType(&x.diesel_type))
+                .collect::<Vec<_>>();
+            let counter_columns = counter_columns.iter().map(|x| &x.ident).collect::<Vec<_>>();
+            let counter_delta_vars = counter_columns
+                .iter()
+                .map(|x| format_ident!("{}_delta", x))
+                .collect::<Vec<_>>();
+            let accept_counter_increments = self.1.function_name("accept_counter_increments");
+            // Changes of the other columns are only applied when they were edited after the
+            // change the backend already has
+            let lww_columns = self
+                .0
+                .reference_table
+                .columns
+                .iter()
+                .filter(|x| {
+                    x.column_scope == ColumnScope::Both
+                        && x.column_type != CarburetorColumnType::Id
+                        && !x.is_immutable
+                        && x.merge_strategy == MergeStrategy::Value
+                })
+                .map(|x| &x.ident)
+                .collect::<Vec<_>>();
+            let lww_column_names = lww_columns
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+            let table_name_str = table_name.to_string();
+            let accept_column_edits = self.1.function_name("accept_column_edits");
+            let use_jsonb_methods = if json_keys_columns.is_empty() {
+                quote!()
+            } else {
+                quote!(
+                    use diesel::PgJsonbExpressionMethods;
+                )
+            };
 
-            let (context_param, insert_context_validation, update_context_validation) =
-                if let Some(ref restrict) = self.0.restrict_to {
-                    let context_var = parse_str::<syn::Ident>(&restrict.context_variable).unwrap();
-                    let restrict_col = &restrict.column_reference.ident;
-                    (
-                        quote!(context: &SyncContext,),
-                        quote! {
-                            if &data.#restrict_col != &context.#context_var {
-                                return Err(carburetor::models::UploadTableResponseError {
-                                    id: data.#id_column.clone(),
-                                    code: carburetor::models::UploadTableResponseErrorType::InsufficientPermission,
-                                });
-                            }
-                        },
-                        quote! {
-                            use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
-                            let existing = super::#table_name::table
-                                .select(#full_model_name::as_select())
-                                .find(&data.#id_column)
-                                .first(connection)
-                                .map_err(|_| carburetor::models::UploadTableResponseError {
-                                    id: data.#id_column.clone(),
-                                    code: carburetor::models::UploadTableResponseErrorType::RecordNotFound,
-                                })?;
-                            if &existing.#restrict_col != &context.#context_var {
-                                return Err(carburetor::models::UploadTableResponseError {
-                                    id: data.#id_column.clone(),
-                                    code: carburetor::models::UploadTableResponseErrorType::InsufficientPermission,
-                                });
+            let (
+                context_param,
+                context_arg,
+                insert_context_validation,
+                update_context_validation,
+                conflict_ownership_validation,
+            ) = if let Some(ref restrict) = self.0.restrict_to {
+                let context_var = parse_str::<syn::Ident>(&restrict.context_variable).unwrap();
+                let restrict_col = &restrict.column_reference.ident;
+                (
+                    quote!(context: &SyncContext,),
+                    quote!(context,),
+                    quote! {
+                        if &data.#restrict_col != &context.#context_var {
+                            return Err(carburetor::models::UploadTableResponseError {
+                                id: data.#id_column.clone(),
+                                code: carburetor::models::UploadTableResponseErrorType::InsufficientPermission,
+                            });
+                        }
+                    },
+                    quote! {
+                        use diesel::SelectableHelper;
+                        let existing = super::#table_name::table
+                            .select(#full_model_name::as_select())
+                            .find(&data.#id_column)
+                            .first(connection)
+                            #await_point
+                            .map_err(|e| carburetor::models::UploadTableResponseError {
+                                id: data.#id_column.clone(),
+                                code: match e {
+                                    diesel::result::Error::NotFound =>
+                                        carburetor::models::UploadTableResponseErrorType::RecordNotFound,
+                                    _ => carburetor::models::UploadTableResponseErrorType::Unknown,
+                                },
+                            })?;
+                        if &existing.#restrict_col != &context.#context_var {
+                            return Err(carburetor::models::UploadTableResponseError {
+                                id: data.#id_column.clone(),
+                                code: carburetor::models::UploadTableResponseErrorType::InsufficientPermission,
+                            });
+                        }
+                    },
+                    quote! {
+                        use diesel::SelectableHelper;
+                        let existing: #full_model_name = super::#table_name::table
+                            .select(#full_model_name::as_select())
+                            .find(&id_to_insert)
+                            .first(connection)
+                            #await_point
+                            .map_err(|_| carburetor::models::UploadTableResponseError {
+                                id: id_to_insert.clone(),
+                                code: carburetor::models::UploadTableResponseErrorType::Unknown,
+                            })?;
+                        if &existing.#restrict_col != &context.#context_var {
+                            return Err(carburetor::models::UploadTableResponseError {
+                                id: id_to_insert,
+                                code: carburetor::models::UploadTableResponseErrorType::RecordAlreadyExists,
+                            });
+                        }
+                    },
+                )
+            } else {
+                (quote!(), quote!(), quote!(), quote!(), quote!())
+            };
+
+            let is_atomic = self.2;
+            let (aborted_param, record_loop_body) = if is_atomic {
+                (
+                    quote!(aborted: &mut bool,),
+                    quote! {
+                        // Once a row failed, the transaction is rolled back, so the remaining rows
+                        // are not attempted
+                        if *aborted {
+                            let id = match &x {
+                                #upload_request_table_name::Insert(data) => data.#id_column.clone(),
+                                #upload_request_table_name::Update(data) => data.#id_column.clone(),
+                            };
+                            responses.push(Err(carburetor::models::UploadTableResponseError {
+                                id,
+                                code: carburetor::models::UploadTableResponseErrorType::Aborted,
+                            }));
+                            continue;
+                        }
+                        let response = process_record(x, connection, #context_arg)#await_point;
+                        *aborted = response.is_err();
+                        responses.push(response);
+                    },
+                )
+            } else {
+                (
+                    quote!(),
+                    quote! {
+                        responses.push(process_record(x, connection, #context_arg)#await_point);
+                    },
+                )
+            };
+
+            // The edit times are recorded in the same transaction as the row, so that they are
+            // rolled back together
+            let insert_in_transaction = self.1.transaction(
+                quote!(connection),
+                quote!(),
+                quote! {
+                    let inserted: #full_model_name = diesel::insert_into(super::#table_name::table)
+                        .values((
+                            &insert_data,
+                            #(#mod_on_backend_only_columns,)*
+                        ))
+                        .get_result(connection)
+                        #await_point?;
+                    carburetor::helpers::column_edit::#accept_column_edits(
+                        connection,
+                        #table_name_str,
+                        &row_id,
+                        &edits,
+                    )#await_point?;
+                    Ok::<_, diesel::result::Error>(inserted)
+                },
+            );
+            let update_in_transaction = self.1.transaction(
+                quote!(connection),
+                quote!(),
+                quote! {
+                    let edits = [#((#lww_column_names, data.#lww_columns.is_some()),)*]
+                        .into_iter()
+                        .filter(|(_, is_uploaded)| *is_uploaded)
+                        .map(|(column_name, _)| {
+                            (column_name, data.carburetor_edited_at.get(column_name).copied())
+                        })
+                        .collect::<Vec<_>>();
+                    let accepted = carburetor::helpers::column_edit::#accept_column_edits(
+                        connection,
+                        #table_name_str,
+                        &row_id,
+                        &edits,
+                    )#await_point?;
+                    let rejected_columns = edits
+                        .iter()
+                        .filter(|(column_name, _)| !accepted.contains(*column_name))
+                        .map(|(column_name, _)| column_name.to_string())
+                        .collect::<Vec<_>>();
+                    // Nothing is left to apply when every uploaded column is rejected, so the row
+                    // is left untouched and its last_synced_at does not move
+                    if !edits.is_empty()
+                        && accepted.is_empty()
+                        #(&& data.#json_keys_columns.is_none())*
+                        #(&& data.#counter_columns.is_none())*
+                    {
+                        use diesel::SelectableHelper;
+                        let existing: #full_model_name = super::#table_name::table
+                            .select(#full_model_name::as_select())
+                            .find(&data.#id_column)
+                            .first(connection)
+                            #await_point?;
+                        return Ok((existing, rejected_columns));
+                    }
+                    #(
+                        if rejected_columns.iter().any(|x| x == #lww_column_names) {
+                            data.#lww_columns = None;
+                        }
+                    )*
+                    #(
+                        let #json_keys_patch_vars = data.#json_keys_columns.clone().unwrap_or_default();
+                    )*
+                    #(
+                        let #counter_delta_vars = match &data.#counter_columns {
+                            Some(increments) => {
+                                let delta = carburetor::helpers::counter_increment::#accept_counter_increments(
+                                    connection,
+                                    #table_name_str,
+                                    &row_id,
+                                    #counter_column_names,
+                                    increments,
+                                )#await_point?;
+                                <#counter_types>::try_from(delta).unwrap_or(if delta < 0 {
+                                    <#counter_types>::MIN
+                                } else {
+                                    <#counter_types>::MAX
+                                })
                             }
-                        },
-                    )
-                } else {
-                    (quote!(), quote!(), quote!())
-                };
+                            None => 0,
+                        };
+                    )*
+                    let update_data = #changeset_model_name::from(data);
+                    let updated: #full_model_name = diesel::update(super::#table_name::table.find(&update_data.#id_column))
+                        .set((
+                            &update_data,
+                            #(super::#table_name::#json_keys_columns.eq(
+                                super::#table_name::#json_keys_columns
+                                    .concat(carburetor::serde_json::Value::Object(#json_keys_patch_vars.set))
+                                    .remove(#json_keys_patch_vars.remove)
+                            ),)*
+                            #(super::#table_name::#counter_columns.eq(
+                                super::#table_name::#counter_columns + #counter_delta_vars
+                            ),)*
+                            #(#mod_on_backend_only_columns,)*
+                        ))
+                        .get_result(connection)
+                        #await_point?;
+                    Ok::<_, diesel::result::Error>((updated, rejected_columns))
+                },
+            );
 
             tokens.extend(quote! {
-                fn #function_name(
+                #asyncness fn #function_name(
                     requests: Vec<#upload_request_table_name>,
-                    connection: &mut diesel::PgConnection,
+                    connection: &mut #connection_type,
+                    #aborted_param
                     #context_param
                 ) -> Vec<
                     Result<
-                        carburetor::models::UploadTableResponseData,
-                        carburetor::models::UploadTableResponseError,
+                        carburetor::models::UploadTableResponseData<#id_type>,
+                        carburetor::models::UploadTableResponseError<#id_type>,
                     >,
                 > {
-                    use diesel::{QueryDsl, RunQueryDsl, Insertable, ExpressionMethods};
-                    requests
-                        .into_iter()
-                        .map(|x| {
-                            match x {
-                                #upload_request_table_name::Insert(data) => {
-                                    #insert_context_validation
-                                    let insert_data = #insert_model_name::from(data);
-                                    let id_to_insert = insert_data.#id_column.clone();
-                                    diesel::insert_into(super::#table_name::table)
-                                        .values((
-                                            &insert_data,
-                                            #(#mod_on_backend_only_columns,)*
-                                        ))
-                                        .get_result(connection)
-                                        .map(
-                                            |x: #full_model_name| carburetor::models::UploadTableResponseData {
-                                                id: x.#id_column,
-                                                last_synced_at: x.#last_synced_at_column,
-                                            },
-                                        )
-                                        .map_err(|e| {
-                                            let code = match e {
-                                                diesel::result::Error::DatabaseError(
-                                                    diesel::result::DatabaseErrorKind::UniqueViolation, _) =>
-                                                    carburetor::models::UploadTableResponseErrorType::RecordAlreadyExists,
-                                                _ => carburetor::models::UploadTableResponseErrorType::Unknown,
-                                            };
-                                            carburetor::models::UploadTableResponseError {
-                                                id: id_to_insert,
-                                                code,
-                                            }
-                                        })
-                                }
-                                #upload_request_table_name::Update(data) => {
-                                    #update_context_validation
-                                    let update_data = #changeset_model_name::from(data);
-                                    let id_to_update = update_data.#id_column.clone();
-                                    diesel::update(super::#table_name::table.find(&update_data.#id_column))
-                                        .set((
-                                            &update_data,
-                                            #(#mod_on_backend_only_columns,)*
-                                        ))
-                                        .get_result(connection)
-                                        .map(|x: #full_model_name| carburetor::models::UploadTableResponseData {
+                    #asyncness fn update_record(
+                        mut data: #upload_update_table_name,
+                        connection: &mut #connection_type,
+                        #context_param
+                    ) -> Result<
+                        carburetor::models::UploadTableResponseData<#id_type>,
+                        carburetor::models::UploadTableResponseError<#id_type>,
+                    > {
+                        use diesel::{QueryDsl, ExpressionMethods};
+                        use #run_query_dsl;
+                        #use_jsonb_methods
+
+                        #update_context_validation
+                        let id_to_update = data.#id_column.clone();
+                        let row_id = id_to_update.to_string();
+                        #update_in_transaction
+                        .map(|(x, rejected_columns): (#full_model_name, Vec<String>)| {
+                            carburetor::models::UploadTableResponseData {
+                                id: x.#id_column,
+                                last_synced_at: x.#last_synced_at_column,
+                                rejected_columns,
+                            }
+                        })
+                        .map_err(|e| {
+                            let code = match e {
+                                diesel::result::Error::NotFound =>
+                                    carburetor::models::UploadTableResponseErrorType::RecordNotFound,
+                                _ => carburetor::models::UploadTableResponseErrorType::Unknown,
+                            };
+                            carburetor::models::UploadTableResponseError {
+                                id: id_to_update,
+                                code,
+                            }
+                        })
+                    }
+
+                    #asyncness fn process_record(
+                        x: #upload_request_table_name,
+                        connection: &mut #connection_type,
+                        #context_param
+                    ) -> Result<
+                        carburetor::models::UploadTableResponseData<#id_type>,
+                        carburetor::models::UploadTableResponseError<#id_type>,
+                    > {
+                        use diesel::{QueryDsl, ExpressionMethods};
+                        use #run_query_dsl;
+
+                        match x {
+                            #upload_request_table_name::Insert(data) => {
+                                #insert_context_validation
+                                let edits: Vec<(&str, Option<carburetor::chrono::DateTimeUtc>)> = vec![
+                                    #((#lww_column_names, data.carburetor_edited_at.get(#lww_column_names).copied()),)*
+                                ];
+                                let replayed_update = #upload_update_table_name::from(&data);
+                                let insert_data = #insert_model_name::from(data);
+                                let id_to_insert = insert_data.#id_column.clone();
+                                let row_id = id_to_insert.to_string();
+                                match #insert_in_transaction {
+                                    Ok(x) => {
+                                        let x: #full_model_name = x;
+                                        Ok(carburetor::models::UploadTableResponseData {
                                             id: x.#id_column,
                                             last_synced_at: x.#last_synced_at_column,
+                                            rejected_columns: Vec::new(),
                                         })
-                                        .map_err(|e| {
-                                            let code = match e {
-                                                diesel::result::Error::NotFound =>
-                                                    carburetor::models::UploadTableResponseErrorType::RecordNotFound,
-                                                _ => carburetor::models::UploadTableResponseErrorType::Unknown,
-                                            };
-                                            carburetor::models::UploadTableResponseError {
-                                                id: id_to_update,
-                                                code,
-                                            }
-                                        })
+                                    }
+                                    // Most likely the same insert uploaded again after its response
+                                    // was lost, so it is applied as an update of the row it created,
+                                    // keeping the local edits made since
+                                    Err(diesel::result::Error::DatabaseError(
+                                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                                        _,
+                                    )) => {
+                                        #conflict_ownership_validation
+                                        update_record(replayed_update, connection, #context_arg)#await_point
+                                    }
+                                    Err(_) => Err(carburetor::models::UploadTableResponseError {
+                                        id: id_to_insert,
+                                        code: carburetor::models::UploadTableResponseErrorType::Unknown,
+                                    }),
                                 }
                             }
-                        })
-                        .collect::<Vec<_>>()
+                            #upload_request_table_name::Update(data) => {
+                                update_record(data, connection, #context_arg)#await_point
+                            }
+                        }
+                    }
+
+                    let mut responses = Vec::with_capacity(requests.len());
+                    for x in requests {
+                        #record_loop_body
+                    }
+                    responses
                 }
             });
         }
     }
 
-    pub struct AsProcessUploadFunction<'a>(pub &'a CarburetorSyncGroup);
+    pub struct AsProcessUploadFunction<'a>(pub &'a CarburetorSyncGroup, pub ExecutionMode);
 
     impl<'a> ToTokens for AsProcessUploadFunction<'a> {
         fn to_tokens(&self, tokens: &mut TokenStream) {
+            let mode = self.1;
+            let function_name = mode.function_name("process_upload_request");
+            let with_connection_function_name =
+                mode.function_name("process_upload_request_with_connection");
+            let asyncness = mode.asyncness();
+            let await_point = mode.await_point();
+            let connection_type = mode.pg_connection_type();
+
             let table_process_functions = self
                 .0
                 .table_configs
                 .iter()
-                .map(|x| AsProcessTableUploadFunction(x))
+                .map(|x| AsProcessTableUploadFunction(x, mode, self.0.is_atomic))
                 .collect::<Vec<_>>();
+            let aborted_arg = if self.0.is_atomic {
+                quote!(aborted,)
+            } else {
+                quote!()
+            };
 
             let upload_request_model_name = AsUploadRequest(self.0).get_model_name();
             let upload_response_model_name = AsUploadResponseModel(self.0).get_model_name();
+            let handle_name = AsSyncGroupHandle(self.0).get_model_name();
             let field_assignments = self
                 .0
                 .table_configs
                 .iter()
                 .map(|x| {
                     let field = &x.reference_table.ident;
-                    let function_name = AsProcessTableUploadFunction(x).get_function_name();
+                    let function_name =
+                        AsProcessTableUploadFunction(x, mode, self.0.is_atomic).get_function_name();
                     let context_arg = if x.restrict_to.is_some() {
                         quote!(context,)
                     } else {
                         quote!()
                     };
-                    quote!(#field: #function_name(upload_request.#field, &mut connection, #context_arg))
+                    quote!(#field: #function_name(upload_request.#field, connection, #aborted_arg #context_arg)#await_point)
                 })
                 .collect::<Vec<_>>();
 
             let has_context = AsSyncContext(self.0).has_context();
-            let context_param = if has_context {
+            let (context_param, context_arg) = if has_context {
                 let sync_context_name = AsSyncContext(self.0).get_model_name();
-                quote!(context: &#sync_context_name,)
+                (quote!(context: &#sync_context_name,), quote!(context,))
             } else {
-                quote!()
+                (quote!(), quote!())
+            };
+
+            let process_tables = if self.0.is_atomic {
+                let field_names = self
+                    .0
+                    .table_configs
+                    .iter()
+                    .map(|x| &x.reference_table.ident)
+                    .collect::<Vec<_>>();
+                let process_in_transaction = mode.transaction(
+                    quote!(connection),
+                    quote!(),
+                    quote! {
+                        let aborted = &mut false;
+                        let response = #upload_response_model_name {
+                            #(#field_assignments,)*
+                        };
+                        if *aborted {
+                            *aborted_response = Some(response);
+                            return Err(diesel::result::Error::RollbackTransaction.into());
+                        }
+                        Ok(response)
+                    },
+                );
+                quote! {
+                    let mut aborted_response = None;
+                    let result = {
+                        let aborted_response = &mut aborted_response;
+                        #process_in_transaction
+                    };
+                    match aborted_response {
+                        Some(mut response) => {
+                            // Rows applied before the failure were rolled back as well
+                            #(
+                                response.#field_names = response
+                                    .#field_names
+                                    .into_iter()
+                                    .map(|x| {
+                                        x.and_then(|data| {
+                                            Err(carburetor::models::UploadTableResponseError {
+                                                id: data.id,
+                                                code: carburetor::models::UploadTableResponseErrorType::Aborted,
+                                            })
+                                        })
+                                    })
+                                    .collect();
+                            )*
+                            Ok(response)
+                        }
+                        None => result,
+                    }
+                }
+            } else {
+                quote! {
+                    Ok(#upload_response_model_name {
+                        #(#field_assignments,)*
+                    })
+                }
             };
 
+            let call_with_connection = quote! {
+                #with_connection_function_name(connection, upload_request, #context_arg)#await_point
+            };
+            let global_body = mode.with_connection(
+                quote!(carburetor::helpers::),
+                quote!(connection),
+                call_with_connection.clone(),
+            );
+            let handle_body = mode.with_connection(
+                quote!(self.carburetor.),
+                quote!(connection),
+                call_with_connection,
+            );
+
             tokens.extend(quote! {
-                pub fn process_upload_request(
+                pub #asyncness fn #function_name(
+                    upload_request: #upload_request_model_name,
+                    #context_param
+                ) -> carburetor::error::Result<#upload_response_model_name> {
+                    #global_body
+                }
+
+                pub #asyncness fn #with_connection_function_name(
+                    connection: &mut #connection_type,
                     upload_request: #upload_request_model_name,
                     #context_param
                 ) -> carburetor::error::Result<#upload_response_model_name> {
 
                     #(#table_process_functions)*
 
-                    let mut connection = carburetor::helpers::get_connection()?;
+                    #process_tables
+                }
 
-                    Ok(#upload_response_model_name {
-                        #(#field_assignments,)*
-                    })
+                impl #handle_name {
+                    pub #asyncness fn #function_name(
+                        &self,
+                        upload_request: #upload_request_model_name,
+                        #context_param
+                    ) -> carburetor::error::Result<#upload_response_model_name> {
+                        #handle_body
+                    }
                 }
             });
         }
@@ -587,18 +1340,23 @@ pub fn generate_upload_sync_group_functions(
 ) {
     if get_target_type() == TargetType::Client {
         use crate::generators::upload::functions::client::{
-            AsProcessUploadResponseFunction, AsRetrieveUploadFunction,
+            AsProcessUploadResponseFunction, AsRetrieveUploadFunction, AsUploadFailureFunctions,
         };
         use quote::ToTokens;
 
         tokens.extend(AsRetrieveUploadFunction(sync_group).to_token_stream());
         tokens.extend(AsProcessUploadResponseFunction(sync_group).to_token_stream());
+        tokens.extend(AsUploadFailureFunctions(sync_group).to_token_stream());
     }
 
     if get_target_type() == TargetType::Backend {
         use crate::generators::upload::functions::backend::AsProcessUploadFunction;
         use quote::ToTokens;
 
-        tokens.extend(AsProcessUploadFunction(sync_group).to_token_stream());
+        crate::helpers::ExecutionMode::all()
+            .into_iter()
+            .for_each(|mode| {
+                tokens.extend(AsProcessUploadFunction(sync_group, mode).to_token_stream())
+            });
     }
 }
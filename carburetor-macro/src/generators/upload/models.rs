@@ -8,10 +8,7 @@ use crate::{
     helpers::{TargetType, get_target_type},
     parsers::{
         sync_group::{CarburetorSyncGroup, SyncGroupTableConfig},
-        table::{
-            column::{CarburetorColumnType, ColumnScope},
-            postgres_type::{DieselPostgresGeneric1Type, DieselPostgresType},
-        },
+        table::column::{CarburetorColumnType, ColumnScope, MergeStrategy},
     },
 };
 
@@ -27,7 +24,7 @@ pub mod client {
         generators::{client::models::AsTableMetadata, diesel::models::AsFullModel},
         parsers::{
             sync_group::SyncGroupTableConfig,
-            table::column::{CarburetorColumnType, ColumnScope},
+            table::column::{CarburetorColumnType, ColumnScope, MergeStrategy},
         },
     };
 
@@ -35,7 +32,15 @@ pub mod client {
 
     impl<'a> AsFromFullToTable<'a> {
         pub fn get_function_name(&self) -> Ident {
-            format_ident!("into_upload_request")
+            format_ident!("{}_into_upload_request", self.0.reference_table.ident)
+        }
+
+        /// Increments of `#[crdt(counter)]` columns are uploaded along with the ID of the device,
+        /// which the function then takes as `device_id`.
+        pub fn has_counter_columns(&self) -> bool {
+            self.0.reference_table.columns.iter().any(|x| {
+                x.column_scope == ColumnScope::Both && x.merge_strategy == MergeStrategy::Counter
+            })
         }
     }
 
@@ -53,7 +58,7 @@ pub mod client {
                 .filter_map(|x| {
                     if x.column_scope == ColumnScope::Both {
                         let field_name = &x.ident;
-                        Some(quote!(#field_name: self.#field_name))
+                        Some(quote!(#field_name: record.#field_name))
                     } else {
                         None
                     }
@@ -68,14 +73,39 @@ pub mod client {
                 .filter_map(|x| {
                     if x.column_type == CarburetorColumnType::Id {
                         let field_name = &x.ident;
-                        Some(quote!(#field_name: self.#field_name))
+                        Some(quote!(#field_name: record.#field_name))
+                    } else if x.column_scope == ColumnScope::Both
+                        && x.merge_strategy == MergeStrategy::Counter
+                    {
+                        // The increments are uploaded instead of the value, which the backend
+                        // adds to its own value
+                        let field_name = &x.ident;
+                        Some(quote! {
+                            #field_name: sync_metadata.#field_name.as_ref().and_then(|x| {
+                                carburetor::helpers::counter::pending_increments(x, device_id, cutoff_time)
+                            })
+                        })
+                    } else if x.column_scope == ColumnScope::Both
+                        && x.merge_strategy == MergeStrategy::JsonKeys
+                    {
+                        // Only the dirty keys are uploaded, which the backend applies over its
+                        // own value
+                        let field_name = &x.ident;
+                        Some(quote! {
+                            #field_name: match sync_metadata.#field_name {
+                                Some(ref metadata) if metadata.dirty_at.is_some_and(|x| x <= cutoff_time) => {
+                                    Some(carburetor::helpers::json_keys::dirty_keys_patch(&record.#field_name, metadata))
+                                }
+                                _ => None
+                            }
+                        })
                     } else if x.column_scope == ColumnScope::Both && !x.is_immutable {
                         let field_name = &x.ident;
                         Some(quote! {
                             #field_name: match sync_metadata.#field_name {
                                 Some(carburetor::helpers::client_sync_metadata::Metadata {
                                     dirty_at: Some(dirty_at), ..
-                                }) if dirty_at <= cutoff_time => Some(self.#field_name),
+                                }) if dirty_at <= cutoff_time => Some(record.#field_name),
                                 _ => None
                             }
                         })
@@ -85,44 +115,99 @@ pub mod client {
                 })
                 .collect::<Vec<_>>();
 
-            let dirty_flag_column = &self
+            // The backend only applies a column when its edit is newer than the one it has
+            let lww_columns = self
                 .0
                 .reference_table
-                .sync_metadata_columns
-                .dirty_flag
-                .deref()
-                .ident;
-            let client_column_metadata_column = &self
+                .columns
+                .iter()
+                .filter(|x| {
+                    x.column_scope == ColumnScope::Both
+                        && x.column_type != CarburetorColumnType::Id
+                        && !x.is_immutable
+                        && x.merge_strategy == MergeStrategy::Value
+                })
+                .collect::<Vec<_>>();
+            let column_insert_times = lww_columns
+                .iter()
+                .map(|x| {
+                    let field_name = &x.ident;
+                    let field_name_str = field_name.to_string();
+                    quote! {
+                        if let Some(edited) = sync_metadata
+                            .data
+                            .as_ref()
+                            .and_then(|x| x.#field_name.as_ref())
+                            .and_then(|x| x.dirty_at)
+                            .or(sync_metadata.insert_time)
+                        {
+                            edited_at.insert(#field_name_str.to_string(), edited);
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            let column_edit_times = lww_columns
+                .iter()
+                .map(|x| {
+                    let field_name = &x.ident;
+                    let field_name_str = field_name.to_string();
+                    quote! {
+                        if let Some(carburetor::helpers::client_sync_metadata::Metadata {
+                            dirty_at: Some(dirty_at), ..
+                        }) = sync_metadata.#field_name {
+                            if dirty_at <= cutoff_time {
+                                edited_at.insert(#field_name_str.to_string(), dirty_at);
+                            }
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let dirty_flag_column = &self
                 .0
                 .reference_table
                 .sync_metadata_columns
-                .client_column_sync_metadata
+                .dirty_flag
                 .deref()
                 .ident;
-
             let sync_metadata_model_name =
                 AsTableMetadata(&self.0.reference_table).get_struct_name();
+            let device_id_param = if self.has_counter_columns() {
+                quote!(device_id: &str,)
+            } else {
+                quote!()
+            };
 
+            // The model is shared by every group syncing the table, so this is not a method
             tokens.extend(quote! {
-            impl #full_model_name {
-                fn #function_name(self, cutoff_time: carburetor::chrono::DateTimeUtc) -> Option<#upload_request_table_name> {
+                fn #function_name(
+                    record: #full_model_name,
+                    sync_metadata: carburetor::helpers::client_sync_metadata::ClientSyncMetadata<#sync_metadata_model_name>,
+                    #device_id_param
+                    cutoff_time: carburetor::chrono::DateTimeUtc,
+                ) -> Option<#upload_request_table_name> {
                     use carburetor::helpers::client_sync_metadata::DirtyFlag;
-                    match self.#dirty_flag_column {
+                    match record.#dirty_flag_column {
                         Some(ref x) if x == &DirtyFlag::Insert.to_string() => {
+                            let mut edited_at = std::collections::BTreeMap::new();
+                            #(#column_insert_times)*
                             Some(#upload_request_table_name::Insert(#upload_insert_table_name {
                                 #(#upload_insert_table_fields,)*
+                                carburetor_edited_at: edited_at,
                             }))
                         }
                         Some(ref x) if x == &DirtyFlag::Update.to_string() => {
-                            let sync_metadata: #sync_metadata_model_name = carburetor::serde_json::from_value(self.#client_column_metadata_column).unwrap_or_default();
+                            let sync_metadata = sync_metadata.data.unwrap_or_default();
+                            let mut edited_at = std::collections::BTreeMap::new();
+                            #(#column_edit_times)*
                             Some(#upload_request_table_name::Update(#upload_update_table_name {
                                 #(#upload_update_table_fields,)*
+                                carburetor_edited_at: edited_at,
                             }))
                         }
                         _ => None,
                     }
                 }
-            }
         });
         }
     }
@@ -137,7 +222,7 @@ pub mod backend {
         generators::diesel::models::{AsChangesetModel, AsInsertModel},
         parsers::{
             sync_group::SyncGroupTableConfig,
-            table::column::{CarburetorColumnType, ColumnScope, DefaultValue},
+            table::column::{CarburetorColumnType, ColumnScope, DefaultValue, MergeStrategy},
         },
     };
 
@@ -195,6 +280,49 @@ pub mod backend {
         }
     }
 
+    pub struct AsFromUploadInsertToUpdate<'a>(pub &'a SyncGroupTableConfig);
+
+    impl<'a> ToTokens for AsFromUploadInsertToUpdate<'a> {
+        fn to_tokens(&self, tokens: &mut TokenStream) {
+            let upload_insert_model_name = AsUploadInsertTable(self.0).get_model_name();
+            let upload_update_model_name = AsUploadUpdateTable(self.0).get_model_name();
+
+            let columns = self
+                .0
+                .reference_table
+                .columns
+                .iter()
+                .filter_map(|x| {
+                    let field_name = &x.ident;
+                    if x.column_type == CarburetorColumnType::Id {
+                        Some(quote!(#field_name: value.#field_name.clone()))
+                    } else if x.column_scope != ColumnScope::Both || x.is_immutable {
+                        None
+                    } else if x.merge_strategy != MergeStrategy::Value {
+                        // The inserted value already holds the keys and increments of the insert
+                        Some(quote!(#field_name: None))
+                    } else if x.default_value.is_some() {
+                        // Omitted by an old client, so left unchanged
+                        Some(quote!(#field_name: value.#field_name.clone()))
+                    } else {
+                        Some(quote!(#field_name: Some(value.#field_name.clone())))
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            tokens.extend(quote! {
+                impl From<&#upload_insert_model_name> for #upload_update_model_name {
+                    fn from(value: &#upload_insert_model_name) -> Self {
+                        Self {
+                            #(#columns,)*
+                            carburetor_edited_at: value.carburetor_edited_at.clone(),
+                        }
+                    }
+                }
+            });
+        }
+    }
+
     pub struct AsFromUploadUpdateToChangeset<'a>(pub &'a SyncGroupTableConfig);
 
     impl<'a> ToTokens for AsFromUploadUpdateToChangeset<'a> {
@@ -218,6 +346,10 @@ pub mod backend {
 
                     match x.column_scope {
                         ColumnScope::ModOnBackendOnly => Some(quote!(#field_name: None)),
+                        // The patch or increment is applied over the stored value when updating
+                        _ if x.merge_strategy != MergeStrategy::Value => {
+                            Some(quote!(#field_name: None))
+                        }
                         _ => Some(quote!(#field_name: value.#field_name)),
                     }
                 })
@@ -236,7 +368,7 @@ pub mod backend {
     }
 }
 
-struct AsUploadUpdateTable<'a>(&'a SyncGroupTableConfig);
+pub struct AsUploadUpdateTable<'a>(pub &'a SyncGroupTableConfig);
 
 impl<'a> ToTokens for AsUploadUpdateTable<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
@@ -248,6 +380,20 @@ impl<'a> ToTokens for AsUploadUpdateTable<'a> {
                 Some(quote! {
                     pub #field_name: #field_type
                 })
+            } else if x.column_scope == ColumnScope::Both
+                && x.merge_strategy == MergeStrategy::JsonKeys
+            {
+                let field_name = &x.ident;
+                Some(quote! {
+                    pub #field_name: Option<carburetor::models::JsonKeysPatch>
+                })
+            } else if x.column_scope == ColumnScope::Both
+                && x.merge_strategy == MergeStrategy::Counter
+            {
+                let field_name = &x.ident;
+                Some(quote! {
+                    pub #field_name: Option<carburetor::models::CounterIncrements>
+                })
             } else if x.column_scope == ColumnScope::Both && !x.is_immutable {
                 let field_name = &x.ident;
                 let field_type = AsModelType(&x.diesel_type);
@@ -262,13 +408,16 @@ impl<'a> ToTokens for AsUploadUpdateTable<'a> {
             #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
             pub struct #model_name {
                 #(#fields,)*
+                /// Time each uploaded column was edited on the client
+                #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+                pub carburetor_edited_at: std::collections::BTreeMap<String, carburetor::chrono::DateTimeUtc>,
             }
         });
     }
 }
 
 impl<'a> AsUploadUpdateTable<'a> {
-    fn get_model_name(&self) -> Ident {
+    pub fn get_model_name(&self) -> Ident {
         format_ident!(
             "UploadUpdate{}",
             self.0
@@ -298,11 +447,7 @@ impl<'a> ToTokens for AsUploadInsertTable<'a> {
                 // Client always includes all its own columns, so no padding needed.
                 TargetType::Backend => {
                     if x.default_value.is_some() {
-                        let is_nullable = matches!(
-                            x.diesel_type,
-                            DieselPostgresType::Generic1(DieselPostgresGeneric1Type::Nullable, _)
-                        );
-                        if is_nullable {
+                        if x.diesel_type.is_nullable() {
                             Some(quote! {
                                 #[serde(default, deserialize_with = "carburetor::helpers::serde_optional::double_optional::deserialize")]
                                 pub #field_name: Option<#field_type>
@@ -324,6 +469,10 @@ impl<'a> ToTokens for AsUploadInsertTable<'a> {
             #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
             pub struct #model_name {
                 #(#fields,)*
+                /// Time each column was last written on the client, either by the insert or by a
+                /// later local update
+                #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+                pub carburetor_edited_at: std::collections::BTreeMap<String, carburetor::chrono::DateTimeUtc>,
             }
         });
     }
@@ -412,8 +561,9 @@ impl<'a> ToTokens for AsUploadResponseModel<'a> {
         let model_name = self.get_model_name();
         let fields = self.0.table_configs.iter().map(|x| {
             let field_name = &x.reference_table.ident;
+            let id_type = AsModelType(&x.reference_table.sync_metadata_columns.id.diesel_type);
             quote! {
-                pub #field_name: Vec<Result<carburetor::models::UploadTableResponseData, carburetor::models::UploadTableResponseError>>
+                pub #field_name: Vec<Result<carburetor::models::UploadTableResponseData<#id_type>, carburetor::models::UploadTableResponseError<#id_type>>>
             }
         });
 
@@ -446,11 +596,14 @@ pub fn generate_upload_sync_group_models(
             }
             TargetType::Backend => {
                 use crate::generators::upload::models::backend::{
-                    AsFromUploadInsertToInsertModel, AsFromUploadUpdateToChangeset,
+                    AsFromUploadInsertToInsertModel, AsFromUploadInsertToUpdate,
+                    AsFromUploadUpdateToChangeset,
                 };
                 let from_insert_to_full = AsFromUploadInsertToInsertModel(x);
+                let from_insert_to_update = AsFromUploadInsertToUpdate(x);
                 let from_update_to_changeset = AsFromUploadUpdateToChangeset(x);
-                conversion_functions = quote!(#from_insert_to_full #from_update_to_changeset);
+                conversion_functions =
+                    quote!(#from_insert_to_full #from_insert_to_update #from_update_to_changeset);
             }
         }
 
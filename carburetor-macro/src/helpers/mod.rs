@@ -1,8 +1,10 @@
 use std::{any::type_name, env::var};
 
-use proc_macro2::Span;
-use quote::ToTokens;
-use syn::{Error, Result, parse::Parse, parse_quote_spanned, parse_str, parse2, spanned::Spanned};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    Error, Ident, Result, parse::Parse, parse_quote_spanned, parse_str, parse2, spanned::Spanned,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TargetType {
@@ -38,3 +40,106 @@ pub(crate) fn parse_str_as<T: Parse + Spanned + ToTokens>(code_str: &str, span:
 
     Ok(out)
 }
+
+/// Flavour of the generated backend functions. Every generator emitting database access on the
+/// backend produces one set of functions per mode returned by [`ExecutionMode::all`], sharing the
+/// same body with only connection type, imports and `.await` points swapped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ExecutionMode {
+    Blocking,
+    #[cfg(feature = "async")]
+    Async,
+}
+
+impl ExecutionMode {
+    pub(crate) fn all() -> Vec<Self> {
+        vec![
+            Self::Blocking,
+            #[cfg(feature = "async")]
+            Self::Async,
+        ]
+    }
+
+    /// Name of the generated function for this mode: async functions get an `_async` suffix.
+    pub(crate) fn function_name(&self, name: &str) -> Ident {
+        match self {
+            Self::Blocking => Ident::new(name, Span::call_site()),
+            #[cfg(feature = "async")]
+            Self::Async => Ident::new(&format!("{}_async", name), Span::call_site()),
+        }
+    }
+
+    pub(crate) fn asyncness(&self) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(),
+            #[cfg(feature = "async")]
+            Self::Async => quote!(async),
+        }
+    }
+
+    pub(crate) fn await_point(&self) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(),
+            #[cfg(feature = "async")]
+            Self::Async => quote!(.await),
+        }
+    }
+
+    pub(crate) fn pg_connection_type(&self) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(diesel::PgConnection),
+            #[cfg(feature = "async")]
+            Self::Async => quote!(carburetor::diesel_async::AsyncPgConnection),
+        }
+    }
+
+    /// Runs `call` with a connection bound to `conn`, obtained through `receiver` which is either
+    /// `carburetor::helpers::` (global config) or `self.carburetor.` (per-instance handle).
+    pub(crate) fn with_connection(
+        &self,
+        receiver: TokenStream,
+        conn: TokenStream,
+        call: TokenStream,
+    ) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(#receiver with_connection(|#conn| #call)),
+            #[cfg(feature = "async")]
+            Self::Async => quote!({
+                let mut #conn = #receiver get_async_connection().await?;
+                let #conn = &mut *#conn;
+                #call
+            }),
+        }
+    }
+
+    /// Runs `body` inside a transaction on `conn`, configured by the `TransactionBuilder` calls in
+    /// `builder` (e.g. `.repeatable_read()`). `body` evaluates to a `carburetor::error::Result`.
+    pub(crate) fn transaction(
+        &self,
+        conn: TokenStream,
+        builder: TokenStream,
+        body: TokenStream,
+    ) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(#conn.build_transaction()#builder.run(|#conn| { #body })),
+            #[cfg(feature = "async")]
+            Self::Async => quote!({
+                use carburetor::diesel_async::scoped_futures::ScopedFutureExt;
+                #conn
+                    .build_transaction()
+                    #builder
+                    .run(|#conn| async move { #body }.scope_boxed())
+                    .await
+            }),
+        }
+    }
+
+    /// Path of the `RunQueryDsl` trait providing `load`, `get_result`, etc. for this mode.
+    pub(crate) fn run_query_dsl(&self) -> TokenStream {
+        match self {
+            Self::Blocking => quote!(diesel::RunQueryDsl),
+            #[cfg(feature = "async")]
+            Self::Async => quote!(carburetor::diesel_async::RunQueryDsl),
+        }
+    }
+}
@@ -6,9 +6,13 @@ mod parsers;
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
 use syn::parse_macro_input;
 
-use crate::{generators::generate_carburetor_sync_config, parsers::CarburetorSyncConfig};
+use crate::{
+    generators::{generate_carburetor_sync_config, sync_enum::AsSyncEnum},
+    parsers::{CarburetorSyncConfig, sync_enum::CarburetorSyncEnum},
+};
 
 #[proc_macro]
 pub fn carburetor_sync_config(input: TokenStream) -> TokenStream {
@@ -17,3 +21,19 @@ pub fn carburetor_sync_config(input: TokenStream) -> TokenStream {
     generate_carburetor_sync_config(&mut tokens, sync_group);
     tokens.into()
 }
+
+/// Makes a Rust enum usable in `Enum<T>` columns, by storing the variant as text.
+///
+/// Variants are stored in snake case unless renamed with `#[sync_enum(rename = "...")]`. Only
+/// enums with variants without fields are supported.
+#[proc_macro_attribute]
+pub fn sync_enum(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let attr = TokenStream2::from(attr);
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(attr, "#[sync_enum] does not take arguments")
+            .to_compile_error()
+            .into();
+    }
+    let sync_enum = parse_macro_input!(input as CarburetorSyncEnum);
+    AsSyncEnum(&sync_enum).into_token_stream().into()
+}
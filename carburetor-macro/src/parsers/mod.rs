@@ -1,3 +1,4 @@
+pub(crate) mod sync_enum;
 pub(crate) mod sync_group;
 pub(crate) mod syntax;
 pub(crate) mod table;
@@ -59,6 +60,7 @@ impl Parse for CarburetorSyncConfig {
                         .map(|x| {
                             Ok(CarburetorSyncGroup::from_lookup_table_names(
                                 x.ident,
+                                &x.arguments,
                                 &Punctuated::<DeclarationSettingBlock, token::Comma>::parse_terminated
                                     .parse2(x.content)?
                                     .into_iter()
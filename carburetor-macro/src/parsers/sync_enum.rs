@@ -0,0 +1,144 @@
+use heck::ToSnakeCase;
+use syn::{
+    Attribute, Error, Fields, Ident, ItemEnum, LitStr, Result,
+    parse::{Parse, ParseStream},
+};
+
+const ATTRIBUTE_NAME: &str = "sync_enum";
+
+pub(crate) struct SyncEnumVariant {
+    pub(crate) ident: Ident,
+    /// Text stored in the database, which defaults to the variant name in snake case.
+    pub(crate) value: String,
+}
+
+pub(crate) struct CarburetorSyncEnum {
+    /// The enum without the `#[sync_enum(...)]` attributes of its variants.
+    pub(crate) item: ItemEnum,
+    pub(crate) variants: Vec<SyncEnumVariant>,
+}
+
+impl Parse for CarburetorSyncEnum {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut item: ItemEnum = input.parse()?;
+        if !item.generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                &item.generics,
+                "#[sync_enum] does not support generic enums",
+            ));
+        }
+
+        let mut variants = vec![];
+        for variant in item.variants.iter_mut() {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(Error::new_spanned(
+                    &variant.fields,
+                    "#[sync_enum] only supports variants without fields",
+                ));
+            }
+            let rename = take_rename(&mut variant.attrs)?;
+            let value = rename.unwrap_or_else(|| variant.ident.to_string().to_snake_case());
+            if variants.iter().any(|x: &SyncEnumVariant| x.value == value) {
+                return Err(Error::new_spanned(
+                    &variant.ident,
+                    format!("Another variant is already stored as \"{}\"", value),
+                ));
+            }
+            variants.push(SyncEnumVariant {
+                ident: variant.ident.clone(),
+                value,
+            });
+        }
+
+        Ok(Self { item, variants })
+    }
+}
+
+/// Removes `#[sync_enum(rename = "...")]` from the attributes, and returns the given name.
+fn take_rename(attrs: &mut Vec<Attribute>) -> Result<Option<String>> {
+    let mut rename = None;
+    let mut error = None;
+    attrs.retain(|attr| {
+        if !attr.path().is_ident(ATTRIBUTE_NAME) {
+            return true;
+        }
+        if let Err(e) = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown argument, expected `rename = \"...\"`"))
+            }
+        }) {
+            error = Some(e);
+        }
+        false
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(rename),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse2;
+
+    #[test]
+    fn test_parse_variants() {
+        let input = quote! {
+            #[derive(Debug, Clone)]
+            pub enum OrderStatus {
+                Pending,
+                InTransit,
+                #[sync_enum(rename = "done")]
+                Delivered,
+            }
+        };
+
+        let result: CarburetorSyncEnum = parse2(input).unwrap();
+
+        let values = result
+            .variants
+            .iter()
+            .map(|x| x.value.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["pending", "in_transit", "done"]);
+        assert!(result.item.variants.iter().all(|x| x.attrs.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_variant_with_fields() {
+        let input = quote! {
+            enum OrderStatus {
+                Pending,
+                Cancelled(String),
+            }
+        };
+
+        let err = parse2::<CarburetorSyncEnum>(input)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("only supports variants without fields"));
+    }
+
+    #[test]
+    fn test_parse_duplicate_value() {
+        let input = quote! {
+            enum OrderStatus {
+                Pending,
+                #[sync_enum(rename = "pending")]
+                Waiting,
+            }
+        };
+
+        let err = parse2::<CarburetorSyncEnum>(input)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("already stored as \"pending\""));
+    }
+}
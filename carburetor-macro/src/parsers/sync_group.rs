@@ -2,11 +2,14 @@ use std::{collections::HashMap, rc::Rc};
 
 use proc_macro2::Span;
 use quote::ToTokens;
-use syn::{Error, Ident, Result};
+use syn::{Error, Ident, LitBool, Result};
 
-use crate::parsers::{
-    syntax::block::{DeclarationArgument, DeclarationSettingBlock},
-    table::{CarburetorTable, column::CarburetorColumn, postgres_type::DieselPostgresType},
+use crate::{
+    helpers::parse_as,
+    parsers::{
+        syntax::block::{DeclarationArgument, DeclarationSettingBlock},
+        table::{CarburetorTable, column::CarburetorColumn, postgres_type::DieselPostgresType},
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -14,15 +17,40 @@ pub(crate) struct CarburetorSyncGroup {
     pub(crate) name: Ident,
     pub(crate) table_configs: Vec<SyncGroupTableConfig>,
     pub(crate) contexts: HashMap<String, DieselPostgresType>,
+
+    /// When set, the whole upload of the group is applied in one transaction and rolled back when
+    /// any row fails
+    pub(crate) is_atomic: bool,
 }
 
 impl CarburetorSyncGroup {
     pub(crate) fn from_lookup_table_names(
         name: Ident,
+        arguments: &[DeclarationArgument],
         table_settings: &[DeclarationSettingBlock],
         tables_lookup: &[Rc<CarburetorTable>],
     ) -> Result<Self> {
         let mut contexts = HashMap::new();
+        let mut is_atomic = None;
+        for arg in arguments {
+            match arg.name.to_string().as_str() {
+                "atomic" => {
+                    if is_atomic.is_some() {
+                        return Err(Error::new_spanned(&arg.name, "Duplicate arguments found"));
+                    }
+                    if arg.value.dollar_prefixed {
+                        return Err(Error::new_spanned(
+                            &arg.name,
+                            "Context variable cannot be used here",
+                        ));
+                    }
+                    is_atomic = Some(parse_as::<LitBool>(&arg.value.name)?.value());
+                }
+                _ => {
+                    return Err(Error::new_spanned(&arg.name, "Unknown argument found"));
+                }
+            }
+        }
         Ok(Self {
             name,
             table_configs: table_settings
@@ -56,6 +84,7 @@ impl CarburetorSyncGroup {
                 })
                 .collect::<Result<Vec<_>>>()?,
             contexts,
+            is_atomic: is_atomic.unwrap_or(false),
         })
     }
 }
@@ -73,6 +102,18 @@ pub struct SyncGroupTableConfig {
 }
 
 impl SyncGroupTableConfig {
+    /// Describes which rows of the table are synced by the group, e.g. `recipient_id = $user_id`,
+    /// or empty when every row is synced.
+    pub(crate) fn restriction(&self) -> String {
+        match self.restrict_to {
+            Some(ref restrict_to) => format!(
+                "{} = ${}",
+                restrict_to.column_reference.ident, restrict_to.context_variable
+            ),
+            None => String::new(),
+        }
+    }
+
     fn new_with_arguments(
         reference_table: Rc<CarburetorTable>,
         arguments: &[DeclarationArgument],
@@ -183,6 +224,7 @@ mod tests {
                 dirty_flag,
                 client_column_sync_metadata,
             },
+            change_tracking: Default::default(),
         })
     }
 
@@ -197,6 +239,7 @@ mod tests {
 
         let result = CarburetorSyncGroup::from_lookup_table_names(
             name.clone(),
+            &[],
             &table_settings,
             &tables_lookup,
         )
@@ -231,6 +274,7 @@ mod tests {
 
         let result = CarburetorSyncGroup::from_lookup_table_names(
             name.clone(),
+            &[],
             &table_settings,
             &tables_lookup,
         )
@@ -257,9 +301,92 @@ mod tests {
         }];
         let name = format_ident!("test_group");
 
-        let result =
-            CarburetorSyncGroup::from_lookup_table_names(name, &table_settings, &tables_lookup);
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            name,
+            &[],
+            &table_settings,
+            &tables_lookup,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_lookup_table_names_atomic_argument() {
+        use quote::quote;
+        use syn::parse2;
+
+        let tables_lookup = vec![create_test_table("user")];
+        let table_settings = vec![DeclarationSettingBlock {
+            ident: format_ident!("user"),
+            arguments: vec![],
+        }];
+
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            format_ident!("test_group"),
+            &[],
+            &table_settings,
+            &tables_lookup,
+        )
+        .unwrap();
+        assert!(!result.is_atomic);
+
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            format_ident!("test_group"),
+            &[parse2::<DeclarationArgument>(quote!(atomic)).unwrap()],
+            &table_settings,
+            &tables_lookup,
+        )
+        .unwrap();
+        assert!(result.is_atomic);
+
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            format_ident!("test_group"),
+            &[parse2::<DeclarationArgument>(quote!(atomic = false)).unwrap()],
+            &table_settings,
+            &tables_lookup,
+        )
+        .unwrap();
+        assert!(!result.is_atomic);
 
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            format_ident!("test_group"),
+            &[parse2::<DeclarationArgument>(quote!(unknown)).unwrap()],
+            &table_settings,
+            &tables_lookup,
+        );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_restriction() {
+        use quote::quote;
+        use syn::parse2;
+
+        let tables_lookup = vec![create_test_table("user")];
+        let table_settings = vec![
+            DeclarationSettingBlock {
+                ident: format_ident!("user"),
+                arguments: vec![],
+            },
+            DeclarationSettingBlock {
+                ident: format_ident!("user"),
+                arguments: vec![
+                    parse2::<DeclarationArgument>(quote!(restrict_to = $user_id)).unwrap(),
+                    parse2::<DeclarationArgument>(quote!(restrict_to_column = id)).unwrap(),
+                ],
+            },
+        ];
+
+        let result = CarburetorSyncGroup::from_lookup_table_names(
+            format_ident!("test_group"),
+            &[],
+            &table_settings,
+            &tables_lookup,
+        )
+        .unwrap();
+
+        assert_eq!(result.table_configs[0].restriction(), "");
+        assert_eq!(result.table_configs[1].restriction(), "id = $user_id");
+    }
 }
@@ -2,6 +2,7 @@ use proc_macro2::TokenStream;
 use syn::{
     Expr, ExprPath, Ident, Result, braced, parenthesized,
     parse::{Parse, ParseStream},
+    parse_quote_spanned,
     punctuated::Punctuated,
     token,
 };
@@ -58,6 +59,9 @@ impl Parse for DeclarationBlock {
 /// Basic setting block with no arguments: `foo`
 ///
 /// Setting block with arguments: `foo(a = "app", b = bar)`
+///
+/// Flag arguments without value are treated as `true`: `foo(atomic)` is the same as
+/// `foo(atomic = true)`
 #[derive(Debug, Clone)]
 pub(crate) struct DeclarationSettingBlock {
     pub(crate) ident: Ident,
@@ -86,7 +90,16 @@ pub(crate) struct DeclarationArgument {
 
 impl Parse for DeclarationArgument {
     fn parse(input: ParseStream) -> Result<Self> {
-        let name = input.parse()?;
+        let name: Ident = input.parse()?;
+        if !input.peek(token::Eq) {
+            return Ok(Self {
+                value: DeclarationArgumentValue {
+                    name: parse_quote_spanned! { name.span() => true },
+                    dollar_prefixed: false,
+                },
+                name,
+            });
+        }
         let _: token::Eq = input.parse()?;
 
         Ok(Self {
@@ -173,6 +186,23 @@ mod tests {
         assert!(result.arguments.is_empty());
     }
 
+    #[test]
+    fn test_parse_block_with_flag_argument() {
+        let input = quote! {
+            MyBlock(atomic, plural = "my_blocks") {}
+        };
+
+        let result: DeclarationBlock = parse2(input).unwrap();
+
+        assert_eq!(result.arguments.len(), 2);
+        assert_eq!(result.arguments[0].name.to_string(), "atomic");
+        assert_eq!(
+            result.arguments[0].value.name.to_token_stream().to_string(),
+            "true"
+        );
+        assert_eq!(result.arguments[1].name.to_string(), "plural");
+    }
+
     #[test]
     fn test_declaration_argument_value() {
         let result: DeclarationArgumentValue = parse2(quote!("value")).unwrap();
@@ -2,7 +2,10 @@ use std::{ops::Deref, rc::Rc};
 
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::{Error, Expr, ExprLit, Ident, Lit, Meta, Result, parse_quote};
+use syn::{
+    Error, Expr, ExprLit, Ident, Lit, Meta, Path, Result,
+    parse::{Parse, ParseStream},
+};
 
 use crate::parsers::{
     syntax::content::DieselTableStyleContent,
@@ -26,6 +29,10 @@ pub(crate) struct CarburetorColumn {
     pub(crate) default_value: Option<DefaultValue>,
     pub(crate) column_type: CarburetorColumnType,
     pub(crate) is_immutable: bool,
+    /// How new IDs are generated, only relevant to the `#[id]` column
+    pub(crate) id_strategy: IdStrategy,
+    /// How concurrent changes to the column are merged
+    pub(crate) merge_strategy: MergeStrategy,
 }
 
 impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
@@ -37,6 +44,8 @@ impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
         let mut column_scope = ColumnScope::default();
         let mut default_value = None;
         let mut is_immutable = false;
+        let mut id_strategy = IdStrategy::default();
+        let mut merge_strategy = MergeStrategy::default();
         let mut has_user_default = false;
 
         for attr in value.attrs.iter() {
@@ -116,14 +125,38 @@ impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
                 }
             }
 
-            let ident: Ident = parse_quote! {#attr};
+            // Doc comments are kept as `#[doc = "..."]` and have no meaning here
+            if attr.path().is_ident("doc") {
+                continue;
+            }
+            let Some(ident) = attr.path().get_ident().cloned() else {
+                return Err(Error::new_spanned(attr.path(), "unknown column attribute"));
+            };
             match ident.to_string().as_str() {
                 "id" => {
-                    if diesel_type != DieselPostgresType::Text {
-                        return Err(Error::new_spanned(
-                            value.name,
-                            "#[id] needs to be of type `Text`",
-                        ));
+                    if let Meta::List(list) = attr {
+                        id_strategy = list.parse_args()?;
+                    }
+                    match diesel_type {
+                        DieselPostgresType::Text => {}
+                        DieselPostgresType::Uuid => {
+                            id_strategy = match id_strategy {
+                                IdStrategy::Default => IdStrategy::UuidV7,
+                                IdStrategy::Ulid | IdStrategy::DeviceCounter => {
+                                    return Err(Error::new_spanned(
+                                        value.name,
+                                        "#[id] of type `Uuid` only accepts `uuid_v4`, `uuid_v7` or `with = <function>`",
+                                    ));
+                                }
+                                other => other,
+                            };
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(
+                                value.name,
+                                "#[id] needs to be of type `Text` or `Uuid`",
+                            ));
+                        }
                     }
                     if column_type != CarburetorColumnType::default() {
                         return Err(Error::new_spanned(value.name, dup_col_type_err_msg));
@@ -194,7 +227,36 @@ impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
                 "immutable" => {
                     is_immutable = true;
                 }
-                _ => {}
+                "merge" | "crdt" => {
+                    let Meta::List(list) = attr else {
+                        return Err(Error::new_spanned(
+                            attr,
+                            "expected `#[merge(json_keys)]` or `#[crdt(counter)]`",
+                        ));
+                    };
+                    if merge_strategy != MergeStrategy::default() {
+                        return Err(Error::new_spanned(
+                            value.name,
+                            "column can only be assigned once with either #[merge(...)] or #[crdt(...)]",
+                        ));
+                    }
+                    merge_strategy = match (ident.to_string().as_str(), list.parse_args()?) {
+                        ("merge", strategy @ MergeStrategy::JsonKeys)
+                        | ("crdt", strategy @ MergeStrategy::Counter) => strategy,
+                        ("merge", _) => {
+                            return Err(Error::new_spanned(attr, "expected `#[merge(json_keys)]`"));
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(attr, "expected `#[crdt(counter)]`"));
+                        }
+                    };
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        &ident,
+                        format!("unknown column attribute `#[{ident}]`"),
+                    ));
+                }
             }
         }
         if has_user_default && column_type != CarburetorColumnType::Data {
@@ -211,6 +273,39 @@ impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
                 "#[immutable] can only be applied to non-special data columns",
             ));
         }
+        if merge_strategy == MergeStrategy::JsonKeys {
+            if column_type != CarburetorColumnType::Data || is_immutable {
+                return Err(Error::new_spanned(
+                    value.name,
+                    "#[merge(json_keys)] can only be applied to mutable data columns",
+                ));
+            }
+            if diesel_type != DieselPostgresType::Jsonb {
+                return Err(Error::new_spanned(
+                    value.name,
+                    "#[merge(json_keys)] needs to be of type `Jsonb`",
+                ));
+            }
+        }
+        if merge_strategy == MergeStrategy::Counter {
+            if column_type != CarburetorColumnType::Data || is_immutable {
+                return Err(Error::new_spanned(
+                    value.name,
+                    "#[crdt(counter)] can only be applied to mutable data columns",
+                ));
+            }
+            if !matches!(
+                diesel_type,
+                DieselPostgresType::SmallInt
+                    | DieselPostgresType::Integer
+                    | DieselPostgresType::BigInt
+            ) {
+                return Err(Error::new_spanned(
+                    value.name,
+                    "#[crdt(counter)] needs to be of type `SmallInt`, `Integer` or `BigInt`",
+                ));
+            }
+        }
         if column_scope != ColumnScope::Both && default_value.is_none() {
             return Err(Error::new_spanned(
                 value.name,
@@ -228,6 +323,8 @@ impl TryFrom<DieselTableStyleContent> for CarburetorColumn {
             default_value,
             column_type,
             is_immutable,
+            id_strategy,
+            merge_strategy,
         })
     }
 }
@@ -287,18 +384,26 @@ impl SqlDefault {
                 }
             }
             SqlDefault::EmptyJson => {
-                if !matches!(diesel_type.unwrap_nullable(), DieselPostgresType::Jsonb) {
+                if !matches!(
+                    diesel_type.unwrap_nullable(),
+                    DieselPostgresType::Jsonb | DieselPostgresType::Json
+                ) {
                     return Err(Error::new_spanned(
                         column_name,
-                        "sql default `EmptyJson` is only compatible with Jsonb and Nullable<Jsonb>",
+                        "sql default `EmptyJson` is only compatible with Jsonb, Json, and their Nullable variants",
                     ));
                 }
             }
             SqlDefault::Text(_) => {
-                if !matches!(diesel_type.unwrap_nullable(), DieselPostgresType::Text) {
+                if !matches!(
+                    diesel_type.unwrap_nullable(),
+                    DieselPostgresType::Text
+                        | DieselPostgresType::Varchar
+                        | DieselPostgresType::Enum(_)
+                ) {
                     return Err(Error::new_spanned(
                         column_name,
-                        "sql default `Text` is only compatible with Text and Nullable<Text>",
+                        "sql default `Text` is only compatible with Text, Varchar, Enum, and their Nullable variants",
                     ));
                 }
             }
@@ -310,10 +415,11 @@ impl SqlDefault {
                         | DieselPostgresType::BigInt
                         | DieselPostgresType::Float
                         | DieselPostgresType::Double
+                        | DieselPostgresType::Numeric
                 ) {
                     return Err(Error::new_spanned(
                         column_name,
-                        "sql default `Number` is only compatible with SmallInt, Integer, BigInt, Float, Double, and their Nullable variants",
+                        "sql default `Number` is only compatible with SmallInt, Integer, BigInt, Float, Double, Numeric, and their Nullable variants",
                     ));
                 }
             }
@@ -408,6 +514,68 @@ fn parse_sql_default(expr: &Expr) -> Result<SqlDefault> {
     }
 }
 
+/// Strategy given with `#[id(...)]`, e.g. `#[id(uuid_v7)]` or `#[id(with = my_crate::new_id)]`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) enum IdStrategy {
+    /// Table name, random part and Unix second
+    #[default]
+    Default,
+    UuidV4,
+    UuidV7,
+    Ulid,
+    /// Random ID of the device, followed by a counter stored in the client database
+    DeviceCounter,
+    /// Path to a user function of signature `fn() -> String`
+    Custom(Path),
+}
+
+impl Parse for IdStrategy {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let expected =
+            "expected `uuid_v4`, `uuid_v7`, `ulid`, `device_counter` or `with = <function>`";
+        match input.parse::<Meta>()? {
+            Meta::Path(path) => match path.get_ident().map(|x| x.to_string()).as_deref() {
+                Some("uuid_v4") => Ok(Self::UuidV4),
+                Some("uuid_v7") => Ok(Self::UuidV7),
+                Some("ulid") => Ok(Self::Ulid),
+                Some("device_counter") => Ok(Self::DeviceCounter),
+                _ => Err(Error::new_spanned(path, expected)),
+            },
+            Meta::NameValue(nv) if nv.path.is_ident("with") => match nv.value {
+                Expr::Path(function) => Ok(Self::Custom(function.path)),
+                other => Err(Error::new_spanned(other, "expected a path to a function")),
+            },
+            other => Err(Error::new_spanned(other, expected)),
+        }
+    }
+}
+
+/// Strategy given with `#[merge(...)]` or `#[crdt(...)]`, e.g. `#[merge(json_keys)]`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) enum MergeStrategy {
+    /// The last write of the whole value wins
+    #[default]
+    Value,
+    /// The last write of each top-level key of a JSON object wins
+    JsonKeys,
+    /// Increments made on each device are summed, given with `#[crdt(counter)]`
+    Counter,
+}
+
+impl Parse for MergeStrategy {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "json_keys" => Ok(Self::JsonKeys),
+            "counter" => Ok(Self::Counter),
+            _ => Err(Error::new_spanned(
+                ident,
+                "expected `json_keys` or `counter`",
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub(crate) enum CarburetorColumnType {
     Id,
@@ -438,6 +606,8 @@ impl Default for IdColumn {
             column_scope: ColumnScope::Both,
             default_value: None,
             is_immutable: true,
+            id_strategy: IdStrategy::default(),
+            merge_strategy: MergeStrategy::default(),
         }))
     }
 }
@@ -461,6 +631,8 @@ impl Default for LastSyncedAtColumn {
             column_scope: ColumnScope::ModOnBackendOnly,
             default_value: Some(DefaultValue::Rust(quote!(diesel::dsl::now))),
             is_immutable: false,
+            id_strategy: IdStrategy::default(),
+            merge_strategy: MergeStrategy::default(),
         }))
     }
 }
@@ -484,6 +656,8 @@ impl Default for IsDeletedColumn {
             column_scope: ColumnScope::Both,
             default_value: None,
             is_immutable: false,
+            id_strategy: IdStrategy::default(),
+            merge_strategy: MergeStrategy::default(),
         }))
     }
 }
@@ -510,6 +684,8 @@ impl Default for DirtyFlagColumn {
             column_scope: ColumnScope::ClientOnly,
             default_value: Some(DefaultValue::Rust(quote!(None))),
             is_immutable: false,
+            id_strategy: IdStrategy::default(),
+            merge_strategy: MergeStrategy::default(),
         }))
     }
 }
@@ -535,6 +711,8 @@ impl Default for ClientColumnSyncMetadata {
                 carburetor::serde_json::from_str("{}").unwrap()
             ))),
             is_immutable: false,
+            id_strategy: IdStrategy::default(),
+            merge_strategy: MergeStrategy::default(),
         }))
     }
 }
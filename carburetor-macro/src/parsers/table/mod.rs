@@ -29,6 +29,20 @@ pub(crate) struct CarburetorTable {
 
     /// Special columns that we need to refer to
     pub(crate) sync_metadata_columns: SyncMetadataColumns,
+
+    /// How the backend tells the rows changed since the last download
+    pub(crate) change_tracking: ChangeTracking,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) enum ChangeTracking {
+    /// Rows with `last_synced_at` after the offset. A row written by a transaction that commits
+    /// after a download, but started before its cutoff, is missed by that client.
+    #[default]
+    Timestamp,
+    /// Rows written by transactions that were not committed yet at the last download, tracked with
+    /// a backend-only `carburetor_xid` column.
+    Xid,
 }
 
 impl Parse for CarburetorTable {
@@ -42,6 +56,7 @@ impl Parse for CarburetorTable {
         let mut is_deleted_column = None;
         let mut dirty_flag_column = None;
         let mut plural_ident = None;
+        let mut change_tracking = None;
 
         for arg in block.arguments {
             match arg.name.to_string().as_str() {
@@ -60,6 +75,28 @@ impl Parse for CarburetorTable {
                         arg.name.span(),
                     )?);
                 }
+                "change_tracking" => {
+                    if change_tracking.is_some() {
+                        return Err(Error::new_spanned(&arg.name, "Duplicate arguments found"));
+                    }
+                    if arg.value.dollar_prefixed {
+                        return Err(Error::new_spanned(
+                            &arg.name,
+                            "Context variable cannot be used here",
+                        ));
+                    }
+                    let value = parse_as::<LitStr>(&arg.value.name)?;
+                    change_tracking = Some(match value.value().as_str() {
+                        "timestamp" => ChangeTracking::Timestamp,
+                        "xid" => ChangeTracking::Xid,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                value,
+                                "change_tracking can only be \"timestamp\" or \"xid\"",
+                            ));
+                        }
+                    });
+                }
                 _ => {
                     return Err(Error::new_spanned(&arg.name, "Unknown argument found"));
                 }
@@ -176,6 +213,7 @@ impl Parse for CarburetorTable {
                 dirty_flag: dirty_flag_column,
                 client_column_sync_metadata: client_column_sync_metadata_column,
             },
+            change_tracking: change_tracking.unwrap_or_default(),
         })
     }
 }
@@ -183,6 +221,8 @@ impl Parse for CarburetorTable {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parsers::table::column::{IdStrategy, MergeStrategy};
+    use crate::parsers::table::postgres_type::DieselPostgresType;
     use quote::quote;
     use syn::parse2;
 
@@ -211,6 +251,206 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_diesel_type_aliases() {
+        use crate::parsers::table::postgres_type::DieselPostgresType;
+
+        let input = quote! {
+            ledger(plural = "ledgers") {
+                code -> VarChar,
+                quantity -> Int8,
+                amount -> Decimal,
+                payload -> Binary,
+                due_in -> Nullable<Interval>,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        let types = result.columns[..5]
+            .iter()
+            .map(|c| c.diesel_type.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                "Varchar",
+                "BigInt",
+                "Numeric",
+                "Bytea",
+                "Nullable<Interval>"
+            ]
+        );
+        assert_eq!(
+            result.columns[4].diesel_type.unwrap_nullable(),
+            &DieselPostgresType::Interval
+        );
+    }
+
+    #[test]
+    fn test_parse_array_types() {
+        let input = quote! {
+            post(plural = "posts") {
+                tags -> Array<Text>,
+                scores -> Nullable<Array<Nullable<Integer>>>,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        let tags = &result.columns[0].diesel_type;
+        assert!(tags.is_array());
+        assert_eq!(tags.get_sql_type_string(), "TEXT[]");
+        assert_eq!(tags.get_sqlite_ddl_type_string(), "JSON");
+        assert_eq!(tags.get_diesel_sqlite_string(), "Json");
+
+        let scores = &result.columns[1].diesel_type;
+        assert!(scores.is_array() && scores.is_nullable());
+        assert_eq!(scores.get_sql_type_string(), "INTEGER[]");
+        assert_eq!(scores.get_diesel_sqlite_string(), "Nullable<Json>");
+    }
+
+    #[test]
+    fn test_parse_enum_types() {
+        let input = quote! {
+            order(plural = "orders") {
+                status -> Enum<crate::OrderStatus>,
+                previous_status -> Nullable<Enum<crate::OrderStatus>>,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        let status = &result.columns[0].diesel_type;
+        assert!(matches!(status, DieselPostgresType::Enum(_)));
+        assert_eq!(status.get_sql_type_string(), "TEXT");
+        assert_eq!(status.get_sqlite_ddl_type_string(), "TEXT");
+        assert_eq!(status.get_diesel_sqlite_string(), "Text");
+        assert_eq!(status.to_string(), "Text");
+
+        let previous_status = &result.columns[1].diesel_type;
+        assert!(previous_status.is_nullable());
+        assert_eq!(previous_status.get_diesel_sqlite_string(), "Nullable<Text>");
+    }
+
+    #[test]
+    fn test_parse_enum_without_path() {
+        let input = quote! {
+            order(plural = "orders") {
+                status -> Enum,
+            }
+        };
+
+        assert!(parse2::<CarburetorTable>(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_merge_json_keys() {
+        let input = quote! {
+            preference(plural = "preferences") {
+                #[merge(json_keys)]
+                settings -> Jsonb,
+                notes -> Jsonb,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(result.columns[0].merge_strategy, MergeStrategy::JsonKeys);
+        assert_eq!(result.columns[1].merge_strategy, MergeStrategy::Value);
+    }
+
+    #[test]
+    fn test_parse_merge_json_keys_wrong_type() {
+        let input = quote! {
+            preference(plural = "preferences") {
+                #[merge(json_keys)]
+                settings -> Nullable<Jsonb>,
+            }
+        };
+
+        let err = parse2::<CarburetorTable>(input).err().unwrap().to_string();
+        assert!(err.contains("#[merge(json_keys)] needs to be of type `Jsonb`"));
+    }
+
+    #[test]
+    fn test_parse_crdt_counter() {
+        let input = quote! {
+            product(plural = "products") {
+                #[crdt(counter)]
+                stock -> Integer,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(result.columns[0].merge_strategy, MergeStrategy::Counter);
+    }
+
+    #[test]
+    fn test_parse_crdt_counter_wrong_type() {
+        let input = quote! {
+            product(plural = "products") {
+                #[crdt(counter)]
+                stock -> Nullable<Integer>,
+            }
+        };
+
+        let err = parse2::<CarburetorTable>(input).err().unwrap().to_string();
+        assert!(err.contains("#[crdt(counter)] needs to be of type"));
+    }
+
+    #[test]
+    fn test_parse_merge_and_crdt_on_same_column() {
+        let input = quote! {
+            product(plural = "products") {
+                #[merge(json_keys)]
+                #[crdt(counter)]
+                stock -> Integer,
+            }
+        };
+
+        let err = parse2::<CarburetorTable>(input).err().unwrap().to_string();
+        assert!(err.contains("column can only be assigned once"));
+    }
+
+    #[test]
+    fn test_parse_change_tracking() {
+        let input = quote! {
+            product(plural = "products", change_tracking = "xid") {
+                name -> Text,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(result.change_tracking, ChangeTracking::Xid);
+    }
+
+    #[test]
+    fn test_parse_unknown_change_tracking() {
+        let input = quote! {
+            product(change_tracking = "sequence") {
+                name -> Text,
+            }
+        };
+
+        let err = parse2::<CarburetorTable>(input).err().unwrap().to_string();
+        assert!(err.contains("change_tracking can only be"));
+    }
+
+    #[test]
+    fn test_parse_nested_array_type() {
+        let input = quote! {
+            grid(plural = "grids") {
+                cells -> Array<Nullable<Array<Integer>>>,
+            }
+        };
+
+        let err = parse2::<CarburetorTable>(input).err().unwrap().to_string();
+        assert!(err.contains("Arrays of arrays are not supported"));
+    }
+
     #[test]
     fn test_parse_table_duplicate_column_with_auto_id() {
         let input = quote! {
@@ -227,6 +467,39 @@ mod tests {
         assert!(err.contains("Duplicate column found"));
     }
 
+    #[test]
+    fn test_parse_unknown_column_attribute() {
+        for input in [
+            quote! {
+                typo {
+                    #[imutable]
+                    name -> Text,
+                }
+            },
+            quote! {
+                typo {
+                    #[carburetor::immutable]
+                    name -> Text,
+                }
+            },
+        ] {
+            let err = parse2::<CarburetorTable>(input).unwrap_err().to_string();
+            assert!(err.contains("unknown column attribute"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_parse_column_doc_comment() {
+        let input = quote! {
+            documented {
+                /// Name shown to other users
+                name -> Text,
+            }
+        };
+
+        assert!(parse2::<CarburetorTable>(input).is_ok());
+    }
+
     #[test]
     fn test_parse_table_duplicate_data_columns() {
         let input = quote! {
@@ -243,6 +516,122 @@ mod tests {
         assert!(err.contains("Duplicate column found"));
     }
 
+    #[test]
+    fn test_parse_id_strategy() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id(uuid_v7)]
+                id -> Text,
+                name -> Text,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(
+            result.sync_metadata_columns.id.id_strategy,
+            IdStrategy::UuidV7
+        );
+    }
+
+    #[test]
+    fn test_parse_id_strategy_with_user_function() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id(with = crate::ids::new_event_id)]
+                id -> Text,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(
+            result.sync_metadata_columns.id.id_strategy,
+            IdStrategy::Custom(syn::parse_quote!(crate::ids::new_event_id))
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_id_strategy() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id(snowflake)]
+                id -> Text,
+            }
+        };
+
+        let result = parse2::<CarburetorTable>(input);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("expected `uuid_v4`"));
+    }
+
+    #[test]
+    fn test_parse_uuid_id_defaults_to_uuid_v7() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id]
+                id -> Uuid,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(
+            result.sync_metadata_columns.id.id_strategy,
+            IdStrategy::UuidV7
+        );
+    }
+
+    #[test]
+    fn test_parse_uuid_id_with_text_strategy() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id(device_counter)]
+                id -> Uuid,
+            }
+        };
+
+        let result = parse2::<CarburetorTable>(input);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("#[id] of type `Uuid` only accepts"));
+    }
+
+    #[test]
+    fn test_parse_id_with_unsupported_type() {
+        let input = quote! {
+            event(plural = "events") {
+                #[id]
+                id -> Integer,
+            }
+        };
+
+        let result = parse2::<CarburetorTable>(input);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("#[id] needs to be of type `Text` or `Uuid`"));
+    }
+
+    #[test]
+    fn test_default_id_strategy() {
+        let input = quote! {
+            event(plural = "events") {
+                name -> Text,
+            }
+        };
+
+        let result: CarburetorTable = parse2(input).unwrap();
+
+        assert_eq!(
+            result.sync_metadata_columns.id.id_strategy,
+            IdStrategy::Default
+        );
+    }
+
     #[cfg(not(feature = "migration"))]
     #[test]
     fn test_parse_sql_default_marker_without_variant() {
@@ -1,29 +1,50 @@
 use derive_more::Display;
+use quote::quote;
 use strum::EnumString;
 use syn::{
     AngleBracketedGenericArguments, Error, GenericArgument, Path, PathArguments, PathSegment, Type,
 };
 
-use crate::helpers::parse_as;
+use crate::helpers::{TargetType, get_target_type, parse_as};
 
-// TODO: List all types
-// https://docs.rs/diesel/latest/diesel/sql_types/index.html
+// Types of https://docs.rs/diesel/latest/diesel/sql_types/index.html and
+// https://docs.rs/diesel/latest/diesel/pg/sql_types/index.html, under the name and aliases given
+// by diesel. Ranges and records are not supported yet.
 #[derive(Debug, Clone, EnumString, PartialEq, Display)]
 pub(crate) enum DieselPostgresType {
     Text,
+    #[strum(serialize = "Varchar", serialize = "VarChar")]
+    Varchar,
+    #[strum(serialize = "SmallInt", serialize = "Int2", serialize = "SmallSerial")]
     SmallInt,
-    #[strum(serialize = "Integer", serialize = "Serial")]
+    #[strum(serialize = "Integer", serialize = "Int4", serialize = "Serial")]
     Integer,
-    #[strum(serialize = "BigInt", serialize = "BigSerial")]
+    #[strum(serialize = "BigInt", serialize = "Int8", serialize = "BigSerial")]
     BigInt,
+    #[strum(serialize = "Float", serialize = "Float4")]
     Float,
+    #[strum(serialize = "Double", serialize = "Float8")]
     Double,
+    #[strum(serialize = "Numeric", serialize = "Decimal")]
+    Numeric,
+    Money,
     Bool,
     Timestamp,
     Timestamptz,
     Date,
     Time,
+    Interval,
+    Json,
     Jsonb,
+    Uuid,
+    #[strum(serialize = "Bytea", serialize = "Binary")]
+    Bytea,
+    Inet,
+
+    // User enum implementing `carburetor::types::SyncEnum`, stored as its text form
+    #[strum(disabled)]
+    #[display("Text")]
+    Enum(Path),
 
     #[strum(disabled)]
     // Generic with single type
@@ -34,6 +55,7 @@ pub(crate) enum DieselPostgresType {
 #[derive(Debug, Clone, EnumString, PartialEq, Display)]
 pub(crate) enum DieselPostgresGeneric1Type {
     Nullable,
+    Array,
 }
 
 impl DieselPostgresType {
@@ -46,24 +68,69 @@ impl DieselPostgresType {
         }
     }
 
+    pub(crate) fn is_nullable(&self) -> bool {
+        matches!(
+            self,
+            DieselPostgresType::Generic1(DieselPostgresGeneric1Type::Nullable, _)
+        )
+    }
+
+    /// Whether this is an `Array<T>`, possibly wrapped in `Nullable`.
+    pub(crate) fn is_array(&self) -> bool {
+        matches!(
+            self.unwrap_nullable(),
+            DieselPostgresType::Generic1(DieselPostgresGeneric1Type::Array, _)
+        )
+    }
+
     pub(crate) fn get_model_type_string(&self) -> String {
         match self {
-            DieselPostgresType::Text => "String".to_string(),
+            DieselPostgresType::Text | DieselPostgresType::Varchar => "String".to_string(),
             DieselPostgresType::SmallInt => "i16".to_string(),
             DieselPostgresType::Integer => "i32".to_string(),
             DieselPostgresType::BigInt => "i64".to_string(),
             DieselPostgresType::Float => "f32".to_string(),
             DieselPostgresType::Double => "f64".to_string(),
+            // The `Numeric` mapping of diesel for SQLite goes through `f64`, so the client keeps
+            // the decimal in its text form instead
+            DieselPostgresType::Numeric => match get_target_type() {
+                TargetType::Backend => "carburetor::bigdecimal::BigDecimal".to_string(),
+                TargetType::Client => "String".to_string(),
+            },
+            DieselPostgresType::Money => "carburetor::types::Money".to_string(),
             DieselPostgresType::Bool => "bool".to_string(),
             DieselPostgresType::Timestamp => "carburetor::chrono::NaiveDateTime".to_string(),
             DieselPostgresType::Timestamptz => "carburetor::chrono::DateTimeUtc".to_string(),
             DieselPostgresType::Date => "carburetor::chrono::NaiveDate".to_string(),
             DieselPostgresType::Time => "carburetor::chrono::NaiveTime".to_string(),
-            DieselPostgresType::Jsonb => "carburetor::serde_json::Value".to_string(),
+            DieselPostgresType::Interval => "carburetor::types::Interval".to_string(),
+            DieselPostgresType::Json | DieselPostgresType::Jsonb => {
+                "carburetor::serde_json::Value".to_string()
+            }
+            // SQLite has no UUID type, so the client keeps the hyphenated text form
+            DieselPostgresType::Uuid => match get_target_type() {
+                TargetType::Backend => "carburetor::uuid::Uuid".to_string(),
+                TargetType::Client => "String".to_string(),
+            },
+            DieselPostgresType::Bytea => "Vec<u8>".to_string(),
+            DieselPostgresType::Enum(path) => quote!(#path).to_string(),
+            // Same as `Uuid`, in the text form of `IpNetwork`
+            DieselPostgresType::Inet => match get_target_type() {
+                TargetType::Backend => "carburetor::ipnetwork::IpNetwork".to_string(),
+                TargetType::Client => "String".to_string(),
+            },
             DieselPostgresType::Generic1(base_ty, generic_ty) => match base_ty {
                 DieselPostgresGeneric1Type::Nullable => {
                     format!("Option<{}>", generic_ty.get_model_type_string())
                 }
+                // SQLite has no arrays, so the client keeps them in a JSON column
+                DieselPostgresGeneric1Type::Array => match get_target_type() {
+                    TargetType::Backend => format!("Vec<{}>", generic_ty.get_model_type_string()),
+                    TargetType::Client => format!(
+                        "carburetor::types::JsonArray<{}>",
+                        generic_ty.get_model_type_string()
+                    ),
+                },
             },
         }
     }
@@ -71,34 +138,58 @@ impl DieselPostgresType {
     /// Source of truth for SQL type strings. The backend migration helper
     /// `normalize_pg_data_type` in `carburetor/src/helpers/migration.rs`
     /// mirrors these mappings — update both when adding a new type.
-    pub(crate) fn get_sql_type_string(&self) -> &'static str {
-        match self {
+    pub(crate) fn get_sql_type_string(&self) -> String {
+        let sql_type = match self {
             DieselPostgresType::Text => "TEXT",
+            DieselPostgresType::Varchar => "VARCHAR",
             DieselPostgresType::SmallInt => "SMALLINT",
             DieselPostgresType::Integer => "INTEGER",
             DieselPostgresType::BigInt => "BIGINT",
             DieselPostgresType::Float => "REAL",
             DieselPostgresType::Double => "DOUBLE PRECISION",
+            DieselPostgresType::Numeric => "NUMERIC",
+            DieselPostgresType::Money => "MONEY",
             DieselPostgresType::Bool => "BOOLEAN",
             DieselPostgresType::Timestamp => "TIMESTAMP",
             DieselPostgresType::Timestamptz => "TIMESTAMPTZ",
             DieselPostgresType::Date => "DATE",
             DieselPostgresType::Time => "TIME",
+            DieselPostgresType::Interval => "INTERVAL",
+            DieselPostgresType::Json => "JSON",
             DieselPostgresType::Jsonb => "JSONB",
-            DieselPostgresType::Generic1(base_ty, inner_ty) => match base_ty {
-                DieselPostgresGeneric1Type::Nullable => inner_ty.get_sql_type_string(),
-            },
-        }
+            DieselPostgresType::Uuid => "UUID",
+            DieselPostgresType::Bytea => "BYTEA",
+            DieselPostgresType::Inet => "INET",
+            DieselPostgresType::Enum(_) => "TEXT",
+            DieselPostgresType::Generic1(base_ty, inner_ty) => {
+                return match base_ty {
+                    DieselPostgresGeneric1Type::Nullable => inner_ty.get_sql_type_string(),
+                    DieselPostgresGeneric1Type::Array => {
+                        format!("{}[]", inner_ty.get_sql_type_string())
+                    }
+                };
+            }
+        };
+        sql_type.to_string()
     }
 
-    /// DDL type strings for SQLite. Identical to `get_sql_type_string` except
-    /// JSONB maps to JSON (SQLite has no native JSONB type).
+    /// DDL type strings for SQLite. Types without a SQLite counterpart are
+    /// stored as the type of their SQLite representation: JSONB as JSON, UUID,
+    /// VARCHAR, NUMERIC and INET as TEXT, BYTEA as BLOB, and INTERVAL (microseconds)
+    /// and MONEY (smallest currency unit) as BIGINT. Arrays are stored as JSON.
     pub(crate) fn get_sqlite_ddl_type_string(&self) -> &'static str {
         match self {
-            DieselPostgresType::Text => "TEXT",
+            DieselPostgresType::Text
+            | DieselPostgresType::Varchar
+            | DieselPostgresType::Numeric
+            | DieselPostgresType::Uuid
+            | DieselPostgresType::Inet
+            | DieselPostgresType::Enum(_) => "TEXT",
             DieselPostgresType::SmallInt => "SMALLINT",
             DieselPostgresType::Integer => "INTEGER",
-            DieselPostgresType::BigInt => "BIGINT",
+            DieselPostgresType::BigInt
+            | DieselPostgresType::Money
+            | DieselPostgresType::Interval => "BIGINT",
             DieselPostgresType::Float => "REAL",
             DieselPostgresType::Double => "DOUBLE PRECISION",
             DieselPostgresType::Bool => "BOOLEAN",
@@ -106,9 +197,11 @@ impl DieselPostgresType {
             DieselPostgresType::Timestamptz => "TIMESTAMPTZ",
             DieselPostgresType::Date => "DATE",
             DieselPostgresType::Time => "TIME",
-            DieselPostgresType::Jsonb => "JSON",
+            DieselPostgresType::Json | DieselPostgresType::Jsonb => "JSON",
+            DieselPostgresType::Bytea => "BLOB",
             DieselPostgresType::Generic1(base_ty, inner_ty) => match base_ty {
                 DieselPostgresGeneric1Type::Nullable => inner_ty.get_sqlite_ddl_type_string(),
+                DieselPostgresGeneric1Type::Array => "JSON",
             },
         }
     }
@@ -124,7 +217,15 @@ impl DieselPostgresType {
             | DieselPostgresType::Bool
             | DieselPostgresType::Timestamp
             | DieselPostgresType::Date
-            | DieselPostgresType::Time => self.to_string(),
+            | DieselPostgresType::Time
+            | DieselPostgresType::Json => self.to_string(),
+            DieselPostgresType::Varchar
+            | DieselPostgresType::Numeric
+            | DieselPostgresType::Uuid
+            | DieselPostgresType::Inet
+            | DieselPostgresType::Enum(_) => "Text".to_string(),
+            DieselPostgresType::Money | DieselPostgresType::Interval => "BigInt".to_string(),
+            DieselPostgresType::Bytea => "Binary".to_string(),
             DieselPostgresType::Timestamptz => "TimestamptzSqlite".to_string(),
             // According to https://docs.rs/diesel/latest/diesel/sql_types/struct.Jsonb.html (at
             // the time of updating), Jsonb for SQLite is only used for internal use by SQLite
@@ -134,6 +235,7 @@ impl DieselPostgresType {
                 DieselPostgresGeneric1Type::Nullable => {
                     format!("Nullable<{}>", generic_ty.get_diesel_sqlite_string())
                 }
+                DieselPostgresGeneric1Type::Array => "Json".to_string(),
             },
         }
     }
@@ -151,6 +253,20 @@ impl TryFrom<&Type> for DieselPostgresType {
             && let Some(PathSegment { ident, arguments }) = segments.first()
         {
             match (ident.to_string().as_str(), arguments) {
+                (
+                    "Enum",
+                    PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }),
+                ) if args.len() == 1 => {
+                    if let Some(GenericArgument::Type(Type::Path(enum_ty))) = args.first()
+                        && enum_ty.qself.is_none()
+                    {
+                        return Ok(DieselPostgresType::Enum(enum_ty.path.clone()));
+                    }
+                    return Err(Error::new_spanned(
+                        ty,
+                        "`Enum` needs the path to a Rust enum, e.g. `Enum<crate::OrderStatus>`",
+                    ));
+                }
                 (
                     generic1_ident,
                     PathArguments::AngleBracketed(AngleBracketedGenericArguments {
@@ -163,13 +279,18 @@ impl TryFrom<&Type> for DieselPostgresType {
                     if let Some(arg) = args.first()
                         && let GenericArgument::Type(ty) = arg
                     {
-                        return Ok(DieselPostgresType::Generic1(
-                            generic1_ident
-                                .to_string()
-                                .parse()
-                                .map_err(|_| Error::new_spanned(ty, error_message))?,
-                            Box::new(DieselPostgresType::try_from(ty)?),
-                        ));
+                        let base_ty = generic1_ident
+                            .to_string()
+                            .parse()
+                            .map_err(|_| Error::new_spanned(ty, error_message))?;
+                        let inner_ty = DieselPostgresType::try_from(ty)?;
+                        if base_ty == DieselPostgresGeneric1Type::Array && inner_ty.is_array() {
+                            return Err(Error::new_spanned(
+                                ty,
+                                "Arrays of arrays are not supported",
+                            ));
+                        }
+                        return Ok(DieselPostgresType::Generic1(base_ty, Box::new(inner_ty)));
                     }
                 }
                 _ => {}
@@ -0,0 +1,149 @@
+//! Exchange of the sync requests of a client with the backend, used by the generated `sync`
+//! function of every sync group.
+
+use std::future::Future;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::{Error, Result};
+
+/// Sends the requests of a sync group to the backend and returns its responses, e.g. over HTTP
+/// to the routes of the generated axum `router()`.
+///
+/// `sync_group` is the name of the group in `carburetor_sync_config!`. The download request is an
+/// `Option`, as the first download of a client has no offset to send.
+pub trait Transport {
+    fn upload<Req, Res>(
+        &self,
+        sync_group: &'static str,
+        request: &Req,
+    ) -> impl Future<Output = Result<Res>>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned;
+
+    fn download<Req, Res>(
+        &self,
+        sync_group: &'static str,
+        request: &Option<Req>,
+    ) -> impl Future<Output = Result<Res>>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned;
+}
+
+/// Rows exchanged by a `sync`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// Rows uploaded to the backend
+    pub sent: usize,
+    /// Rows downloaded from the backend
+    pub received: usize,
+    /// Uploaded rows that the backend did not apply. They are kept dirty on the client, see
+    /// `UploadRetryPolicy` for when they are uploaded again.
+    pub rejected: usize,
+}
+
+/// `Transport` handing the requests, serialized to JSON, to functions of the same process. Tests
+/// use it to call the backend functions directly, without a server in between.
+///
+/// `upload` and `download` are called with the name of the sync group and the JSON request, and
+/// return the JSON response.
+pub struct InProcessTransport<U, D> {
+    upload: U,
+    download: D,
+}
+
+impl<U, D> InProcessTransport<U, D> {
+    pub fn new(upload: U, download: D) -> Self {
+        Self { upload, download }
+    }
+}
+
+impl<U, UFut, D, DFut> Transport for InProcessTransport<U, D>
+where
+    U: Fn(&'static str, String) -> UFut,
+    UFut: Future<Output = Result<String>>,
+    D: Fn(&'static str, String) -> DFut,
+    DFut: Future<Output = Result<String>>,
+{
+    async fn upload<Req, Res>(&self, sync_group: &'static str, request: &Req) -> Result<Res>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let response = (self.upload)(sync_group, to_json(sync_group, request)?).await?;
+        from_json(sync_group, &response)
+    }
+
+    async fn download<Req, Res>(
+        &self,
+        sync_group: &'static str,
+        request: &Option<Req>,
+    ) -> Result<Res>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let response = (self.download)(sync_group, to_json(sync_group, request)?).await?;
+        from_json(sync_group, &response)
+    }
+}
+
+fn to_json(sync_group: &str, request: &impl Serialize) -> Result<String> {
+    serde_json::to_string(request).map_err(|e| Error::Serialization {
+        message: format!("Failed to serialize request of sync group '{}'", sync_group),
+        source: e.into(),
+    })
+}
+
+fn from_json<Res: DeserializeOwned>(sync_group: &str, response: &str) -> Result<Res> {
+    serde_json::from_str(response).map_err(|e| Error::Serialization {
+        message: format!(
+            "Failed to deserialize response of sync group '{}'",
+            sync_group
+        ),
+        source: e.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_in_process_transport_round_trips_json() {
+        let transport = InProcessTransport::new(
+            |sync_group, request| async move {
+                assert_eq!(sync_group, "all_clients");
+                assert_eq!(request, r#"{"id":"1"}"#);
+                Ok(r#"["1"]"#.to_string())
+            },
+            |_, request| async move {
+                assert_eq!(request, "null");
+                Ok("[]".to_string())
+            },
+        );
+
+        let response: Vec<String> =
+            block_on(transport.upload("all_clients", &serde_json::json!({"id": "1"}))).unwrap();
+        assert_eq!(response, vec!["1".to_string()]);
+
+        let response: Vec<String> =
+            block_on(transport.download::<String, _>("all_clients", &None)).unwrap();
+        assert!(response.is_empty());
+    }
+
+    #[test]
+    fn test_in_process_transport_invalid_response() {
+        let transport = InProcessTransport::new(
+            |_, _| async { Ok("not json".to_string()) },
+            |_, _| async { Ok("not json".to_string()) },
+        );
+
+        let error = block_on(transport.upload::<_, Vec<String>>("all_clients", &())).unwrap_err();
+        assert!(matches!(error, Error::Serialization { .. }));
+    }
+}
@@ -1,30 +1,49 @@
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 use crate::error::Error;
 
-static CONFIG: OnceLock<CarburetorGlobalConfig> = OnceLock::new();
+static GLOBAL_CARBURETOR: OnceLock<Carburetor> = OnceLock::new();
 
-pub fn initialize_carburetor_global_config(config: CarburetorGlobalConfig) {
-    if CONFIG.set(config).is_err() {
+pub fn initialize_carburetor_global_config(config: CarburetorConfig) {
+    if GLOBAL_CARBURETOR.set(Carburetor::new(config)).is_err() {
         panic!("{}", Error::ConfigInit)
     }
 }
 
+/// Returns the handle used by the generated global functions, falling back to the default config
+/// when `initialize_carburetor_global_config` was never called.
 #[cfg(any(for_backend, for_client))]
-pub(crate) fn get_carburetor_config() -> &'static CarburetorGlobalConfig {
-    CONFIG.get_or_init(|| CarburetorGlobalConfig::default())
+pub(crate) fn get_global_carburetor() -> &'static Carburetor {
+    GLOBAL_CARBURETOR.get_or_init(|| Carburetor::new(CarburetorConfig::default()))
 }
 
 #[derive(Debug, Clone)]
-pub struct CarburetorGlobalConfig {
+pub struct CarburetorConfig {
     #[cfg(for_backend)]
     pub database_url: String,
 
     #[cfg(for_client)]
     pub database_path: String,
+
+    /// Decides when rows that failed to upload are sent again by `retrieve_upload_request`.
+    #[cfg(for_client)]
+    pub upload_retry_policy: crate::helpers::upload_failure::UploadRetryPolicy,
+
+    /// When set, generated functions check out their connection from this pool instead of
+    /// establishing a new one from `database_url`/`database_path` on every call.
+    #[cfg(feature = "r2d2")]
+    pub connection_pool: Option<crate::helpers::ConnectionPool>,
+
+    /// When set, the async backend functions check out their connection from this pool instead
+    /// of establishing a new one from `database_url` on every call.
+    #[cfg(all(for_backend, feature = "bb8"))]
+    pub async_connection_pool: Option<crate::helpers::AsyncConnectionPool>,
 }
 
-impl Default for CarburetorGlobalConfig {
+/// Kept for compatibility with code written against the single global configuration.
+pub type CarburetorGlobalConfig = CarburetorConfig;
+
+impl Default for CarburetorConfig {
     fn default() -> Self {
         Self {
             #[cfg(for_backend)]
@@ -32,6 +51,87 @@ impl Default for CarburetorGlobalConfig {
 
             #[cfg(for_client)]
             database_path: "./default.db".to_string(),
+
+            #[cfg(for_client)]
+            upload_retry_policy: Default::default(),
+
+            #[cfg(feature = "r2d2")]
+            connection_pool: None,
+
+            #[cfg(all(for_backend, feature = "bb8"))]
+            async_connection_pool: None,
         }
     }
 }
+
+/// Handle to one database.
+///
+/// Each handle owns its own configuration, so a process can talk to several databases (e.g. one
+/// per tenant or per signed-in account) by creating one handle for each. Cloning is cheap and
+/// shares the configuration, including the connection pool when one is set.
+#[derive(Debug, Clone)]
+pub struct Carburetor {
+    config: Arc<CarburetorConfig>,
+}
+
+impl Carburetor {
+    pub fn new(config: CarburetorConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn config(&self) -> &CarburetorConfig {
+        &self.config
+    }
+
+    /// Establishes a new connection to the database of this handle.
+    #[cfg(any(for_backend, for_client))]
+    pub fn get_connection(&self) -> crate::error::Result<crate::helpers::DatabaseConnection> {
+        crate::helpers::establish_connection(&self.config)
+    }
+
+    /// Returns an async connection to the database of this handle. Used by the `_async` variants
+    /// of the generated backend functions.
+    ///
+    /// The connection is checked out of `CarburetorConfig::async_connection_pool` when one is set,
+    /// otherwise a new connection is established.
+    #[cfg(all(for_backend, feature = "async"))]
+    pub async fn get_async_connection(
+        &self,
+    ) -> crate::error::Result<crate::helpers::AsyncDatabaseConnection> {
+        #[cfg(feature = "bb8")]
+        if let Some(pool) = &self.config.async_connection_pool {
+            let conn = pool.get_owned().await.map_err(|e| Error::Connection {
+                message: "Failed to check out connection from pool".to_string(),
+                source: e.into(),
+            })?;
+            return Ok(crate::helpers::AsyncDatabaseConnection::Pooled(conn));
+        }
+
+        Ok(crate::helpers::AsyncDatabaseConnection::Established(
+            crate::helpers::establish_async_connection(&self.config).await?,
+        ))
+    }
+
+    /// Runs `f` with a connection to the database of this handle.
+    ///
+    /// The connection is checked out of `CarburetorConfig::connection_pool` when one is set,
+    /// otherwise a new connection is established.
+    #[cfg(any(for_backend, for_client))]
+    pub fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut crate::helpers::DatabaseConnection) -> crate::error::Result<T>,
+    ) -> crate::error::Result<T> {
+        #[cfg(feature = "r2d2")]
+        if let Some(pool) = &self.config.connection_pool {
+            let mut conn = pool.get().map_err(|e| Error::Connection {
+                message: "Failed to check out connection from pool".to_string(),
+                source: e.into(),
+            })?;
+            return f(&mut conn);
+        }
+
+        f(&mut self.get_connection()?)
+    }
+}
@@ -12,6 +12,68 @@ pub enum Error {
     #[error("Migration error: {0}")]
     Migration(String),
 
+    /// The existing database does not match the schema declared with `carburetor_sync_config!`.
+    #[error("Schema mismatch: {0}")]
+    SchemaMismatch(String),
+
+    /// The database could not be reached, or the connection was lost.
+    #[error("Connection error: {message}\n{source}")]
+    Connection {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// The database is locked by another connection, or the transaction conflicted with a
+    /// concurrent one.
+    #[error("Database busy: {message}\n{source}")]
+    Busy {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// A query failed for a reason other than the ones with a dedicated variant.
+    #[error("Query error: {message}\n{source}")]
+    Query {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// A unique, foreign key, not null or check constraint rejected the change.
+    #[error("Constraint violation: {message}\n{source}")]
+    ConstraintViolation {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// A value could not be converted to or from its stored representation.
+    #[error("Serialization error: {message}\n{source}")]
+    Serialization {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// The value of an `Enum<T>` column is not a variant of `T`.
+    #[error("{0}")]
+    UnknownVariant(#[from] crate::types::UnknownVariant),
+
+    /// A request could not be exchanged with the backend by the client `Transport`.
+    #[error("Transport error: {message}\n{source}")]
+    Transport {
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    /// A download reported more pages without moving its position, so that the next request
+    /// would download the same page again.
+    #[error("Download stalled: {0}")]
+    DownloadStalled(String),
+
     #[error("Unknown error: {message}\n{source}")]
     Unhandled {
         message: String,
@@ -19,3 +81,290 @@ pub enum Error {
         source: anyhow::Error,
     },
 }
+
+impl Error {
+    /// Classifies a diesel error under the variant matching its cause.
+    pub fn from_diesel(message: impl Into<String>, e: diesel::result::Error) -> Self {
+        use diesel::result::{DatabaseErrorKind, Error as DieselError};
+
+        let message = message.into();
+        match &e {
+            DieselError::DatabaseError(kind, info) => match kind {
+                DatabaseErrorKind::UniqueViolation
+                | DatabaseErrorKind::ForeignKeyViolation
+                | DatabaseErrorKind::NotNullViolation
+                | DatabaseErrorKind::CheckViolation => Self::ConstraintViolation {
+                    message,
+                    source: e.into(),
+                },
+                DatabaseErrorKind::SerializationFailure => Self::Busy {
+                    message,
+                    source: e.into(),
+                },
+                DatabaseErrorKind::ClosedConnection => Self::Connection {
+                    message,
+                    source: e.into(),
+                },
+                _ if is_busy_message(info.message()) => Self::Busy {
+                    message,
+                    source: e.into(),
+                },
+                _ => Self::Query {
+                    message,
+                    source: e.into(),
+                },
+            },
+            DieselError::DeserializationError(inner) => {
+                match find_unknown_variant(inner.as_ref()) {
+                    Some(unknown_variant) => Self::UnknownVariant(unknown_variant.clone()),
+                    None => Self::Serialization {
+                        message,
+                        source: e.into(),
+                    },
+                }
+            }
+            DieselError::SerializationError(_) => Self::Serialization {
+                message,
+                source: e.into(),
+            },
+            DieselError::BrokenTransactionManager => Self::Connection {
+                message,
+                source: e.into(),
+            },
+            _ => Self::Query {
+                message,
+                source: e.into(),
+            },
+        }
+    }
+
+    /// Returns whether the same operation may succeed when attempted again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Connection { .. } | Self::Busy { .. } | Self::Transport { .. }
+        )
+    }
+
+    /// Returns whether the error comes from the data rather than from the database, so that
+    /// retrying without changing the data cannot succeed.
+    pub fn is_data_error(&self) -> bool {
+        matches!(
+            self,
+            Self::ConstraintViolation { .. } | Self::Serialization { .. } | Self::UnknownVariant(_)
+        )
+    }
+
+    /// Returns whether the database has to be migrated before it can be used.
+    pub fn is_schema_error(&self) -> bool {
+        matches!(self, Self::Migration(_) | Self::SchemaMismatch(_))
+    }
+}
+
+#[cfg(feature = "axum")]
+impl Error {
+    /// HTTP status of the response returned for this error by the generated routers.
+    pub fn status_code(&self) -> axum::http::StatusCode {
+        use axum::http::StatusCode;
+
+        match self {
+            Self::Connection { .. } | Self::Busy { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::ConstraintViolation { .. } => StatusCode::CONFLICT,
+            Self::Serialization { .. } | Self::UnknownVariant(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            Self::Transport { .. } => StatusCode::BAD_GATEWAY,
+            Self::ConfigInit
+            | Self::Migration(_)
+            | Self::SchemaMismatch(_)
+            | Self::Query { .. }
+            | Self::DownloadStalled(_)
+            | Self::Unhandled { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status_code();
+        // The source may tell about the database, so only the data errors are described
+        let body = match &self {
+            Self::ConstraintViolation { message, .. } | Self::Serialization { message, .. } => {
+                message.clone()
+            }
+            Self::UnknownVariant(e) => e.to_string(),
+            _ => status.canonical_reason().unwrap_or_default().to_string(),
+        };
+
+        (status, body).into_response()
+    }
+}
+
+/// Loading a row wraps the error of the failing field, so the sources are searched as well.
+fn find_unknown_variant<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a crate::types::UnknownVariant> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(unknown_variant) = error.downcast_ref::<crate::types::UnknownVariant>() {
+            return Some(unknown_variant);
+        }
+        current = error.source();
+    }
+    None
+}
+
+/// SQLite reports `SQLITE_BUSY` and `SQLITE_LOCKED`, and Postgres reports `lock_not_available`,
+/// without a dedicated `DatabaseErrorKind`.
+fn is_busy_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("database is locked")
+        || message.contains("database table is locked")
+        || message.contains("could not obtain lock")
+        || message.contains("lock timeout")
+}
+
+impl From<diesel::result::Error> for Error {
+    fn from(e: diesel::result::Error) -> Self {
+        Self::from_diesel("Database transaction failed", e)
+    }
+}
+
+impl From<diesel::ConnectionError> for Error {
+    fn from(e: diesel::ConnectionError) -> Self {
+        Self::Connection {
+            message: "Connection to database failed".to_string(),
+            source: e.into(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization {
+            message: "JSON conversion failed".to_string(),
+            source: e.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use diesel::result::{DatabaseErrorKind, Error as DieselError};
+
+    fn database_error(kind: DatabaseErrorKind, message: &str) -> DieselError {
+        DieselError::DatabaseError(kind, Box::new(message.to_string()))
+    }
+
+    #[test]
+    fn test_constraint_violation() {
+        let error = Error::from_diesel(
+            "insert failed",
+            database_error(
+                DatabaseErrorKind::UniqueViolation,
+                "UNIQUE constraint failed",
+            ),
+        );
+
+        assert!(matches!(error, Error::ConstraintViolation { .. }));
+        assert!(error.is_data_error());
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_unknown_variant() {
+        let unknown_variant = crate::types::UnknownVariant {
+            enum_name: "OrderStatus",
+            value: "archived".to_string(),
+        };
+        let error = Error::from_diesel(
+            "load failed",
+            DieselError::DeserializationError(Box::new(unknown_variant.clone())),
+        );
+
+        assert!(matches!(&error, Error::UnknownVariant(x) if *x == unknown_variant));
+        assert!(error.is_data_error());
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_locked_database_is_busy() {
+        let error = Error::from_diesel(
+            "update failed",
+            database_error(DatabaseErrorKind::Unknown, "database is locked"),
+        );
+
+        assert!(matches!(error, Error::Busy { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_serialization_failure_is_busy() {
+        let error = Error::from_diesel(
+            "upload failed",
+            database_error(
+                DatabaseErrorKind::SerializationFailure,
+                "could not serialize access",
+            ),
+        );
+
+        assert!(matches!(error, Error::Busy { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_closed_connection() {
+        let error = Error::from_diesel(
+            "query failed",
+            database_error(DatabaseErrorKind::ClosedConnection, "server closed"),
+        );
+
+        assert!(matches!(error, Error::Connection { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_other_query_error() {
+        let error = Error::from(DieselError::NotFound);
+
+        assert!(matches!(error, Error::Query { .. }));
+        assert!(!error.is_retryable());
+        assert!(!error.is_data_error());
+    }
+
+    #[test]
+    fn test_json_error() {
+        let error = Error::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err());
+
+        assert!(matches!(error, Error::Serialization { .. }));
+        assert!(error.is_data_error());
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_status_code() {
+        use axum::{http::StatusCode, response::IntoResponse};
+
+        let busy = Error::from_diesel(
+            "update failed",
+            database_error(DatabaseErrorKind::Unknown, "database is locked"),
+        );
+        assert_eq!(busy.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let constraint_violation = Error::from_diesel(
+            "insert failed",
+            database_error(DatabaseErrorKind::UniqueViolation, "duplicate key"),
+        );
+        assert_eq!(constraint_violation.status_code(), StatusCode::CONFLICT);
+
+        let query = Error::from(DieselError::NotFound);
+        assert_eq!(query.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            query.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
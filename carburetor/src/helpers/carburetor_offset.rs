@@ -1,68 +1,418 @@
-use std::collections::HashMap;
-
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    models::DownloadCursor,
+};
 
 diesel::table! {
-    carburetor_offsets (table_name) {
+    carburetor_offsets (sync_group, table_name, restriction) {
+        sync_group -> Text,
         table_name -> Text,
+        restriction -> Text,
         cutoff_at -> TimestamptzSqlite,
+        xmin -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    carburetor_download_cursors (sync_group, table_name, restriction) {
+        sync_group -> Text,
+        table_name -> Text,
+        restriction -> Text,
+        last_synced_at -> TimestamptzSqlite,
+        id -> Text,
+        xmin -> Nullable<BigInt>,
     }
 }
 
+// The composite primary key is not supported by `create_table`
+pub const CREATE_OFFSETS_SQL: &str = "CREATE TABLE carburetor_offsets (\
+     sync_group TEXT NOT NULL, \
+     table_name TEXT NOT NULL, \
+     restriction TEXT NOT NULL, \
+     cutoff_at TIMESTAMPTZ NOT NULL, \
+     xmin BIGINT, \
+     PRIMARY KEY (sync_group, table_name, restriction))";
+
+pub const CREATE_DOWNLOAD_CURSORS_SQL: &str = "CREATE TABLE carburetor_download_cursors (\
+     sync_group TEXT NOT NULL, \
+     table_name TEXT NOT NULL, \
+     restriction TEXT NOT NULL, \
+     last_synced_at TIMESTAMPTZ NOT NULL, \
+     id TEXT NOT NULL, \
+     xmin BIGINT, \
+     PRIMARY KEY (sync_group, table_name, restriction))";
+
+/// Identifies the download position of a table within a sync group. A table synced through
+/// several groups has one position per group, as each group may download a different part of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OffsetKey<'a> {
+    pub sync_group: &'a str,
+    pub table_name: &'a str,
+    /// `DownloadTableResponse::restriction` of the download, or empty when the table is not
+    /// restricted or the offset was kept from before restrictions were tracked. The backend
+    /// resets the download when the value bound to the restriction has changed since.
+    pub restriction: &'a str,
+}
+
 #[derive(Debug, Clone, Queryable, Selectable, Insertable)]
 #[diesel(table_name = carburetor_offsets)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct FullCarburetorOffset {
+    pub sync_group: String,
     pub table_name: String,
+    pub restriction: String,
     pub cutoff_at: DateTime<Utc>,
+    pub xmin: Option<i64>,
 }
 
-#[derive(Debug, Clone, AsChangeset)]
-#[diesel(table_name = carburetor_offsets)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct ChangesetCarburetorOffset {
-    pub table_name: String,
-    pub cutoff_at: Option<DateTime<Utc>>,
+pub fn upsert_offset(
+    conn: &mut diesel::SqliteConnection,
+    key: OffsetKey,
+    cutoff_at: DateTime<Utc>,
+) -> crate::error::Result<()> {
+    upsert_offset_with_xmin(conn, key, cutoff_at, None)
 }
 
-pub fn upsert_offset(
+/// Same as `upsert_offset`, with the position of a table with `change_tracking = "xid"`.
+pub fn upsert_offset_with_xmin(
     conn: &mut diesel::SqliteConnection,
-    table_name: &str,
+    key: OffsetKey,
     cutoff_at: DateTime<Utc>,
+    xmin: Option<i64>,
 ) -> crate::error::Result<()> {
     use crate::error::Error;
 
     let offset = FullCarburetorOffset {
-        table_name: table_name.to_string(),
+        sync_group: key.sync_group.to_string(),
+        table_name: key.table_name.to_string(),
+        restriction: key.restriction.to_string(),
         cutoff_at,
+        xmin,
     };
 
+    // A table has a single position per group, the ones of a previous restriction are stale
+    diesel::delete(
+        carburetor_offsets::table
+            .filter(carburetor_offsets::sync_group.eq(key.sync_group))
+            .filter(carburetor_offsets::table_name.eq(key.table_name))
+            .filter(carburetor_offsets::restriction.ne(key.restriction)),
+    )
+    .execute(conn)
+    .map_err(|e| {
+        Error::from_diesel(
+            format!(
+                "Failed to delete stale offset for table '{}' in sync group '{}'",
+                key.table_name, key.sync_group
+            ),
+            e,
+        )
+    })?;
     diesel::replace_into(carburetor_offsets::table)
         .values(&offset)
         .execute(conn)
-        .map_err(|e| Error::Unhandled {
-            message: format!("Failed to update offset for table '{}'", table_name),
-            source: e.into(),
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to update offset for table '{}' in sync group '{}'",
+                    key.table_name, key.sync_group
+                ),
+                e,
+            )
         })?;
 
     Ok(())
 }
 
-pub fn retrieve_offsets(
+/// Deletes the offsets of the table in every sync group, so that each of them downloads the table
+/// again from the start.
+pub fn delete_offsets(conn: &mut diesel::SqliteConnection, table_name: &str) -> Result<()> {
+    diesel::delete(carburetor_offsets::table.filter(carburetor_offsets::table_name.eq(table_name)))
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!("Failed to delete offsets for table '{}'", table_name),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Deletes the offset and download cursor of the table in the sync group, so that the group
+/// downloads the table again from the start.
+pub fn reset_offset(
     conn: &mut diesel::SqliteConnection,
-) -> Result<HashMap<String, DateTime<Utc>>> {
-    let offsets = carburetor_offsets::table
+    sync_group: &str,
+    table_name: &str,
+) -> Result<()> {
+    diesel::delete(
+        carburetor_offsets::table
+            .filter(carburetor_offsets::sync_group.eq(sync_group))
+            .filter(carburetor_offsets::table_name.eq(table_name)),
+    )
+    .execute(conn)
+    .and_then(|_| {
+        diesel::delete(
+            carburetor_download_cursors::table
+                .filter(carburetor_download_cursors::sync_group.eq(sync_group))
+                .filter(carburetor_download_cursors::table_name.eq(table_name)),
+        )
+        .execute(conn)
+    })
+    .map_err(|e| {
+        Error::from_diesel(
+            format!(
+                "Failed to reset offset for table '{}' in sync group '{}'",
+                table_name, sync_group
+            ),
+            e,
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Returns the offset of the table in the sync group, whatever restriction it was downloaded
+/// with.
+pub fn retrieve_offset(
+    conn: &mut diesel::SqliteConnection,
+    sync_group: &str,
+    table_name: &str,
+) -> Result<Option<FullCarburetorOffset>> {
+    carburetor_offsets::table
+        .filter(carburetor_offsets::sync_group.eq(sync_group))
+        .filter(carburetor_offsets::table_name.eq(table_name))
+        .first::<FullCarburetorOffset>(conn)
+        .optional()
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to retrieve offset for table '{}' in sync group '{}'",
+                    table_name, sync_group
+                ),
+                e,
+            )
+        })
+}
+
+/// Returns the offsets of every table in every sync group.
+pub fn retrieve_offsets(conn: &mut diesel::SqliteConnection) -> Result<Vec<FullCarburetorOffset>> {
+    carburetor_offsets::table
         .load::<FullCarburetorOffset>(conn)
-        .map_err(|e| Error::Unhandled {
-            message: "Failed to retrieve offsets".to_string(),
-            source: e.into(),
-        })?
-        .into_iter()
-        .map(|o| (o.table_name, o.cutoff_at))
-        .collect();
-
-    Ok(offsets)
+        .map_err(|e| Error::from_diesel("Failed to retrieve offsets", e))
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Insertable)]
+#[diesel(table_name = carburetor_download_cursors)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FullCarburetorDownloadCursor {
+    pub sync_group: String,
+    pub table_name: String,
+    pub restriction: String,
+    pub last_synced_at: DateTime<Utc>,
+    pub id: String,
+    pub xmin: Option<i64>,
+}
+
+impl From<FullCarburetorDownloadCursor> for DownloadCursor {
+    fn from(value: FullCarburetorDownloadCursor) -> Self {
+        Self {
+            last_synced_at: value.last_synced_at,
+            id: value.id,
+            xmin: value.xmin,
+        }
+    }
+}
+
+/// Saves the position of a table download that still has pages left. The offset of the table is
+/// left untouched until the download is complete.
+pub fn upsert_cursor(
+    conn: &mut diesel::SqliteConnection,
+    key: OffsetKey,
+    cursor: DownloadCursor,
+) -> Result<()> {
+    let cursor = FullCarburetorDownloadCursor {
+        sync_group: key.sync_group.to_string(),
+        table_name: key.table_name.to_string(),
+        restriction: key.restriction.to_string(),
+        last_synced_at: cursor.last_synced_at,
+        id: cursor.id,
+        xmin: cursor.xmin,
+    };
+
+    diesel::delete(
+        carburetor_download_cursors::table
+            .filter(carburetor_download_cursors::sync_group.eq(key.sync_group))
+            .filter(carburetor_download_cursors::table_name.eq(key.table_name))
+            .filter(carburetor_download_cursors::restriction.ne(key.restriction)),
+    )
+    .execute(conn)
+    .map_err(|e| {
+        Error::from_diesel(
+            format!(
+                "Failed to delete stale download cursor for table '{}' in sync group '{}'",
+                key.table_name, key.sync_group
+            ),
+            e,
+        )
+    })?;
+    diesel::replace_into(carburetor_download_cursors::table)
+        .values(&cursor)
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to update download cursor for table '{}' in sync group '{}'",
+                    key.table_name, key.sync_group
+                ),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+pub fn delete_cursor(conn: &mut diesel::SqliteConnection, key: OffsetKey) -> Result<()> {
+    diesel::delete(carburetor_download_cursors::table.find((
+        key.sync_group,
+        key.table_name,
+        key.restriction,
+    )))
+    .execute(conn)
+    .map_err(|e| {
+        Error::from_diesel(
+            format!(
+                "Failed to delete download cursor for table '{}' in sync group '{}'",
+                key.table_name, key.sync_group
+            ),
+            e,
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Deletes the download cursors of the table in every sync group.
+pub fn delete_cursors(conn: &mut diesel::SqliteConnection, table_name: &str) -> Result<()> {
+    diesel::delete(
+        carburetor_download_cursors::table
+            .filter(carburetor_download_cursors::table_name.eq(table_name)),
+    )
+    .execute(conn)
+    .map_err(|e| {
+        Error::from_diesel(
+            format!(
+                "Failed to delete download cursors for table '{}'",
+                table_name
+            ),
+            e,
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Returns the download cursor of the table in the sync group, whatever restriction it was
+/// downloaded with.
+pub fn retrieve_cursor(
+    conn: &mut diesel::SqliteConnection,
+    sync_group: &str,
+    table_name: &str,
+) -> Result<Option<FullCarburetorDownloadCursor>> {
+    carburetor_download_cursors::table
+        .filter(carburetor_download_cursors::sync_group.eq(sync_group))
+        .filter(carburetor_download_cursors::table_name.eq(table_name))
+        .first::<FullCarburetorDownloadCursor>(conn)
+        .optional()
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to retrieve download cursor for table '{}' in sync group '{}'",
+                    table_name, sync_group
+                ),
+                e,
+            )
+        })
+}
+
+/// Creates the offset and download cursor tables. Tables created before offsets were kept per
+/// sync group are rebuilt: the offset of a table synced by a single group in `keys` is kept for
+/// that group, and the other tables are downloaded again from the start, as their offset may
+/// have been moved by any of their groups.
+#[cfg(feature = "migration")]
+pub fn migrate_offset_tables(
+    conn: &mut diesel::SqliteConnection,
+    keys: &[OffsetKey],
+) -> Result<()> {
+    use diesel::connection::SimpleConnection;
+
+    use crate::helpers::migration::{check_table_exists, client::introspect_columns};
+
+    let has_sync_group = |conn: &mut diesel::SqliteConnection, table_name: &str| {
+        Ok::<_, Error>(
+            introspect_columns(conn, table_name)?
+                .iter()
+                .any(|x| x.name == "sync_group"),
+        )
+    };
+
+    if !check_table_exists(conn, "carburetor_offsets")? {
+        conn.batch_execute(CREATE_OFFSETS_SQL)
+            .map_err(|e| Error::from_diesel("Failed to create table 'carburetor_offsets'", e))?;
+    } else if !has_sync_group(conn, "carburetor_offsets")? {
+        conn.batch_execute(&format!(
+            "DROP TABLE IF EXISTS carburetor_offsets_legacy; \
+             ALTER TABLE carburetor_offsets RENAME TO carburetor_offsets_legacy; \
+             {CREATE_OFFSETS_SQL}"
+        ))
+        .map_err(|e| Error::from_diesel("Failed to rebuild table 'carburetor_offsets'", e))?;
+        for key in keys {
+            if keys
+                .iter()
+                .filter(|x| x.table_name == key.table_name)
+                .count()
+                > 1
+            {
+                continue;
+            }
+            diesel::sql_query(
+                "INSERT INTO carburetor_offsets (sync_group, table_name, restriction, cutoff_at) \
+                 SELECT ?, table_name, ?, cutoff_at FROM carburetor_offsets_legacy \
+                 WHERE table_name = ?",
+            )
+            .bind::<diesel::sql_types::Text, _>(key.sync_group)
+            .bind::<diesel::sql_types::Text, _>(key.restriction)
+            .bind::<diesel::sql_types::Text, _>(key.table_name)
+            .execute(conn)
+            .map_err(|e| {
+                Error::from_diesel(
+                    format!("Failed to keep offset of table '{}'", key.table_name),
+                    e,
+                )
+            })?;
+        }
+        conn.batch_execute("DROP TABLE carburetor_offsets_legacy")
+            .map_err(|e| Error::from_diesel("Failed to rebuild table 'carburetor_offsets'", e))?;
+    }
+
+    // A download cut short is resumed from the offset instead
+    if check_table_exists(conn, "carburetor_download_cursors")?
+        && !has_sync_group(conn, "carburetor_download_cursors")?
+    {
+        conn.batch_execute("DROP TABLE carburetor_download_cursors")
+            .map_err(|e| {
+                Error::from_diesel("Failed to rebuild table 'carburetor_download_cursors'", e)
+            })?;
+    }
+    if !check_table_exists(conn, "carburetor_download_cursors")? {
+        conn.batch_execute(CREATE_DOWNLOAD_CURSORS_SQL)
+            .map_err(|e| {
+                Error::from_diesel("Failed to create table 'carburetor_download_cursors'", e)
+            })?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,77 @@
+//! Backend side of tables with `change_tracking = "xid"`. Their rows hold the ID of the
+//! transaction that last wrote them in a backend-only `carburetor_xid` column, so that a download
+//! can tell the rows committed after its snapshot, whatever their `last_synced_at`.
+
+use crate::error::{Error, Result};
+
+const SNAPSHOT_XMIN_SQL: &str =
+    "SELECT pg_snapshot_xmin(pg_current_snapshot())::text::BIGINT AS xmin";
+
+#[cfg(feature = "migration")]
+const CREATE_XID_FUNCTION_SQL: &str = "CREATE OR REPLACE FUNCTION carburetor_set_xid() \
+     RETURNS trigger AS $$ \
+     BEGIN \
+     NEW.carburetor_xid := pg_current_xact_id()::text::BIGINT; \
+     RETURN NEW; \
+     END; \
+     $$ LANGUAGE plpgsql";
+
+#[derive(diesel::QueryableByName)]
+struct SnapshotXmin {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    xmin: i64,
+}
+
+/// Returns the oldest transaction still running when the snapshot of the current transaction
+/// was taken. Every row written by a transaction from this one onward may not be visible yet, so
+/// it is downloaded again next time.
+pub fn get_snapshot_xmin(conn: &mut diesel::PgConnection) -> Result<i64> {
+    use diesel::RunQueryDsl;
+
+    let result: SnapshotXmin = diesel::sql_query(SNAPSHOT_XMIN_SQL)
+        .get_result(conn)
+        .map_err(|e| Error::from_diesel("Failed to retrieve snapshot xmin", e))?;
+
+    Ok(result.xmin)
+}
+
+#[cfg(feature = "async")]
+pub async fn get_snapshot_xmin_async(conn: &mut diesel_async::AsyncPgConnection) -> Result<i64> {
+    use diesel_async::RunQueryDsl;
+
+    let result: SnapshotXmin = diesel::sql_query(SNAPSHOT_XMIN_SQL)
+        .get_result(conn)
+        .await
+        .map_err(|e| Error::from_diesel("Failed to retrieve snapshot xmin", e))?;
+
+    Ok(result.xmin)
+}
+
+/// Sets `carburetor_xid` of every row written to `table_name` with a trigger, so that rows
+/// written outside of carburetor are tracked as well. The column is indexed along with
+/// `id_column`, so that a download only reads the rows from its `xmin` onward.
+#[cfg(feature = "migration")]
+pub fn install_xid_trigger(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    id_column: &str,
+) -> Result<()> {
+    use diesel::connection::SimpleConnection;
+
+    let sql = format!(
+        "{CREATE_XID_FUNCTION_SQL}; \
+         DROP TRIGGER IF EXISTS carburetor_set_xid ON {table_name}; \
+         CREATE TRIGGER carburetor_set_xid BEFORE INSERT OR UPDATE ON {table_name} \
+         FOR EACH ROW EXECUTE FUNCTION carburetor_set_xid(); \
+         CREATE INDEX IF NOT EXISTS {table_name}_carburetor_xid_idx \
+         ON {table_name} (carburetor_xid, {id_column})"
+    );
+    conn.batch_execute(&sql).map_err(|e| {
+        Error::from_diesel(
+            format!("Failed to install xid trigger on table '{}'", table_name),
+            e,
+        )
+    })?;
+
+    Ok(())
+}
@@ -1,13 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::{Value, from_value, to_value};
 
+use crate::error::{Error, Result};
+
 #[derive(Debug, Clone)]
 pub enum DirtyFlag {
     Insert,
     Update,
+    /// Rejected by the backend for insufficient permission. The row is kept locally but no longer
+    /// uploaded.
+    Rejected,
 }
 
 impl ToString for DirtyFlag {
@@ -15,6 +20,7 @@ impl ToString for DirtyFlag {
         match self {
             Self::Insert => "insert".to_string(),
             Self::Update => "update".to_string(),
+            Self::Rejected => "rejected".to_string(),
         }
     }
 }
@@ -25,6 +31,33 @@ pub struct Metadata {
     pub dirty_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub column_last_synced_at: Option<DateTime<Utc>>,
+    /// Metadata of each top-level key, only kept for `#[merge(json_keys)]` columns
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, Metadata>,
+    /// Local changes of the value, only kept for `#[crdt(counter)]` columns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub increments: Vec<CounterIncrement>,
+}
+
+/// Change of a `#[crdt(counter)]` column made on this device.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CounterIncrement {
+    pub at: DateTime<Utc>,
+    pub delta: i64,
+    /// Time of the backend once the upload of the increment is accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+impl Metadata {
+    /// Returns whether the local value has changes that the last download did not include, either
+    /// because they are not uploaded yet or because they were uploaded after it.
+    pub fn is_dirty(&self, last_synced_at: Option<DateTime<Utc>>) -> bool {
+        self.dirty_at.is_some()
+            || self
+                .column_last_synced_at
+                .is_some_and(|x| last_synced_at.is_none_or(|last_synced_at| x > last_synced_at))
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -53,15 +86,25 @@ pub struct ClientSyncMetadata<T> {
     pub unknown_data: HashMap<String, UnknownMetadata>,
 }
 
-impl<T: DeserializeOwned> From<Value> for ClientSyncMetadata<T> {
-    fn from(value: Value) -> Self {
-        from_value(value).unwrap()
+impl<T: DeserializeOwned> TryFrom<Value> for ClientSyncMetadata<T> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        from_value(value).map_err(|e| Error::Serialization {
+            message: "Column sync metadata is corrupted".to_string(),
+            source: e.into(),
+        })
     }
 }
 
-impl<T: Serialize> From<ClientSyncMetadata<T>> for Value {
-    fn from(item: ClientSyncMetadata<T>) -> Self {
-        to_value(item).unwrap()
+impl<T: Serialize> TryFrom<ClientSyncMetadata<T>> for Value {
+    type Error = Error;
+
+    fn try_from(item: ClientSyncMetadata<T>) -> Result<Self> {
+        to_value(item).map_err(|e| Error::Serialization {
+            message: "Failed to serialize column sync metadata".to_string(),
+            source: e.into(),
+        })
     }
 }
 
@@ -85,40 +128,66 @@ mod tests {
         // This test is important to ensure that we are not storing extra information when we
         // retrieve and put back into the database.
         let value: Value = json!({});
-        let metadata: ClientSyncMetadata<User> = value.clone().into();
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
 
-        assert_eq!(value, Value::from(metadata));
+        assert_eq!(value, Value::try_from(metadata).unwrap());
     }
 
     #[test]
     fn test_partial_filled_known_data() {
         let value: Value = json!({"name": {}});
-        let metadata: ClientSyncMetadata<User> = value.clone().into();
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
 
-        assert_eq!(value, Value::from(metadata));
+        assert_eq!(value, Value::try_from(metadata).unwrap());
     }
 
     #[test]
     fn test_partial_filled_metadata() {
         let value: Value = json!({"name": {"dirty_at": "2025-01-01T00:00:00Z"}});
-        let metadata: ClientSyncMetadata<User> = value.clone().into();
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
+
+        assert_eq!(value, Value::try_from(metadata).unwrap());
+    }
 
-        assert_eq!(value, Value::from(metadata));
+    #[test]
+    fn test_key_metadata() {
+        let value: Value = json!({"name": {
+            "dirty_at": "2025-01-01T00:00:00Z",
+            "keys": {"theme": {"dirty_at": "2025-01-01T00:00:00Z"}}
+        }});
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
+
+        assert_eq!(value, Value::try_from(metadata).unwrap());
     }
 
     #[test]
     fn test_insert_time_metadata() {
         let value: Value = json!({".insert_time": "2025-05-01T00:00:00Z"});
-        let metadata: ClientSyncMetadata<User> = value.clone().into();
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
 
-        assert_eq!(value, Value::from(metadata));
+        assert_eq!(value, Value::try_from(metadata).unwrap());
     }
 
     #[test]
     fn test_empty_unknown_data() {
         let value: Value = json!({"unknown": {}});
-        let metadata: ClientSyncMetadata<User> = value.clone().into();
+        let metadata: ClientSyncMetadata<User> = value.clone().try_into().unwrap();
+
+        assert_eq!(value, Value::try_from(metadata).unwrap());
+    }
+
+    #[test]
+    fn test_corrupted_metadata() {
+        let value: Value = json!({".insert_time": "not a date"});
+        let result = ClientSyncMetadata::<User>::try_from(value);
+
+        assert!(matches!(result, Err(Error::Serialization { .. })));
+    }
+
+    #[test]
+    fn test_non_object_metadata() {
+        let result = ClientSyncMetadata::<User>::try_from(json!([1, 2]));
 
-        assert_eq!(value, Value::from(metadata));
+        assert!(matches!(result, Err(Error::Serialization { .. })));
     }
 }
@@ -0,0 +1,90 @@
+//! Time source of the client sync metadata (`dirty_at`, `.insert_time` and the upload cutoff).
+//!
+//! Without the `hlc` feature, the device clock is used. With it, a hybrid logical clock stored in
+//! the client database is used instead: it never goes backward, never returns the same time twice
+//! and never falls behind a backend time passed to [`observe`]. Local changes are then always
+//! ordered after the data they were made on, whatever the skew of the device clock.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::Result;
+
+diesel::table! {
+    carburetor_clock (id) {
+        id -> Integer,
+        last_at -> TimestamptzSqlite,
+    }
+}
+
+#[cfg(feature = "hlc")]
+const CLOCK_ID: i32 = 1;
+
+/// Returns the time to record for a local change.
+#[cfg(not(feature = "hlc"))]
+pub fn now(_conn: &mut diesel::SqliteConnection) -> Result<DateTime<Utc>> {
+    Ok(super::get_utc_now())
+}
+
+/// Returns the time to record for a local change.
+#[cfg(feature = "hlc")]
+pub fn now(conn: &mut diesel::SqliteConnection) -> Result<DateTime<Utc>> {
+    use chrono::{SubsecRound, TimeDelta};
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        // Backend times have a microsecond precision, so the clock ticks in microseconds as well
+        let wall = super::get_utc_now().trunc_subsecs(6);
+        let next = match retrieve_last(conn)? {
+            Some(last) if last >= wall => last + TimeDelta::microseconds(1),
+            _ => wall,
+        };
+        store_last(conn, next)?;
+        Ok(next)
+    })
+}
+
+/// Moves the clock forward to `remote`, a time received from the backend.
+#[cfg(not(feature = "hlc"))]
+pub fn observe(_conn: &mut diesel::SqliteConnection, _remote: DateTime<Utc>) -> Result<()> {
+    Ok(())
+}
+
+/// Moves the clock forward to `remote`, a time received from the backend.
+#[cfg(feature = "hlc")]
+pub fn observe(conn: &mut diesel::SqliteConnection, remote: DateTime<Utc>) -> Result<()> {
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        if retrieve_last(conn)?.is_none_or(|last| last < remote) {
+            store_last(conn, remote)?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(feature = "hlc")]
+fn retrieve_last(conn: &mut diesel::SqliteConnection) -> Result<Option<DateTime<Utc>>> {
+    use diesel::{OptionalExtension, QueryDsl, RunQueryDsl};
+
+    carburetor_clock::table
+        .find(CLOCK_ID)
+        .select(carburetor_clock::last_at)
+        .first(conn)
+        .optional()
+        .map_err(|e| crate::error::Error::from_diesel("Failed to read the clock", e))
+}
+
+#[cfg(feature = "hlc")]
+fn store_last(conn: &mut diesel::SqliteConnection, last_at: DateTime<Utc>) -> Result<()> {
+    use diesel::{ExpressionMethods, RunQueryDsl};
+
+    diesel::replace_into(carburetor_clock::table)
+        .values((
+            carburetor_clock::id.eq(CLOCK_ID),
+            carburetor_clock::last_at.eq(last_at),
+        ))
+        .execute(conn)
+        .map_err(|e| crate::error::Error::from_diesel("Failed to advance the clock", e))?;
+
+    Ok(())
+}
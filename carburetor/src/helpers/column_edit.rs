@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{Error, Result};
+
+pub const CREATE_COLUMN_EDITS_SQL: &str = "CREATE TABLE carburetor_column_edits (\
+     table_name TEXT NOT NULL, \
+     row_id TEXT NOT NULL, \
+     column_name TEXT NOT NULL, \
+     edited_at TIMESTAMPTZ NOT NULL, \
+     PRIMARY KEY (table_name, row_id, column_name))";
+
+// A column without edit time comes from a client that does not send them, and the change is
+// treated as made when it is received. An edit time ahead of the backend is clamped to it, so a
+// client with a fast clock cannot win the later edits of the column
+const ACCEPT_COLUMN_EDITS_SQL: &str = "INSERT INTO carburetor_column_edits \
+     (table_name, row_id, column_name, edited_at) \
+     SELECT $1, $2, x.column_name, \
+     LEAST(COALESCE(x.edited_at, CURRENT_TIMESTAMP), CURRENT_TIMESTAMP) \
+     FROM UNNEST($3::TEXT[], $4::TIMESTAMPTZ[]) AS x(column_name, edited_at) \
+     ON CONFLICT (table_name, row_id, column_name) DO UPDATE SET \
+     edited_at = EXCLUDED.edited_at \
+     WHERE carburetor_column_edits.edited_at <= EXCLUDED.edited_at \
+     RETURNING column_name";
+
+const DELETE_COLUMN_EDITS_SQL: &str = "DELETE FROM carburetor_column_edits \
+     WHERE table_name = $1 AND row_id = ANY($2::TEXT[])";
+
+#[derive(diesel::QueryableByName)]
+struct AcceptedColumn {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    column_name: String,
+}
+
+/// Records the edit time of the uploaded columns of a row, and returns the columns whose edit is
+/// not older than the one recorded before. The other columns must be left unchanged.
+///
+/// Returns a diesel error so that it can run in the same transaction as the update of the row.
+pub fn accept_column_edits(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    row_id: &str,
+    edits: &[(&str, Option<DateTime<Utc>>)],
+) -> diesel::QueryResult<HashSet<String>> {
+    use diesel::RunQueryDsl;
+
+    if edits.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let (column_names, edited_at): (Vec<_>, Vec<_>) = edits.iter().copied().unzip();
+    let accepted = diesel::sql_query(ACCEPT_COLUMN_EDITS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Text, _>(row_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(column_names)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Nullable<diesel::sql_types::Timestamptz>>, _>(
+            edited_at,
+        )
+        .load::<AcceptedColumn>(conn)?;
+
+    Ok(accepted.into_iter().map(|x| x.column_name).collect())
+}
+
+#[cfg(feature = "async")]
+pub async fn accept_column_edits_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+    table_name: &str,
+    row_id: &str,
+    edits: &[(&str, Option<DateTime<Utc>>)],
+) -> diesel::QueryResult<HashSet<String>> {
+    use diesel_async::RunQueryDsl;
+
+    if edits.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let (column_names, edited_at): (Vec<_>, Vec<_>) = edits.iter().copied().unzip();
+    let accepted = diesel::sql_query(ACCEPT_COLUMN_EDITS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Text, _>(row_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(column_names)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Nullable<diesel::sql_types::Timestamptz>>, _>(
+            edited_at,
+        )
+        .load::<AcceptedColumn>(conn)
+        .await?;
+
+    Ok(accepted.into_iter().map(|x| x.column_name).collect())
+}
+
+/// Removes the edit times of rows that no longer exist, e.g. after a tombstone compaction. A row
+/// inserted again later with the same ID starts without edit times.
+pub fn delete_column_edits(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    row_ids: &[String],
+) -> Result<()> {
+    use diesel::RunQueryDsl;
+
+    if row_ids.is_empty() {
+        return Ok(());
+    }
+    diesel::sql_query(DELETE_COLUMN_EDITS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(row_ids)
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!("Failed to delete column edits of table '{}'", table_name),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+pub async fn delete_column_edits_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+    table_name: &str,
+    row_ids: &[String],
+) -> Result<()> {
+    use diesel_async::RunQueryDsl;
+
+    if row_ids.is_empty() {
+        return Ok(());
+    }
+    diesel::sql_query(DELETE_COLUMN_EDITS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(row_ids)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            Error::from_diesel(
+                format!("Failed to delete column edits of table '{}'", table_name),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
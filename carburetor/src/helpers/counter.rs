@@ -0,0 +1,223 @@
+//! Client side of `#[crdt(counter)]` columns, which upload the increments made on the device
+//! instead of the value, so that increments made on other devices in the meantime are not lost.
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    helpers::client_sync_metadata::{CounterIncrement, Metadata},
+    models::{CounterIncrementDelta, CounterIncrements},
+};
+
+/// Integer type of a `#[crdt(counter)]` column.
+pub trait CounterValue: Copy + Into<i64> + TryFrom<i64> {
+    const MIN: Self;
+    const MAX: Self;
+
+    /// Converts a sum of increments, saturating at the bounds of the type.
+    fn from_i64_saturating(value: i64) -> Self {
+        Self::try_from(value).unwrap_or(if value < 0 { Self::MIN } else { Self::MAX })
+    }
+}
+
+impl CounterValue for i16 {
+    const MIN: Self = i16::MIN;
+    const MAX: Self = i16::MAX;
+}
+
+impl CounterValue for i32 {
+    const MIN: Self = i32::MIN;
+    const MAX: Self = i32::MAX;
+}
+
+impl CounterValue for i64 {
+    const MIN: Self = i64::MIN;
+    const MAX: Self = i64::MAX;
+}
+
+/// Records the change from `old` to `new` as an increment, and marks the column as dirty.
+///
+/// `last_synced_at` is the one of the local row, whose value already includes the increments
+/// uploaded up to then, so that those are dropped.
+pub fn record_increment<T: CounterValue>(
+    metadata: &mut Option<Metadata>,
+    old: T,
+    new: T,
+    now: DateTime<Utc>,
+    last_synced_at: Option<DateTime<Utc>>,
+) {
+    let delta = new.into().saturating_sub(old.into());
+    if delta == 0 {
+        return;
+    }
+    let metadata = metadata.get_or_insert_default();
+    drop_downloaded_increments(metadata, last_synced_at);
+    metadata.dirty_at = Some(now);
+    metadata.increments.push(CounterIncrement {
+        at: now,
+        delta,
+        synced_at: None,
+    });
+}
+
+/// Increments not uploaded yet up to the cutoff, or `None` when there is none. `device_id` is the
+/// one of this device, see `carburetor::helpers::id::device_id`.
+pub fn pending_increments(
+    metadata: &Metadata,
+    device_id: &str,
+    cutoff_at: DateTime<Utc>,
+) -> Option<CounterIncrements> {
+    let increments = metadata
+        .increments
+        .iter()
+        .filter(|x| x.synced_at.is_none() && x.at <= cutoff_at)
+        .map(|x| CounterIncrementDelta {
+            at: x.at,
+            delta: x.delta,
+        })
+        .collect::<Vec<_>>();
+    if increments.is_empty() {
+        return None;
+    }
+    Some(CounterIncrements {
+        device_id: device_id.to_string(),
+        increments,
+    })
+}
+
+/// Marks the increments uploaded with the cutoff as accepted by the backend.
+pub fn mark_increments_synced(
+    metadata: &mut Metadata,
+    cutoff_at: DateTime<Utc>,
+    synced_at: DateTime<Utc>,
+    last_synced_at: Option<DateTime<Utc>>,
+) {
+    drop_downloaded_increments(metadata, last_synced_at);
+    for increment in metadata
+        .increments
+        .iter_mut()
+        .filter(|x| x.synced_at.is_none() && x.at <= cutoff_at)
+    {
+        increment.synced_at = Some(synced_at);
+    }
+}
+
+/// Adds the increments that the downloaded value does not include yet, which are those not
+/// uploaded yet and those uploaded after the downloaded row was last synced.
+pub fn merge_download<T: CounterValue>(
+    downloaded: T,
+    metadata: Option<&Metadata>,
+    downloaded_last_synced_at: Option<DateTime<Utc>>,
+) -> T {
+    let Some(metadata) = metadata else {
+        return downloaded;
+    };
+    let pending = metadata
+        .increments
+        .iter()
+        .filter(|x| {
+            x.synced_at.is_none_or(|synced_at| {
+                downloaded_last_synced_at.is_none_or(|last_synced_at| synced_at > last_synced_at)
+            })
+        })
+        .fold(0i64, |sum, x| sum.saturating_add(x.delta));
+    T::from_i64_saturating(downloaded.into().saturating_add(pending))
+}
+
+fn drop_downloaded_increments(metadata: &mut Metadata, last_synced_at: Option<DateTime<Utc>>) {
+    if let Some(last_synced_at) = last_synced_at {
+        metadata.increments.retain(|x| {
+            x.synced_at
+                .is_none_or(|synced_at| synced_at > last_synced_at)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pending_increments() {
+        let mut metadata = None;
+        record_increment(&mut metadata, 10, 13, time("2025-01-01T00:00:00Z"), None);
+        record_increment(&mut metadata, 13, 12, time("2025-01-01T00:01:00Z"), None);
+        record_increment(&mut metadata, 12, 20, time("2025-01-01T00:03:00Z"), None);
+        let metadata = metadata.unwrap();
+
+        let pending =
+            pending_increments(&metadata, "device", time("2025-01-01T00:02:00Z")).unwrap();
+        assert_eq!(pending.device_id, "device");
+        assert_eq!(
+            pending.increments,
+            vec![
+                CounterIncrementDelta {
+                    at: time("2025-01-01T00:00:00Z"),
+                    delta: 3,
+                },
+                CounterIncrementDelta {
+                    at: time("2025-01-01T00:01:00Z"),
+                    delta: -1,
+                },
+            ]
+        );
+        assert!(pending_increments(&metadata, "device", time("2024-12-31T00:00:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_unchanged_value_is_not_recorded() {
+        let mut metadata = None;
+        record_increment(&mut metadata, 5i16, 5, time("2025-01-01T00:00:00Z"), None);
+
+        assert!(metadata.is_none());
+    }
+
+    #[test]
+    fn test_synced_increments_are_not_uploaded_again() {
+        let mut metadata = None;
+        record_increment(&mut metadata, 0, 1, time("2025-01-01T00:00:00Z"), None);
+        let mut metadata = metadata.unwrap();
+        mark_increments_synced(
+            &mut metadata,
+            time("2025-01-01T00:01:00Z"),
+            time("2025-01-01T00:02:00Z"),
+            None,
+        );
+
+        assert!(pending_increments(&metadata, "device", time("2025-01-01T00:05:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_merge_download() {
+        let mut metadata = None;
+        record_increment(&mut metadata, 0, 1, time("2025-01-01T00:00:00Z"), None);
+        let mut metadata = Some(metadata.unwrap());
+        mark_increments_synced(
+            metadata.as_mut().unwrap(),
+            time("2025-01-01T00:01:00Z"),
+            time("2025-01-01T00:02:00Z"),
+            None,
+        );
+        record_increment(&mut metadata, 1, 3, time("2025-01-01T00:03:00Z"), None);
+
+        // Downloaded before the upload of the first increment was applied
+        assert_eq!(
+            merge_download(10, metadata.as_ref(), Some(time("2025-01-01T00:01:30Z"))),
+            13
+        );
+        // Downloaded after, so only the increment not uploaded yet is added
+        assert_eq!(
+            merge_download(10, metadata.as_ref(), Some(time("2025-01-01T00:02:00Z"))),
+            12
+        );
+    }
+
+    #[test]
+    fn test_saturating_conversion() {
+        assert_eq!(i16::from_i64_saturating(100_000), i16::MAX);
+        assert_eq!(i16::from_i64_saturating(-100_000), i16::MIN);
+    }
+}
@@ -0,0 +1,163 @@
+//! Backend side of `#[crdt(counter)]` columns, which keeps track of the increments already added
+//! to each row, so that an upload received again (e.g. after its response was lost) does not add
+//! them twice.
+
+use crate::{
+    error::{Error, Result},
+    models::CounterIncrements,
+};
+
+pub const CREATE_COUNTER_INCREMENTS_SQL: &str = "CREATE TABLE carburetor_counter_increments (\
+     table_name TEXT NOT NULL, \
+     row_id TEXT NOT NULL, \
+     column_name TEXT NOT NULL, \
+     device_id TEXT NOT NULL, \
+     counted_until TIMESTAMPTZ NOT NULL, \
+     last_delta BIGINT NOT NULL, \
+     PRIMARY KEY (table_name, row_id, column_name, device_id))";
+
+// A device uploads every increment it made since the last accepted upload, in the order they were
+// made, so the increments up to `counted_until` are already added. The delta is computed against
+// the locked row on conflict, so that two deliveries of the same upload running at once do not
+// both add it
+const ACCEPT_COUNTER_INCREMENTS_SQL: &str = "INSERT INTO carburetor_counter_increments \
+     (table_name, row_id, column_name, device_id, counted_until, last_delta) \
+     SELECT $1, $2, $3, $4, MAX(x.at), COALESCE(SUM(x.delta), 0)::BIGINT \
+     FROM UNNEST($5::TIMESTAMPTZ[], $6::BIGINT[]) AS x(at, delta) \
+     ON CONFLICT (table_name, row_id, column_name, device_id) DO UPDATE SET \
+     counted_until = GREATEST(carburetor_counter_increments.counted_until, EXCLUDED.counted_until), \
+     last_delta = (SELECT COALESCE(SUM(x.delta), 0)::BIGINT \
+     FROM UNNEST($5::TIMESTAMPTZ[], $6::BIGINT[]) AS x(at, delta) \
+     WHERE x.at > carburetor_counter_increments.counted_until) \
+     RETURNING last_delta";
+
+const DELETE_COUNTER_INCREMENTS_SQL: &str = "DELETE FROM carburetor_counter_increments \
+     WHERE table_name = $1 AND row_id = ANY($2::TEXT[])";
+
+#[derive(diesel::QueryableByName)]
+struct AcceptedDelta {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    last_delta: i64,
+}
+
+/// Records the uploaded increments of a column of a row, and returns the sum of those not added
+/// to it before, which is the delta to add to the stored value.
+///
+/// Returns a diesel error so that it can run in the same transaction as the update of the row.
+pub fn accept_counter_increments(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    row_id: &str,
+    column_name: &str,
+    increments: &CounterIncrements,
+) -> diesel::QueryResult<i64> {
+    use diesel::RunQueryDsl;
+
+    if increments.increments.is_empty() {
+        return Ok(0);
+    }
+    let (at, delta): (Vec<_>, Vec<_>) = increments
+        .increments
+        .iter()
+        .map(|x| (x.at, x.delta))
+        .unzip();
+    let accepted = diesel::sql_query(ACCEPT_COUNTER_INCREMENTS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Text, _>(row_id)
+        .bind::<diesel::sql_types::Text, _>(column_name)
+        .bind::<diesel::sql_types::Text, _>(&increments.device_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Timestamptz>, _>(at)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::BigInt>, _>(delta)
+        .get_result::<AcceptedDelta>(conn)?;
+
+    Ok(accepted.last_delta)
+}
+
+#[cfg(feature = "async")]
+pub async fn accept_counter_increments_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+    table_name: &str,
+    row_id: &str,
+    column_name: &str,
+    increments: &CounterIncrements,
+) -> diesel::QueryResult<i64> {
+    use diesel_async::RunQueryDsl;
+
+    if increments.increments.is_empty() {
+        return Ok(0);
+    }
+    let (at, delta): (Vec<_>, Vec<_>) = increments
+        .increments
+        .iter()
+        .map(|x| (x.at, x.delta))
+        .unzip();
+    let accepted = diesel::sql_query(ACCEPT_COUNTER_INCREMENTS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Text, _>(row_id)
+        .bind::<diesel::sql_types::Text, _>(column_name)
+        .bind::<diesel::sql_types::Text, _>(&increments.device_id)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Timestamptz>, _>(at)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::BigInt>, _>(delta)
+        .get_result::<AcceptedDelta>(conn)
+        .await?;
+
+    Ok(accepted.last_delta)
+}
+
+/// Removes the increments recorded for rows that no longer exist, e.g. after a tombstone
+/// compaction.
+pub fn delete_counter_increments(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    row_ids: &[String],
+) -> Result<()> {
+    use diesel::RunQueryDsl;
+
+    if row_ids.is_empty() {
+        return Ok(());
+    }
+    diesel::sql_query(DELETE_COUNTER_INCREMENTS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(row_ids)
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to delete counter increments of table '{}'",
+                    table_name
+                ),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+pub async fn delete_counter_increments_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+    table_name: &str,
+    row_ids: &[String],
+) -> Result<()> {
+    use diesel_async::RunQueryDsl;
+
+    if row_ids.is_empty() {
+        return Ok(());
+    }
+    diesel::sql_query(DELETE_COUNTER_INCREMENTS_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(row_ids)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to delete counter increments of table '{}'",
+                    table_name
+                ),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! ID strategies that can be selected with `#[id(...)]`.
+//!
+//! The generated `insert_<table>` calls the function matching the strategy of the table, and so
+//! does the upload when a record has to be inserted again under a new ID.
+
+use crate::error::Result;
+
+diesel::table! {
+    carburetor_device (id) {
+        id -> Integer,
+        device_id -> Text,
+        counter -> BigInt,
+    }
+}
+
+const DEVICE_ROW_ID: i32 = 1;
+
+/// Returns a random UUID, as used by `#[id(uuid_v4)]`.
+#[cfg(feature = "uuid")]
+pub fn uuid_v4() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Returns a UUID ordered by creation time, as used by `#[id(uuid_v7)]`.
+#[cfg(feature = "uuid")]
+pub fn uuid_v7() -> String {
+    uuid::Uuid::now_v7().to_string()
+}
+
+/// Returns a ULID, as used by `#[id(ulid)]`.
+#[cfg(feature = "ulid")]
+pub fn ulid() -> String {
+    ulid::Ulid::generate().to_string()
+}
+
+/// Returns the ID of this device, drawn at random and stored in the client database the first time.
+pub fn device_id(conn: &mut diesel::SqliteConnection) -> Result<String> {
+    use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+
+    use crate::error::Error;
+
+    conn.transaction(|conn| {
+        let device_id = carburetor_device::table
+            .find(DEVICE_ROW_ID)
+            .select(carburetor_device::device_id)
+            .first::<String>(conn)
+            .optional()
+            .map_err(|e| Error::from_diesel("Failed to read the device ID", e))?;
+        if let Some(device_id) = device_id {
+            return Ok(device_id);
+        }
+
+        let device_id = format!("{:016x}", super::random_u64());
+        diesel::insert_into(carburetor_device::table)
+            .values((
+                carburetor_device::id.eq(DEVICE_ROW_ID),
+                carburetor_device::device_id.eq(&device_id),
+                carburetor_device::counter.eq(0),
+            ))
+            .execute(conn)
+            .map_err(|e| Error::from_diesel("Failed to store the device ID", e))?;
+
+        Ok(device_id)
+    })
+}
+
+/// Returns the ID of this device followed by the next value of its counter, as used by
+/// `#[id(device_counter)]`.
+///
+/// The device ID is drawn at random the first time and stored along with the counter in the
+/// client database, so IDs are never reused on this device, even across restarts.
+pub fn device_counter(conn: &mut diesel::SqliteConnection) -> Result<String> {
+    use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+
+    use crate::error::Error;
+
+    conn.transaction(|conn| {
+        let device = carburetor_device::table
+            .find(DEVICE_ROW_ID)
+            .select((carburetor_device::device_id, carburetor_device::counter))
+            .first::<(String, i64)>(conn)
+            .optional()
+            .map_err(|e| Error::from_diesel("Failed to read the device counter", e))?;
+        let (device_id, counter) = match device {
+            Some((device_id, counter)) => (device_id, counter + 1),
+            None => (format!("{:016x}", super::random_u64()), 1),
+        };
+
+        diesel::replace_into(carburetor_device::table)
+            .values((
+                carburetor_device::id.eq(DEVICE_ROW_ID),
+                carburetor_device::device_id.eq(&device_id),
+                carburetor_device::counter.eq(counter),
+            ))
+            .execute(conn)
+            .map_err(|e| Error::from_diesel("Failed to advance the device counter", e))?;
+
+        Ok(format!("{}-{}", device_id, counter))
+    })
+}
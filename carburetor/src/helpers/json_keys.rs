@@ -0,0 +1,241 @@
+//! Client side of `#[merge(json_keys)]` columns, which keep the sync metadata of each top-level
+//! key of a JSON object, so that keys changed on different devices do not overwrite each other.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::{helpers::client_sync_metadata::Metadata, models::JsonKeysPatch};
+
+#[derive(Debug, thiserror::Error)]
+#[error("The value of a `#[merge(json_keys)]` column needs to be a JSON object")]
+pub struct NotAnObject;
+
+/// Marks the keys that differ between `old` and `new` as dirty, along with the column when any
+/// key changed. A removed key is dirty as well, so that the removal is uploaded.
+pub fn mark_dirty_keys(
+    metadata: &mut Option<Metadata>,
+    old: &Value,
+    new: &Value,
+    now: DateTime<Utc>,
+) -> Result<(), NotAnObject> {
+    let new = new.as_object().ok_or(NotAnObject)?;
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+
+    let changed_keys = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key)
+        .chain(old.keys().filter(|key| !new.contains_key(*key)))
+        .collect::<Vec<_>>();
+    if changed_keys.is_empty() {
+        return Ok(());
+    }
+
+    let metadata = metadata.get_or_insert_default();
+    metadata.dirty_at = Some(now);
+    for key in changed_keys {
+        metadata.keys.entry(key.clone()).or_default().dirty_at = Some(now);
+    }
+    Ok(())
+}
+
+/// Builds the upload of the dirty keys from the local value.
+pub fn dirty_keys_patch(value: &Value, metadata: &Metadata) -> JsonKeysPatch {
+    let mut patch = JsonKeysPatch::default();
+    for (key, _) in metadata.keys.iter().filter(|(_, x)| x.dirty_at.is_some()) {
+        match value.get(key) {
+            Some(value) => {
+                patch.set.insert(key.clone(), value.clone());
+            }
+            None => patch.remove.push(key.clone()),
+        }
+    }
+    patch
+}
+
+/// Clears the keys changed before the cutoff of an accepted upload, the same way as the column.
+pub fn mark_keys_synced(
+    metadata: &mut Metadata,
+    cutoff_at: DateTime<Utc>,
+    last_synced_at: DateTime<Utc>,
+) {
+    for metadata in metadata.keys.values_mut() {
+        if metadata.dirty_at.is_some_and(|x| x < cutoff_at) {
+            metadata.dirty_at = None;
+            metadata.column_last_synced_at = Some(last_synced_at);
+        }
+    }
+}
+
+/// Merges a downloaded value into the local one, keeping the local value of the dirty keys.
+/// `last_synced_at` is the one of the local row, before the download is stored.
+///
+/// When either value is not an object, keys cannot be told apart and the whole value is kept
+/// while the column is dirty.
+pub fn merge_download(
+    local: &Value,
+    downloaded: Value,
+    metadata: Option<&Metadata>,
+    last_synced_at: Option<DateTime<Utc>>,
+) -> Value {
+    let Some(metadata) = metadata else {
+        return downloaded;
+    };
+    let (Some(local), Value::Object(mut merged)) = (local.as_object(), downloaded.clone()) else {
+        return if metadata.is_dirty(last_synced_at) {
+            local.clone()
+        } else {
+            downloaded
+        };
+    };
+
+    for (key, _) in metadata
+        .keys
+        .iter()
+        .filter(|(_, x)| x.is_dirty(last_synced_at))
+    {
+        match local.get(key) {
+            Some(value) => {
+                merged.insert(key.clone(), value.clone());
+            }
+            None => {
+                merged.remove(key);
+            }
+        }
+    }
+    Value::Object(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_mark_dirty_keys() {
+        let mut metadata = None;
+        let now = time("2025-01-01T00:00:00Z");
+
+        mark_dirty_keys(
+            &mut metadata,
+            &json!({"theme": "dark", "lang": "en", "font": 12}),
+            &json!({"theme": "light", "lang": "en", "zoom": 2}),
+            now,
+        )
+        .unwrap();
+
+        let metadata = metadata.unwrap();
+        assert_eq!(metadata.dirty_at, Some(now));
+        assert_eq!(
+            metadata.keys.keys().collect::<Vec<_>>(),
+            ["font", "theme", "zoom"]
+        );
+    }
+
+    #[test]
+    fn test_mark_dirty_keys_unchanged() {
+        let mut metadata = None;
+
+        mark_dirty_keys(
+            &mut metadata,
+            &json!({"theme": "dark"}),
+            &json!({"theme": "dark"}),
+            time("2025-01-01T00:00:00Z"),
+        )
+        .unwrap();
+
+        assert!(metadata.is_none());
+    }
+
+    #[test]
+    fn test_mark_dirty_keys_not_an_object() {
+        let result = mark_dirty_keys(
+            &mut None,
+            &json!({}),
+            &json!([1]),
+            time("2025-01-01T00:00:00Z"),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dirty_keys_patch() {
+        let mut metadata = None;
+        mark_dirty_keys(
+            &mut metadata,
+            &json!({"theme": "dark", "font": 12, "lang": "en"}),
+            &json!({"theme": "light", "lang": "en"}),
+            time("2025-01-01T00:00:00Z"),
+        )
+        .unwrap();
+
+        let patch = dirty_keys_patch(&json!({"theme": "light", "lang": "en"}), &metadata.unwrap());
+
+        assert_eq!(Value::Object(patch.set), json!({"theme": "light"}));
+        assert_eq!(patch.remove, ["font"]);
+    }
+
+    #[test]
+    fn test_merge_download_keeps_dirty_keys() {
+        let mut metadata = None;
+        mark_dirty_keys(
+            &mut metadata,
+            &json!({"theme": "dark", "font": 12}),
+            &json!({"theme": "light"}),
+            time("2025-01-01T00:00:00Z"),
+        )
+        .unwrap();
+
+        let merged = merge_download(
+            &json!({"theme": "light"}),
+            json!({"theme": "dark", "font": 14, "lang": "fr"}),
+            metadata.as_ref(),
+            Some(time("2025-01-01T00:00:00Z")),
+        );
+
+        assert_eq!(merged, json!({"theme": "light", "lang": "fr"}));
+    }
+
+    #[test]
+    fn test_merge_download_after_synced_key() {
+        let mut metadata = None;
+        mark_dirty_keys(
+            &mut metadata,
+            &json!({}),
+            &json!({"theme": "light"}),
+            time("2025-01-01T00:00:00Z"),
+        )
+        .unwrap();
+        let mut metadata = metadata.unwrap();
+        mark_keys_synced(
+            &mut metadata,
+            time("2025-01-01T00:01:00Z"),
+            time("2025-01-01T00:02:00Z"),
+        );
+
+        // The local row was last downloaded before the upload of the key
+        let merged = merge_download(
+            &json!({"theme": "light"}),
+            json!({"lang": "fr"}),
+            Some(&metadata),
+            Some(time("2025-01-01T00:00:30Z")),
+        );
+        assert_eq!(merged, json!({"theme": "light", "lang": "fr"}));
+
+        // The local row was downloaded again since the upload of the key
+        let merged = merge_download(
+            &json!({"theme": "light"}),
+            json!({"theme": "dark"}),
+            Some(&metadata),
+            Some(time("2025-01-01T00:03:00Z")),
+        );
+        assert_eq!(merged, json!({"theme": "dark"}));
+    }
+}
@@ -4,6 +4,9 @@ pub struct ColumnDef {
     pub primary_key: bool,
     pub null: bool,
     pub default: Option<String>,
+    /// Restricts the column to these values with a `CHECK` constraint, e.g. for the variants of
+    /// an `Enum<T>` column.
+    pub allowed_values: Option<&'static [&'static str]>,
 }
 
 impl ColumnDef {
@@ -18,8 +21,22 @@ impl ColumnDef {
         if let Some(ref default) = self.default {
             def.push_str(&format!(" DEFAULT {}", default));
         }
+        if let Some(check) = self.check_sql() {
+            def.push_str(&format!(" CHECK {}", check));
+        }
         def
     }
+
+    fn check_sql(&self) -> Option<String> {
+        self.allowed_values.map(|values| {
+            let values = values
+                .iter()
+                .map(|x| format!("'{}'", x.replace("'", "''")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({} IN ({}))", self.name, values)
+        })
+    }
 }
 
 /// Normalized representation of an existing column in the database, produced
@@ -54,21 +71,60 @@ pub mod backend {
     fn normalize_pg_data_type(data_type: &str) -> &str {
         match data_type {
             "text" => "TEXT",
+            "character varying" => "VARCHAR",
             "smallint" => "SMALLINT",
             "integer" => "INTEGER",
             "bigint" => "BIGINT",
             "real" => "REAL",
             "double precision" => "DOUBLE PRECISION",
+            "numeric" => "NUMERIC",
+            "money" => "MONEY",
             "boolean" => "BOOLEAN",
             "timestamp without time zone" => "TIMESTAMP",
             "timestamp with time zone" => "TIMESTAMPTZ",
             "date" => "DATE",
             "time without time zone" => "TIME",
+            "interval" => "INTERVAL",
+            "json" => "JSON",
             "jsonb" => "JSONB",
+            "uuid" => "UUID",
+            "bytea" => "BYTEA",
+            "inet" => "INET",
             _ => data_type,
         }
     }
 
+    /// Same as `normalize_pg_data_type` for the element of an array column, which
+    /// `information_schema.columns` only gives as the `udt_name` of the array, e.g. `_int4`
+    /// for `INTEGER[]`.
+    fn normalize_pg_array_udt_name(udt_name: &str) -> String {
+        let element = udt_name.strip_prefix('_').unwrap_or(udt_name);
+        let element = match element {
+            "text" => "TEXT",
+            "varchar" => "VARCHAR",
+            "int2" => "SMALLINT",
+            "int4" => "INTEGER",
+            "int8" => "BIGINT",
+            "float4" => "REAL",
+            "float8" => "DOUBLE PRECISION",
+            "numeric" => "NUMERIC",
+            "money" => "MONEY",
+            "bool" => "BOOLEAN",
+            "timestamp" => "TIMESTAMP",
+            "timestamptz" => "TIMESTAMPTZ",
+            "date" => "DATE",
+            "time" => "TIME",
+            "interval" => "INTERVAL",
+            "json" => "JSON",
+            "jsonb" => "JSONB",
+            "uuid" => "UUID",
+            "bytea" => "BYTEA",
+            "inet" => "INET",
+            _ => element,
+        };
+        format!("{}[]", element)
+    }
+
     pub fn check_table_exists(
         conn: &mut diesel::PgConnection,
         table_name: &str,
@@ -84,10 +140,7 @@ pub mod backend {
         )
         .bind::<diesel::sql_types::Text, _>(table_name)
         .get_result(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!("Failed to check if table '{}' exists", table_name),
-            source: e.into(),
-        })?;
+        .map_err(|e| crate::error::Error::from_diesel(format!("Failed to check if table '{}' exists", table_name), e))?;
 
         Ok(result.exists)
     }
@@ -102,6 +155,8 @@ pub mod backend {
             column_name: String,
             #[diesel(sql_type = diesel::sql_types::Text)]
             data_type: String,
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            udt_name: String,
             #[diesel(sql_type = diesel::sql_types::Bool)]
             is_nullable: bool,
             #[diesel(sql_type = diesel::sql_types::Bool)]
@@ -113,6 +168,7 @@ pub mod backend {
         let rows: Vec<Row> = diesel::sql_query(
             "SELECT c.column_name, \
              c.data_type, \
+             c.udt_name::text AS udt_name, \
              CASE WHEN c.is_nullable = 'YES' THEN true ELSE false END AS is_nullable, \
              COALESCE(pk.is_primary_key, false) AS is_primary_key, \
              c.column_default \
@@ -132,16 +188,21 @@ pub mod backend {
         )
         .bind::<diesel::sql_types::Text, _>(table_name)
         .load(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!("Failed to introspect columns of table '{}'", table_name),
-            source: e.into(),
+        .map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!("Failed to introspect columns of table '{}'", table_name),
+                e,
+            )
         })?;
 
         Ok(rows
             .into_iter()
             .map(|r| ExistingColumn {
                 name: r.column_name,
-                sql_type: normalize_pg_data_type(&r.data_type).to_string(),
+                sql_type: match r.data_type.as_str() {
+                    "ARRAY" => normalize_pg_array_udt_name(&r.udt_name),
+                    data_type => normalize_pg_data_type(data_type).to_string(),
+                },
                 is_nullable: r.is_nullable,
                 is_primary_key: r.is_primary_key,
                 column_default: r.column_default,
@@ -159,7 +220,7 @@ pub mod backend {
         for col in declared {
             if let Some(db_col) = existing.iter().find(|e| e.name == col.name) {
                 if db_col.sql_type != col.sql_type {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a type mismatch: \
                          schema declares '{}', but the database has '{}'.",
                         col.name, table_name, col.sql_type, db_col.sql_type
@@ -167,7 +228,7 @@ pub mod backend {
                 }
 
                 if col.primary_key != db_col.is_primary_key {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a primary key mismatch: \
                          schema declares {}, but the database has {}.",
                         col.name, table_name, col.primary_key, db_col.is_primary_key
@@ -175,7 +236,7 @@ pub mod backend {
                 }
 
                 if !col.null && db_col.is_nullable {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a nullability mismatch: \
                          schema declares NOT NULL, but the database allows NULL.",
                         col.name, table_name
@@ -196,7 +257,7 @@ pub mod backend {
                 && !col.is_nullable
                 && col.column_default.is_none()
             {
-                return Err(crate::error::Error::Migration(format!(
+                return Err(crate::error::Error::SchemaMismatch(format!(
                     "Column '{}' on table '{}' is not in the schema, is NOT NULL, and has no default \
                      value. INSERT operations generated by carburetor omit columns not in the schema, \
                      which would violate the NOT NULL constraint. Either make the column nullable, \
@@ -218,15 +279,48 @@ pub mod backend {
             table_name,
             col.to_sql()
         );
-        diesel::sql_query(&query)
-            .execute(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!(
+        diesel::sql_query(&query).execute(conn).map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!(
                     "Failed to add column '{}' to table '{}'",
                     col.name, table_name
                 ),
-                source: e.into(),
+                e,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Replaces the `CHECK` constraint of the columns with allowed values, so that values added
+    /// since the column was created are accepted.
+    pub(crate) fn replace_allowed_values_checks(
+        conn: &mut diesel::PgConnection,
+        table_name: &str,
+        declared: &[ColumnDef],
+    ) -> crate::error::Result<()> {
+        for col in declared {
+            let Some(check) = col.check_sql() else {
+                continue;
+            };
+            // Same name as Postgres gives to the constraint declared along with the column
+            let constraint_name = format!("{}_{}_check", table_name, col.name);
+            let query = format!(
+                "ALTER TABLE {table} DROP CONSTRAINT IF EXISTS {constraint}; \
+                 ALTER TABLE {table} ADD CONSTRAINT {constraint} CHECK {check}",
+                table = table_name,
+                constraint = constraint_name,
+                check = check
+            );
+            diesel::connection::SimpleConnection::batch_execute(conn, &query).map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!(
+                        "Failed to update allowed values of column '{}' on table '{}'",
+                        col.name, table_name
+                    ),
+                    e,
+                )
             })?;
+        }
         Ok(())
     }
 
@@ -240,15 +334,15 @@ pub mod backend {
                 "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
                 table_name, col.name
             );
-            diesel::sql_query(&query)
-                .execute(conn)
-                .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                    message: format!(
+            diesel::sql_query(&query).execute(conn).map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!(
                         "Failed to make column '{}' nullable on table '{}'",
                         col.name, table_name
                     ),
-                    source: e.into(),
-                })?;
+                    e,
+                )
+            })?;
         }
         Ok(())
     }
@@ -274,9 +368,11 @@ pub mod client {
         )
         .bind::<diesel::sql_types::Text, _>(table_name)
         .get_result(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!("Failed to check if table '{}' exists", table_name),
-            source: e.into(),
+        .map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!("Failed to check if table '{}' exists", table_name),
+                e,
+            )
         })?;
 
         Ok(result.count > 0)
@@ -302,9 +398,11 @@ pub mod client {
 
         let rows: Vec<Row> = diesel::sql_query(format!("PRAGMA table_info({})", table_name))
             .load(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!("Failed to introspect columns of table '{}'", table_name),
-                source: e.into(),
+            .map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!("Failed to introspect columns of table '{}'", table_name),
+                    e,
+                )
             })?;
 
         Ok(rows
@@ -347,7 +445,7 @@ pub mod client {
                 let declared_affinity = sqlite_affinity(&col.sql_type);
                 let db_affinity = sqlite_affinity(&db_col.sql_type);
                 if declared_affinity != db_affinity {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a type mismatch: \
                          schema declares '{}' ({} affinity), but the database has '{}' ({} affinity).",
                         col.name,
@@ -360,7 +458,7 @@ pub mod client {
                 }
 
                 if col.primary_key != db_col.is_primary_key {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a primary key mismatch: \
                          schema declares {}, but the database has {}.",
                         col.name, table_name, col.primary_key, db_col.is_primary_key
@@ -368,7 +466,7 @@ pub mod client {
                 }
 
                 if !col.null && db_col.is_nullable {
-                    return Err(crate::error::Error::Migration(format!(
+                    return Err(crate::error::Error::SchemaMismatch(format!(
                         "Column '{}' on table '{}' has a nullability mismatch: \
                          schema declares NOT NULL, but the database allows NULL.",
                         col.name, table_name
@@ -389,7 +487,7 @@ pub mod client {
                 && !col.is_nullable
                 && col.column_default.is_none()
             {
-                return Err(crate::error::Error::Migration(format!(
+                return Err(crate::error::Error::SchemaMismatch(format!(
                     "Column '{}' on table '{}' is not in the schema, is NOT NULL, and has no default \
                      value. INSERT operations generated by carburetor omit columns not in the schema, \
                      which would violate the NOT NULL constraint. Either make the column nullable, \
@@ -407,15 +505,15 @@ pub mod client {
         col: &ColumnDef,
     ) -> crate::error::Result<()> {
         let query = format!("ALTER TABLE {} ADD COLUMN {}", table_name, col.to_sql());
-        diesel::sql_query(&query)
-            .execute(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!(
+        diesel::sql_query(&query).execute(conn).map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!(
                     "Failed to add column '{}' to table '{}'",
                     col.name, table_name
                 ),
-                source: e.into(),
-            })?;
+                e,
+            )
+        })?;
         Ok(())
     }
 
@@ -462,22 +560,26 @@ pub mod client {
         // A failed prior rebuild may have left a stale temp table behind.
         diesel::sql_query(format!("DROP TABLE IF EXISTS {}", tmp_table))
             .execute(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!(
-                    "Failed to drop stale temp table for rebuilding '{}'",
-                    table_name
-                ),
-                source: e.into(),
+            .map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!(
+                        "Failed to drop stale temp table for rebuilding '{}'",
+                        table_name
+                    ),
+                    e,
+                )
             })?;
 
         diesel::sql_query(format!("CREATE TABLE {} ({})", tmp_table, column_defs_str))
             .execute(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!(
-                    "Failed to create temp table for rebuilding '{}'",
-                    table_name
-                ),
-                source: e.into(),
+            .map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!(
+                        "Failed to create temp table for rebuilding '{}'",
+                        table_name
+                    ),
+                    e,
+                )
             })?;
 
         let col_list = existing
@@ -491,22 +593,26 @@ pub mod client {
             tmp_table, col_list, col_list, table_name
         ))
         .execute(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!(
-                "Failed to copy data during table rebuild for '{}'",
-                table_name
-            ),
-            source: e.into(),
+        .map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!(
+                    "Failed to copy data during table rebuild for '{}'",
+                    table_name
+                ),
+                e,
+            )
         })?;
 
         diesel::sql_query(format!("DROP TABLE {}", table_name))
             .execute(conn)
-            .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-                message: format!(
-                    "Failed to drop old table during rebuild of '{}'",
-                    table_name
-                ),
-                source: e.into(),
+            .map_err(|e| {
+                crate::error::Error::from_diesel(
+                    format!(
+                        "Failed to drop old table during rebuild of '{}'",
+                        table_name
+                    ),
+                    e,
+                )
             })?;
 
         diesel::sql_query(format!(
@@ -514,12 +620,14 @@ pub mod client {
             tmp_table, table_name
         ))
         .execute(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!(
-                "Failed to rename temp table during rebuild of '{}'",
-                table_name
-            ),
-            source: e.into(),
+        .map_err(|e| {
+            crate::error::Error::from_diesel(
+                format!(
+                    "Failed to rename temp table during rebuild of '{}'",
+                    table_name
+                ),
+                e,
+            )
         })?;
 
         Ok(())
@@ -596,6 +704,9 @@ pub fn alter_table(
         drop_not_null(conn, table_name, &needs_drop_not_null)?;
     }
 
+    #[cfg(for_backend)]
+    backend::replace_allowed_values_checks(conn, table_name, declared_columns)?;
+
     Ok(())
 }
 
@@ -618,11 +729,9 @@ pub fn create_table(
 
     let query = format!("CREATE TABLE {} ({})", table_name, column_defs_str);
 
-    conn.batch_execute(&query)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: format!("Failed to create table '{}'", table_name),
-            source: e.into(),
-        })?;
+    conn.batch_execute(&query).map_err(|e| {
+        crate::error::Error::from_diesel(format!("Failed to create table '{}'", table_name), e)
+    })?;
 
     Ok(())
 }
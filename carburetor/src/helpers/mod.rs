@@ -2,9 +2,27 @@
 pub mod carburetor_offset;
 #[cfg(for_client)]
 pub mod client_sync_metadata;
+#[cfg(for_client)]
+pub mod clock;
+#[cfg(for_client)]
+pub mod counter;
+#[cfg(for_client)]
+pub mod id;
+#[cfg(for_client)]
+pub mod json_keys;
+#[cfg(for_client)]
+pub mod upload_failure;
 
+#[cfg(for_backend)]
+pub mod change_tracking;
+#[cfg(for_backend)]
+pub mod column_edit;
+#[cfg(for_backend)]
+pub mod counter_increment;
 #[cfg(for_backend)]
 pub mod serde_optional;
+#[cfg(for_backend)]
+pub mod tombstone_compaction;
 
 #[cfg(feature = "migration")]
 pub mod migration;
@@ -18,61 +36,262 @@ pub fn get_db_utc_now(conn: &mut diesel::PgConnection) -> crate::error::Result<D
     use diesel::sql_types::Timestamptz;
     sql::<Timestamptz>("SELECT CURRENT_TIMESTAMP")
         .get_result(conn)
-        .map_err(|e: diesel::result::Error| crate::error::Error::Unhandled {
-            message: "Failed to get database time".to_string(),
-            source: e.into(),
-        })
+        .map_err(|e| crate::error::Error::from_diesel("Failed to get database time", e))
+}
+
+#[cfg(all(for_backend, feature = "async"))]
+pub async fn get_db_utc_now_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> crate::error::Result<DateTime<Utc>> {
+    use diesel::dsl::sql;
+    use diesel::sql_types::Timestamptz;
+    use diesel_async::RunQueryDsl;
+    sql::<Timestamptz>("SELECT CURRENT_TIMESTAMP")
+        .get_result(conn)
+        .await
+        .map_err(|e| crate::error::Error::from_diesel("Failed to get database time", e))
+}
+
+/// Returns the fingerprint of a restriction (e.g. `recipient_id = $user_id`) with the value bound
+/// to it, for `DownloadTableResponse::restriction`.
+#[cfg(for_backend)]
+pub fn restriction_fingerprint<T: serde::Serialize>(
+    restriction: &str,
+    value: &T,
+) -> crate::error::Result<String> {
+    let value = serde_json::to_string(value).map_err(|e| crate::error::Error::Serialization {
+        message: format!(
+            "Failed to serialize the value of restriction '{}'",
+            restriction
+        ),
+        source: e.into(),
+    })?;
+
+    // FNV-1a, as the fingerprint stored by clients has to stay the same across processes and
+    // versions of the backend
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in restriction.bytes().chain([0]).chain(value.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Establishes a new connection to the database configured globally with
+/// `initialize_carburetor_global_config`.
+#[cfg(any(for_backend, for_client))]
+pub fn get_connection() -> crate::error::Result<DatabaseConnection> {
+    crate::config::get_global_carburetor().get_connection()
 }
 
 #[cfg(for_backend)]
-pub fn get_connection() -> crate::error::Result<diesel::PgConnection> {
-    use crate::{config::get_carburetor_config, error::Error};
+pub(crate) fn establish_connection(
+    config: &crate::config::CarburetorConfig,
+) -> crate::error::Result<diesel::PgConnection> {
+    use crate::error::Error;
     use diesel::{Connection, PgConnection};
     Ok(
-        PgConnection::establish(&get_carburetor_config().database_url.clone()).map_err(|e| {
-            Error::Unhandled {
-                message: "Connection to PostgresDB failed".to_string(),
-                source: e.into(),
-            }
+        PgConnection::establish(&config.database_url).map_err(|e| Error::Connection {
+            message: "Connection to PostgresDB failed".to_string(),
+            source: e.into(),
         })?,
     )
 }
 
+/// Returns an async connection to the database configured globally with
+/// `initialize_carburetor_global_config`.
+#[cfg(all(for_backend, feature = "async"))]
+pub async fn get_async_connection() -> crate::error::Result<AsyncDatabaseConnection> {
+    crate::config::get_global_carburetor()
+        .get_async_connection()
+        .await
+}
+
+#[cfg(all(for_backend, feature = "async"))]
+pub(crate) async fn establish_async_connection(
+    config: &crate::config::CarburetorConfig,
+) -> crate::error::Result<diesel_async::AsyncPgConnection> {
+    use crate::error::Error;
+    use diesel_async::{AsyncConnection, AsyncPgConnection};
+    AsyncPgConnection::establish(&config.database_url)
+        .await
+        .map_err(|e| Error::Connection {
+            message: "Connection to PostgresDB failed".to_string(),
+            source: e.into(),
+        })
+}
+
+/// Async connection returned by [`get_async_connection`]. It dereferences to the
+/// `AsyncPgConnection`, and a pooled connection goes back to its pool when dropped.
+#[cfg(all(for_backend, feature = "async"))]
+pub enum AsyncDatabaseConnection {
+    Established(diesel_async::AsyncPgConnection),
+    #[cfg(feature = "bb8")]
+    Pooled(
+        diesel_async::pooled_connection::bb8::PooledConnection<
+            'static,
+            diesel_async::AsyncPgConnection,
+        >,
+    ),
+}
+
+#[cfg(all(for_backend, feature = "async"))]
+impl std::ops::Deref for AsyncDatabaseConnection {
+    type Target = diesel_async::AsyncPgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Established(conn) => conn,
+            #[cfg(feature = "bb8")]
+            Self::Pooled(conn) => conn,
+        }
+    }
+}
+
+#[cfg(all(for_backend, feature = "async"))]
+impl std::ops::DerefMut for AsyncDatabaseConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Established(conn) => conn,
+            #[cfg(feature = "bb8")]
+            Self::Pooled(conn) => conn,
+        }
+    }
+}
+
+#[cfg(all(for_backend, feature = "bb8"))]
+pub type AsyncConnectionPool =
+    diesel_async::pooled_connection::bb8::Pool<diesel_async::AsyncPgConnection>;
+
+/// Builds a connection pool suitable for `CarburetorConfig::async_connection_pool`.
+#[cfg(all(for_backend, feature = "bb8"))]
+pub async fn build_async_connection_pool(
+    database_url: &str,
+) -> crate::error::Result<AsyncConnectionPool> {
+    use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+
+    diesel_async::pooled_connection::bb8::Pool::builder()
+        .build(AsyncDieselConnectionManager::new(database_url))
+        .await
+        .map_err(|e| crate::error::Error::Connection {
+            message: "Failed to build connection pool".to_string(),
+            source: e.into(),
+        })
+}
+
 #[cfg(for_client)]
-pub fn get_connection() -> crate::error::Result<diesel::SqliteConnection> {
-    use crate::{config::get_carburetor_config, error::Error};
-    use diesel::{Connection, SqliteConnection, connection::SimpleConnection};
+pub(crate) fn establish_connection(
+    config: &crate::config::CarburetorConfig,
+) -> crate::error::Result<diesel::SqliteConnection> {
+    use crate::error::Error;
+    use diesel::{Connection, SqliteConnection};
     let mut conn =
-        SqliteConnection::establish(&get_carburetor_config().database_path).map_err(|e| {
-            Error::Unhandled {
-                message: "Connection to Sqlite failed".to_string(),
-                source: e.into(),
-            }
+        SqliteConnection::establish(&config.database_path).map_err(|e| Error::Connection {
+            message: "Connection to Sqlite failed".to_string(),
+            source: e.into(),
         })?;
 
+    configure_sqlite_connection(&mut conn)
+        .map_err(|e| Error::from_diesel("Updating config for Sqlite failed", e))?;
+
+    Ok(conn)
+}
+
+#[cfg(for_client)]
+fn configure_sqlite_connection(conn: &mut diesel::SqliteConnection) -> diesel::QueryResult<()> {
+    use diesel::connection::SimpleConnection;
+
     // Default Sqlite lock fails immediately, this config ensure that connection tries to acquire
     // lock for 5 seconds before failing
     conn.batch_execute("PRAGMA busy_timeout = 5000;")
-        .map_err(|e| Error::Unhandled {
-            message: "Updating config for Sqlite failed".to_string(),
+}
+
+/// Connection type of the database targeted by the current build.
+#[cfg(for_backend)]
+pub type DatabaseConnection = diesel::PgConnection;
+
+#[cfg(for_client)]
+pub type DatabaseConnection = diesel::SqliteConnection;
+
+#[cfg(feature = "r2d2")]
+pub type ConnectionPool = diesel::r2d2::Pool<diesel::r2d2::ConnectionManager<DatabaseConnection>>;
+
+/// Builds a connection pool suitable for `CarburetorConfig::connection_pool`.
+///
+/// On the client, every pooled connection gets the same configuration as the ones returned by
+/// [`get_connection`].
+#[cfg(feature = "r2d2")]
+pub fn build_connection_pool(database_url: &str) -> crate::error::Result<ConnectionPool> {
+    use diesel::r2d2::{ConnectionManager, Pool};
+
+    let builder = Pool::builder();
+
+    #[cfg(for_client)]
+    let builder = builder.connection_customizer(Box::new(SqliteConnectionCustomizer));
+
+    builder
+        .build(ConnectionManager::new(database_url))
+        .map_err(|e| crate::error::Error::Connection {
+            message: "Failed to build connection pool".to_string(),
             source: e.into(),
-        })?;
+        })
+}
 
-    Ok(conn)
+#[cfg(all(feature = "r2d2", for_client))]
+#[derive(Debug)]
+struct SqliteConnectionCustomizer;
+
+#[cfg(all(feature = "r2d2", for_client))]
+impl diesel::r2d2::CustomizeConnection<diesel::SqliteConnection, diesel::r2d2::Error>
+    for SqliteConnectionCustomizer
+{
+    fn on_acquire(&self, conn: &mut diesel::SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        configure_sqlite_connection(conn).map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Runs `f` with a connection to the database configured globally with
+/// `initialize_carburetor_global_config`. Generated functions use this to back their variants that
+/// take neither an explicit connection nor a `Carburetor` handle.
+#[cfg(any(for_backend, for_client))]
+pub fn with_connection<T>(
+    f: impl FnOnce(&mut DatabaseConnection) -> crate::error::Result<T>,
+) -> crate::error::Result<T> {
+    crate::config::get_global_carburetor().with_connection(f)
 }
 
 pub fn get_utc_now() -> DateTime<Utc> {
     Utc::now()
 }
 
+/// Returns an ID made of `prefix`, a random part and the current Unix second. This is the strategy
+/// used by `#[id]` when no other is given.
 pub fn generate_id(prefix: String) -> String {
-    use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    // Tells apart the IDs generated within the same nanosecond by this process
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
 
     let now = get_utc_now();
-    let mut hasher = DefaultHasher::new();
+    let mut hasher = random_hasher();
     now.timestamp_nanos_opt().hash(&mut hasher);
-    let hash = hasher.finish();
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+
+    format!("{}-{:016x}-{}", prefix, hasher.finish(), now.timestamp())
+}
+
+/// Returns a hasher seeded with random keys, so that the same input hashes differently on every
+/// device and in every process.
+fn random_hasher() -> std::hash::DefaultHasher {
+    use std::hash::BuildHasher;
+
+    std::hash::RandomState::new().build_hasher()
+}
+
+#[cfg(for_client)]
+pub(crate) fn random_u64() -> u64 {
+    use std::hash::Hasher;
 
-    format!("{}-{:06x}-{}", prefix, hash & 0xFFFFFF, now.timestamp())
+    random_hasher().finish()
 }
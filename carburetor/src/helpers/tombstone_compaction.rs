@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{Error, Result};
+
+diesel::table! {
+    carburetor_tombstone_compactions (table_name) {
+        table_name -> Text,
+        compacted_before -> Timestamptz,
+    }
+}
+
+const UPSERT_COMPACTION_SQL: &str = "INSERT INTO carburetor_tombstone_compactions (table_name, compacted_before) \
+     VALUES ($1, $2) \
+     ON CONFLICT (table_name) DO UPDATE SET compacted_before = \
+     GREATEST(carburetor_tombstone_compactions.compacted_before, EXCLUDED.compacted_before)";
+
+/// Returns the time before which deleted rows are old enough to be compacted.
+pub fn compacted_before(
+    now: DateTime<Utc>,
+    older_than: std::time::Duration,
+) -> Result<DateTime<Utc>> {
+    chrono::Duration::from_std(older_than)
+        .ok()
+        .and_then(|older_than| now.checked_sub_signed(older_than))
+        .ok_or_else(|| Error::Unhandled {
+            message: "Retention window is out of range".to_string(),
+            source: anyhow::anyhow!("{:?} cannot be subtracted from {}", older_than, now),
+        })
+}
+
+/// Records that deleted rows of `table_name` last synced before `compacted_before` were
+/// physically removed. The recorded time never moves backward.
+pub fn record_compaction(
+    conn: &mut diesel::PgConnection,
+    table_name: &str,
+    compacted_before: DateTime<Utc>,
+) -> Result<()> {
+    use diesel::RunQueryDsl;
+
+    diesel::sql_query(UPSERT_COMPACTION_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Timestamptz, _>(compacted_before)
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!("Failed to record compaction for table '{}'", table_name),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+pub async fn record_compaction_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+    table_name: &str,
+    compacted_before: DateTime<Utc>,
+) -> Result<()> {
+    use diesel_async::RunQueryDsl;
+
+    diesel::sql_query(UPSERT_COMPACTION_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Timestamptz, _>(compacted_before)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            Error::from_diesel(
+                format!("Failed to record compaction for table '{}'", table_name),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Returns, for each compacted table, the offset below which a client may have missed deletions.
+pub fn retrieve_compactions(
+    conn: &mut diesel::PgConnection,
+) -> Result<HashMap<String, DateTime<Utc>>> {
+    use diesel::RunQueryDsl;
+
+    let compactions = carburetor_tombstone_compactions::table
+        .load::<(String, DateTime<Utc>)>(conn)
+        .map_err(|e| Error::from_diesel("Failed to retrieve tombstone compactions", e))?
+        .into_iter()
+        .collect();
+
+    Ok(compactions)
+}
+
+#[cfg(feature = "async")]
+pub async fn retrieve_compactions_async(
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> Result<HashMap<String, DateTime<Utc>>> {
+    use diesel_async::RunQueryDsl;
+
+    let compactions = carburetor_tombstone_compactions::table
+        .load::<(String, DateTime<Utc>)>(conn)
+        .await
+        .map_err(|e| Error::from_diesel("Failed to retrieve tombstone compactions", e))?
+        .into_iter()
+        .collect();
+
+    Ok(compactions)
+}
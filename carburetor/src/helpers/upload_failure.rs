@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::{
+    error::{Error, Result},
+    models::UploadTableResponseErrorType,
+};
+
+diesel::table! {
+    carburetor_upload_failures (table_name, id) {
+        table_name -> Text,
+        id -> Text,
+        code -> Text,
+        attempts -> Integer,
+        last_attempt_at -> TimestamptzSqlite,
+    }
+}
+
+pub const CREATE_UPLOAD_FAILURES_SQL: &str = "CREATE TABLE carburetor_upload_failures (\
+     table_name TEXT NOT NULL, \
+     id TEXT NOT NULL, \
+     code TEXT NOT NULL, \
+     attempts INTEGER NOT NULL, \
+     last_attempt_at TIMESTAMPTZ NOT NULL, \
+     PRIMARY KEY (table_name, id))";
+
+const UPSERT_FAILURE_SQL: &str = "INSERT INTO carburetor_upload_failures \
+     (table_name, id, code, attempts, last_attempt_at) VALUES (?, ?, ?, 1, ?) \
+     ON CONFLICT (table_name, id) DO UPDATE SET \
+     code = excluded.code, \
+     attempts = carburetor_upload_failures.attempts + 1, \
+     last_attempt_at = excluded.last_attempt_at";
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = carburetor_upload_failures)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FullCarburetorUploadFailure {
+    pub table_name: String,
+    pub id: String,
+    pub code: String,
+    pub attempts: i32,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+/// A local row that the backend refused during upload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadFailure {
+    pub table_name: String,
+    pub id: String,
+    pub code: UploadTableResponseErrorType,
+    pub attempts: u32,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+impl TryFrom<FullCarburetorUploadFailure> for UploadFailure {
+    type Error = Error;
+
+    fn try_from(value: FullCarburetorUploadFailure) -> Result<Self> {
+        let code = serde_json::from_value(serde_json::Value::String(value.code)).map_err(|e| {
+            Error::Serialization {
+                message: format!(
+                    "Failed to read upload failure of '{}' in table '{}'",
+                    value.id, value.table_name
+                ),
+                source: e.into(),
+            }
+        })?;
+
+        Ok(Self {
+            table_name: value.table_name,
+            id: value.id,
+            code,
+            attempts: value.attempts.max(0) as u32,
+            last_attempt_at: value.last_attempt_at,
+        })
+    }
+}
+
+/// Decides when a row that failed with `UploadTableResponseErrorType::Unknown` is uploaded again.
+///
+/// The wait doubles after every failed attempt, starting from `initial_backoff` and capped at
+/// `max_backoff`. Once `max_attempts` is reached, the row is held back until it is retried or
+/// discarded explicitly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadRetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: std::time::Duration,
+    pub max_backoff: std::time::Duration,
+}
+
+impl Default for UploadRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: std::time::Duration::from_secs(30),
+            max_backoff: std::time::Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl UploadRetryPolicy {
+    /// Returns whether the row of `failure` should be left out of the upload at `now`.
+    pub fn holds_back(&self, failure: &UploadFailure, now: DateTime<Utc>) -> bool {
+        match failure.code {
+            UploadTableResponseErrorType::Unknown => {
+                failure.attempts >= self.max_attempts || now < self.next_attempt_at(failure)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the earliest time at which the row of `failure` is uploaded again.
+    pub fn next_attempt_at(&self, failure: &UploadFailure) -> DateTime<Utc> {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(failure.attempts.saturating_sub(1)))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+
+        chrono::Duration::from_std(backoff)
+            .ok()
+            .and_then(|backoff| failure.last_attempt_at.checked_add_signed(backoff))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// Returns the retry policy of the database configured globally with
+/// `initialize_carburetor_global_config`.
+pub fn global_upload_retry_policy() -> UploadRetryPolicy {
+    crate::config::get_global_carburetor()
+        .config()
+        .upload_retry_policy
+        .clone()
+}
+
+/// Counts one more failed upload attempt of the row `id` of `table_name`.
+pub fn record_failure(
+    conn: &mut diesel::SqliteConnection,
+    table_name: &str,
+    id: &str,
+    code: &UploadTableResponseErrorType,
+    attempted_at: DateTime<Utc>,
+) -> Result<()> {
+    let code = serde_json::to_value(code).map_err(|e| Error::Serialization {
+        message: format!("Failed to serialize upload error code {:?}", code),
+        source: e.into(),
+    })?;
+
+    diesel::sql_query(UPSERT_FAILURE_SQL)
+        .bind::<diesel::sql_types::Text, _>(table_name)
+        .bind::<diesel::sql_types::Text, _>(id)
+        .bind::<diesel::sql_types::Text, _>(code.as_str().unwrap_or_default())
+        .bind::<diesel::sql_types::TimestamptzSqlite, _>(attempted_at)
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to record upload failure of '{}' in table '{}'",
+                    id, table_name
+                ),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+pub fn delete_failure(
+    conn: &mut diesel::SqliteConnection,
+    table_name: &str,
+    id: &str,
+) -> Result<()> {
+    diesel::delete(carburetor_upload_failures::table.find((table_name, id)))
+        .execute(conn)
+        .map_err(|e| {
+            Error::from_diesel(
+                format!(
+                    "Failed to delete upload failure of '{}' in table '{}'",
+                    id, table_name
+                ),
+                e,
+            )
+        })?;
+
+    Ok(())
+}
+
+pub fn retrieve_failures(
+    conn: &mut diesel::SqliteConnection,
+    table_names: &[&str],
+) -> Result<Vec<UploadFailure>> {
+    carburetor_upload_failures::table
+        .filter(carburetor_upload_failures::table_name.eq_any(table_names))
+        .order((
+            carburetor_upload_failures::table_name,
+            carburetor_upload_failures::id,
+        ))
+        .select(FullCarburetorUploadFailure::as_select())
+        .load(conn)
+        .map_err(|e| Error::from_diesel("Failed to retrieve upload failures", e))?
+        .into_iter()
+        .map(UploadFailure::try_from)
+        .collect()
+}
+
+/// Error returned when `failure` is given to a sync group that does not sync its table.
+pub fn unknown_table_error(failure: &UploadFailure) -> Error {
+    Error::Unhandled {
+        message: "Upload failure does not belong to this sync group".to_string(),
+        source: anyhow::anyhow!("table '{}' is not synced by the group", failure.table_name),
+    }
+}
+
+/// Returns the IDs of the rows of `table_name` that `policy` leaves out of the upload at `now`.
+pub fn retrieve_held_back_ids(
+    conn: &mut diesel::SqliteConnection,
+    table_name: &str,
+    policy: &UploadRetryPolicy,
+    now: DateTime<Utc>,
+) -> Result<HashSet<String>> {
+    Ok(retrieve_failures(conn, &[table_name])?
+        .into_iter()
+        .filter(|x| policy.holds_back(x, now))
+        .map(|x| x.id)
+        .collect())
+}
@@ -1,13 +1,16 @@
+#[cfg(for_client)]
+pub mod client;
 pub mod config;
 pub mod error;
 pub mod helpers;
 pub mod models;
+pub mod types;
 
 // Re-export chrono so that user can use Chrono type for model without adding to their dependencies
 pub mod chrono {
     use chrono::{DateTime, Utc};
 
-    pub use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+    pub use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
     pub type DateTimeUtc = DateTime<Utc>;
 }
 
@@ -17,6 +20,25 @@ pub mod serde_json {
     pub use serde_json::{Value, from_str, from_value, to_string, to_value};
 }
 
+// Re-export uuid so that user can use Uuid type for model without adding to their dependencies
+#[cfg(feature = "uuid")]
+pub use uuid;
+
+// Re-export bigdecimal and ipnetwork for the same reason, for `Numeric` and `Inet` columns
+#[cfg(feature = "numeric")]
+pub use bigdecimal;
+#[cfg(feature = "network-address")]
+pub use ipnetwork;
+
+// Re-export diesel_async so that the generated async functions do not require user to add it to their
+// dependencies
+#[cfg(feature = "async")]
+pub use diesel_async;
+
+// Re-export axum for the generated routers, for the same reason
+#[cfg(feature = "axum")]
+pub use axum;
+
 pub use prelude::*;
 pub mod prelude {
     pub use carburetor_macro::*;
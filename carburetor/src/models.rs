@@ -2,9 +2,51 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DownloadTableResponse<T> {
+pub struct DownloadTableResponse<T, Id = String> {
     pub cutoff_at: DateTime<Utc>,
     pub data: Vec<DownloadTableResponseData<T>>,
+    /// Position of the last row in `data`, to be sent back as the cursor of the next page.
+    #[serde(default)]
+    pub cursor: Option<DownloadCursor<Id>>,
+    /// Whether rows up to `cutoff_at` were left out because of the page limit.
+    #[serde(default)]
+    pub has_more: bool,
+    /// The offset of the request predates a tombstone compaction, so deletions may have been
+    /// missed, or was downloaded for another value of the restriction. `data` is a download from
+    /// the start, and rows the client has not modified must be dropped before storing it, see
+    /// `reset_before`.
+    #[serde(default)]
+    pub reset: bool,
+    /// With `reset`, the time of the compaction that caused it. Rows last synced from then on
+    /// cannot have missed a deletion, so only the older ones are dropped, and `data` includes the
+    /// remaining deleted rows. Every row the client has not modified is dropped when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_before: Option<DateTime<Utc>>,
+    /// Position of the table with `change_tracking = "xid"`, to be sent back along with the
+    /// offset. Rows committed after this position are downloaded next time, even when their
+    /// `last_synced_at` is before `cutoff_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xmin: Option<i64>,
+    /// Fingerprint of the value bound to the restriction of the table in its group, e.g. of the
+    /// `$user_id` of `recipient_id = $user_id`, to be sent back along with the offset. A request
+    /// with another fingerprint is answered with a reset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restriction: Option<String>,
+}
+
+/// Resumable position in a paginated table download. Rows are downloaded in
+/// `(last_synced_at, id)` order, so the cursor stays stable when several rows share the same
+/// `last_synced_at`.
+///
+/// `Id` is the type of the `#[id]` column of the table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadCursor<Id = String> {
+    pub last_synced_at: DateTime<Utc>,
+    pub id: Id,
+    /// Position of the first page with `change_tracking = "xid"`, which becomes the position of
+    /// the table once every page is downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xmin: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,15 +56,21 @@ pub enum DownloadTableResponseData<T> {
     // UpdatePartial(U)
 }
 
+/// `Id` is the type of the `#[id]` column of the table.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UploadTableResponseData {
-    pub id: String,
+pub struct UploadTableResponseData<Id = String> {
+    pub id: Id,
     pub last_synced_at: DateTime<Utc>,
+    /// Columns whose change was not applied, because the backend has a newer edit of them. The
+    /// client takes the value of the backend on the next download.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected_columns: Vec<String>,
 }
 
+/// `Id` is the type of the `#[id]` column of the table.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UploadTableResponseError {
-    pub id: String,
+pub struct UploadTableResponseError<Id = String> {
+    pub id: Id,
     pub code: UploadTableResponseErrorType,
 }
 
@@ -33,4 +81,32 @@ pub enum UploadTableResponseErrorType {
     RecordNotFound,
     RecordAlreadyExists,
     InsufficientPermission,
+    /// The row was not applied because another row failed in an atomic group upload. The row that
+    /// caused the abort carries its own error code.
+    Aborted,
+}
+
+/// Upload of a `#[merge(json_keys)]` column, with only the top-level keys changed on the client.
+/// The backend applies it over the stored object, so keys changed by other clients are kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonKeysPatch {
+    /// Keys added or changed, with their new value
+    pub set: serde_json::Map<String, serde_json::Value>,
+    /// Keys removed
+    pub remove: Vec<String>,
+}
+
+/// Upload of a `#[crdt(counter)]` column, with the increments made on the client and not yet
+/// accepted by the backend. An increment is identified by the device that made it and the time it
+/// was made, so that the backend adds it once even when the same upload is received again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CounterIncrements {
+    pub device_id: String,
+    pub increments: Vec<CounterIncrementDelta>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CounterIncrementDelta {
+    pub at: DateTime<Utc>,
+    pub delta: i64,
 }
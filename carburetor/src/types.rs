@@ -0,0 +1,250 @@
+//! Model types of the columns that have no ready-made Rust type usable on both targets.
+//!
+//! Postgres stores them in their native type. SQLite has no counterpart, so the client keeps
+//! `Interval` and `Money` in a `BIGINT` column, which is also how they travel between the two, and
+//! arrays in a `JSON` column.
+
+#![cfg(any(for_backend, for_client))]
+
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(for_backend)]
+use diesel::pg::{Pg, PgValue};
+#[cfg(for_client)]
+use diesel::sqlite::{Sqlite, SqliteValue};
+
+/// Model type of `Interval` columns.
+///
+/// Intervals with a month part cannot be represented and fail to load, as they do not have a fixed
+/// length. The value is serialized as a number of microseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, AsExpression, FromSqlRow)]
+#[cfg_attr(for_backend, diesel(sql_type = diesel::sql_types::Interval))]
+#[cfg_attr(for_client, diesel(sql_type = diesel::sql_types::BigInt))]
+pub struct Interval(pub chrono::TimeDelta);
+
+impl Interval {
+    pub fn from_microseconds(microseconds: i64) -> Self {
+        Self(chrono::TimeDelta::microseconds(microseconds))
+    }
+
+    /// Returns the length of the interval in microseconds, or `None` on overflow.
+    pub fn num_microseconds(&self) -> Option<i64> {
+        self.0.num_microseconds()
+    }
+}
+
+impl From<chrono::TimeDelta> for Interval {
+    fn from(value: chrono::TimeDelta) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let microseconds = self.num_microseconds().ok_or_else(|| {
+            serde::ser::Error::custom("interval is too long to be counted in microseconds")
+        })?;
+        serializer.serialize_i64(microseconds)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i64::deserialize(deserializer).map(Self::from_microseconds)
+    }
+}
+
+#[cfg(for_backend)]
+impl ToSql<diesel::sql_types::Interval, Pg> for Interval {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <chrono::TimeDelta as ToSql<diesel::sql_types::Interval, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+#[cfg(for_backend)]
+impl FromSql<diesel::sql_types::Interval, Pg> for Interval {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        <chrono::TimeDelta as FromSql<diesel::sql_types::Interval, Pg>>::from_sql(bytes).map(Self)
+    }
+}
+
+#[cfg(for_client)]
+impl ToSql<diesel::sql_types::BigInt, Sqlite> for Interval {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        let microseconds = self
+            .num_microseconds()
+            .ok_or("interval is too long to be counted in microseconds")?;
+        out.set_value(microseconds);
+        Ok(serialize::IsNull::No)
+    }
+}
+
+#[cfg(for_client)]
+impl FromSql<diesel::sql_types::BigInt, Sqlite> for Interval {
+    fn from_sql(bytes: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        <i64 as FromSql<diesel::sql_types::BigInt, Sqlite>>::from_sql(bytes)
+            .map(Self::from_microseconds)
+    }
+}
+
+/// Model type of `Money` columns, as an amount in the smallest unit of the currency (e.g. cents).
+///
+/// Postgres formats `MONEY` with the `lc_monetary` setting of the database, which is not known to
+/// the client, so the amount is kept and serialized as a plain number.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+)]
+#[serde(transparent)]
+#[cfg_attr(for_backend, diesel(sql_type = diesel::sql_types::Money))]
+#[cfg_attr(for_client, diesel(sql_type = diesel::sql_types::BigInt))]
+pub struct Money(pub i64);
+
+#[cfg(for_backend)]
+impl ToSql<diesel::sql_types::Money, Pg> for Money {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <i64 as ToSql<diesel::sql_types::BigInt, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+#[cfg(for_backend)]
+impl FromSql<diesel::sql_types::Money, Pg> for Money {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        <diesel::data_types::PgMoney as FromSql<diesel::sql_types::Money, Pg>>::from_sql(bytes)
+            .map(|x| Self(x.0))
+    }
+}
+
+#[cfg(for_client)]
+impl ToSql<diesel::sql_types::BigInt, Sqlite> for Money {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        <i64 as ToSql<diesel::sql_types::BigInt, Sqlite>>::to_sql(&self.0, out)
+    }
+}
+
+#[cfg(for_client)]
+impl FromSql<diesel::sql_types::BigInt, Sqlite> for Money {
+    fn from_sql(bytes: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        <i64 as FromSql<diesel::sql_types::BigInt, Sqlite>>::from_sql(bytes).map(Self)
+    }
+}
+
+/// Client model type of `Array<T>` columns, which are kept in a JSON column as SQLite has no
+/// arrays. The backend uses `Vec<T>`, and both serialize to the same JSON array.
+#[cfg(for_client)]
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow,
+)]
+#[serde(transparent)]
+#[diesel(sql_type = diesel::sql_types::Json)]
+pub struct JsonArray<T>(pub Vec<T>);
+
+#[cfg(for_client)]
+impl<T> std::ops::Deref for JsonArray<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(for_client)]
+impl<T> std::ops::DerefMut for JsonArray<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(for_client)]
+impl<T> From<Vec<T>> for JsonArray<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(for_client)]
+impl<T> FromIterator<T> for JsonArray<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[cfg(for_client)]
+impl<T: Serialize + std::fmt::Debug> ToSql<diesel::sql_types::Json, Sqlite> for JsonArray<T> {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(serde_json::to_string(&self.0)?);
+        Ok(serialize::IsNull::No)
+    }
+}
+
+#[cfg(for_client)]
+impl<T: serde::de::DeserializeOwned> FromSql<diesel::sql_types::Json, Sqlite> for JsonArray<T> {
+    fn from_sql(bytes: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        let value =
+            <serde_json::Value as FromSql<diesel::sql_types::Json, Sqlite>>::from_sql(bytes)?;
+        Ok(Self(serde_json::from_value(value)?))
+    }
+}
+
+/// Rust enum stored in an `Enum<T>` column, as the text of its variant.
+///
+/// Implemented with the `#[sync_enum]` attribute, which also implements the conversions to the
+/// database and to serde.
+pub trait SyncEnum: Sized + 'static {
+    /// Text of every variant, as stored in the database.
+    const VARIANTS: &'static [&'static str];
+
+    fn as_str(&self) -> &'static str;
+
+    fn from_str(value: &str) -> Result<Self, UnknownVariant>;
+}
+
+/// A value that is not one of the variants of the enum of an `Enum<T>` column, usually written by
+/// a newer version of the app that added the variant.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown variant `{value}` of enum `{enum_name}`")]
+pub struct UnknownVariant {
+    pub enum_name: &'static str,
+    pub value: String,
+}
+
+impl UnknownVariant {
+    pub fn new<T: SyncEnum>(value: impl Into<String>) -> Self {
+        Self {
+            enum_name: std::any::type_name::<T>(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Serializes a [`SyncEnum`] as the text of its variant, for the `Serialize` implementation added
+/// by `#[sync_enum]`.
+pub fn serialize_sync_enum<T: SyncEnum, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(value.as_str())
+}
+
+/// Deserializes a [`SyncEnum`] from the text of its variant, for the `Deserialize` implementation
+/// added by `#[sync_enum]`.
+pub fn deserialize_sync_enum<'de, T: SyncEnum, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value).map_err(serde::de::Error::custom)
+}
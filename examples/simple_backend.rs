@@ -29,6 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     first_name: None,
                     joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
                     is_deleted: false,
+                    carburetor_edited_at: Default::default(),
                 }),
                 all_clients::UploadRequestUser::Insert(all_clients::UploadInsertUser {
                     id: "USER2".to_string(),
@@ -36,6 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     first_name: None,
                     joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
                     is_deleted: false,
+                    carburetor_edited_at: Default::default(),
                 }),
                 all_clients::UploadRequestUser::Insert(all_clients::UploadInsertUser {
                     id: "USER3".to_string(),
@@ -43,6 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     first_name: None,
                     joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
                     is_deleted: true,
+                    carburetor_edited_at: Default::default(),
                 }),
             ],
         }
@@ -62,6 +65,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     first_name: Some(Some("John".to_string())),
                     joined_on: None,
                     is_deleted: Some(true),
+                    carburetor_edited_at: Default::default(),
                 },
             )],
         }
@@ -72,7 +76,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
     let _ = dbg!(all_clients::process_download_request(Some(
         all_clients::DownloadRequest {
-            user_offset: Some(res.user.cutoff_at)
+            user_offset: Some(res.user.cutoff_at),
+            ..Default::default()
         }
     )));
     Ok(())
@@ -22,7 +22,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::env::var("DATABASE_PATH").unwrap_or_else(|_| "./default.db".to_string());
     let mut connection =
         SqliteConnection::establish(&database_path).expect("Error connecting to database");
-    initialize_carburetor_global_config(CarburetorGlobalConfig { database_path });
+    initialize_carburetor_global_config(CarburetorGlobalConfig {
+        database_path,
+        ..Default::default()
+    });
 
     schema::run_migrations(&mut connection)?;
 
@@ -43,6 +46,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     is_deleted: false,
                 },
             )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
         },
     })?;
     dbg!(
@@ -68,6 +77,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     is_deleted: false,
                 },
             )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
         },
     })?;
     dbg!(
@@ -90,6 +105,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     is_deleted: false,
                 },
             )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
         },
     })?;
     dbg!(
@@ -121,7 +142,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }),
                     ..Default::default()
                 }
-                .into(),
+                .try_into()?,
             ),
         })
         .execute(&mut connection)?;
@@ -138,6 +159,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     is_deleted: false,
                 },
             )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
         },
     })?;
     dbg!(
@@ -167,7 +194,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }),
                     ..Default::default()
                 }
-                .into(),
+                .try_into()?,
             ),
         })
         .execute(&mut connection)?;
@@ -184,6 +211,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     is_deleted: false,
                 },
             )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
         },
     })?;
     dbg!(
@@ -218,10 +251,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(UploadTableResponseData {
                 id: inserted_record.id.clone(),
                 last_synced_at: get_utc_now(),
+                rejected_columns: Vec::new(),
             }),
             Ok(UploadTableResponseData {
                 id: "FromBackend".to_string(),
                 last_synced_at: get_utc_now(),
+                rejected_columns: Vec::new(),
             }),
         ],
     };
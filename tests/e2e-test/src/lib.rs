@@ -108,6 +108,7 @@ impl TestClientDatabase {
         let _ = std::panic::catch_unwind(|| {
             initialize_carburetor_global_config(CarburetorGlobalConfig {
                 database_path: database_path.clone(),
+                ..Default::default()
             });
         });
 
@@ -116,6 +117,14 @@ impl TestClientDatabase {
         }
     }
 
+    pub fn database_path(&self) -> String {
+        self._temp_dir
+            .path()
+            .join("test.db")
+            .to_string_lossy()
+            .to_string()
+    }
+
     pub fn get_connection(&self) -> SqliteConnection {
         get_connection().unwrap()
     }
@@ -0,0 +1,68 @@
+use carburetor::chrono::NaiveDate;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::user_only;
+
+#[tokio::test]
+async fn test_local_changes_use_device_clock() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    // The backend clock is an hour ahead of the device clock
+    let backend_now = carburetor::helpers::get_utc_now() + std::time::Duration::from_secs(60 * 60);
+    user_only::store_download_response(user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: backend_now,
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                user_only::DownloadUpdateUser {
+                    id: "user-skewed-1".to_string(),
+                    username: "downloaded".to_string(),
+                    first_name: None,
+                    joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    created_at: backend_now,
+                    nickname: None,
+                    priority: 0,
+                    preferences: None,
+                    last_synced_at: backend_now,
+                    is_deleted: false,
+                },
+            )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    let before = carburetor::helpers::get_utc_now();
+    user_only::update_user(user_only::UpdateUser {
+        id: "user-skewed-1".to_string(),
+        username: Some("edited".to_string()),
+        first_name: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+        joined_on: None,
+    })
+    .unwrap();
+    let after = carburetor::helpers::get_utc_now();
+
+    let updated: user_only::FullUser = user_only::users::table
+        .find("user-skewed-1")
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    let dirty_at = updated.column_sync_metadata["username"]["dirty_at"]
+        .as_str()
+        .unwrap()
+        .parse::<carburetor::chrono::DateTimeUtc>()
+        .unwrap();
+    assert!(
+        before <= dirty_at && dirty_at <= after,
+        "update should be recorded at the device time"
+    );
+    assert!(dirty_at < backend_now);
+}
@@ -0,0 +1,367 @@
+use carburetor::serde_json::{from_str, to_string, to_value};
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::product_only;
+use tarpc::context::current as ctx;
+
+fn get_product(conn: &mut diesel::SqliteConnection, id: &str) -> product_only::FullProduct {
+    product_only::products::table
+        .find(id)
+        .select(product_only::FullProduct::as_select())
+        .first(conn)
+        .unwrap()
+}
+
+/// Stores a downloaded row as if it was synced from the backend, and returns its ID.
+fn store_synced_product(name: &str, last_synced_at: &str) -> String {
+    let response: product_only::DownloadResponse = from_str(&format!(
+        r#"{{"product": {{
+            "cutoff_at": "{last_synced_at}",
+            "data": [{{"Update": {{
+                "id": "product-1",
+                "name": "{name}",
+                "stock": 0,
+                "views": 0,
+                "last_synced_at": "{last_synced_at}",
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": false
+        }}}}"#
+    ))
+    .unwrap();
+    product_only::store_download_response(response).unwrap();
+    "product-1".to_string()
+}
+
+fn update_name(id: &str, name: &str) {
+    product_only::update_product(product_only::UpdateProduct {
+        id: id.to_string(),
+        name: Some(name.to_string()),
+        stock: None,
+        views: None,
+    })
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_upload_sends_column_edit_times() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+    let id = store_synced_product("Lamp", "2025-01-01T00:00:00Z");
+
+    update_name(&id, "Desk lamp");
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+
+    let metadata = get_product(&mut conn, &id).column_sync_metadata;
+    let request = to_value(&upload_request).unwrap();
+    let update = &request["product"][0]["Update"];
+    assert_eq!(update["name"], "Desk lamp");
+    assert_eq!(
+        update["carburetor_edited_at"]["name"],
+        metadata["name"]["dirty_at"]
+    );
+    // Increments are added up on the backend, so they are never rejected
+    assert!(update["carburetor_edited_at"]["stock"].is_null());
+}
+
+#[tokio::test]
+async fn test_upload_insert_sends_column_edit_times() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 0,
+        views: 0,
+    })
+    .unwrap();
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+
+    let metadata = get_product(&mut conn, &inserted.id).column_sync_metadata;
+    let request = to_value(&upload_request).unwrap();
+    let insert = &request["product"][0]["Insert"];
+    assert_eq!(
+        insert["carburetor_edited_at"]["name"],
+        metadata[".insert_time"]
+    );
+    assert!(insert["carburetor_edited_at"]["stock"].is_null());
+
+    // A column updated before the insert is uploaded carries the time of the update
+    update_name(&inserted.id, "Desk lamp");
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let metadata = get_product(&mut conn, &inserted.id).column_sync_metadata;
+    let request = to_value(&upload_request).unwrap();
+    assert_eq!(
+        request["product"][0]["Insert"]["carburetor_edited_at"]["name"],
+        metadata["name"]["dirty_at"]
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_column_takes_backend_value() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+    let id = store_synced_product("Lamp", "2025-01-01T00:00:00Z");
+
+    update_name(&id, "Desk lamp");
+    let (cutoff, _) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(&format!(
+        r#"{{"product": [{{"Ok": {{
+            "id": "{id}",
+            "last_synced_at": "2025-01-03T00:00:00Z",
+            "rejected_columns": ["name"]
+        }}}}]}}"#
+    ))
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.product.is_empty());
+
+    // Another device renamed it later than this one
+    store_synced_product("Floor lamp", "2025-01-03T00:00:00Z");
+    assert_eq!(get_product(&mut conn, &id).name, "Floor lamp");
+}
+
+#[tokio::test]
+async fn test_backend_keeps_newer_column_edit() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let _db = get_clean_test_client_db();
+
+    let inserted = product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 0,
+        views: 0,
+    })
+    .unwrap();
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let upload_name = |name: &str, edited_at: &str| {
+        format!(
+            r#"{{"product": [{{"Update": {{
+                "id": "{}",
+                "name": "{name}",
+                "stock": null,
+                "views": null,
+                "carburetor_edited_at": {{"name": "{edited_at}"}}
+            }}}}]}}"#,
+            inserted.id
+        )
+    };
+
+    let response = backend
+        .process_product_only_upload_request(
+            ctx(),
+            upload_name("Desk lamp", "2025-01-02T00:00:00Z"),
+        )
+        .await
+        .unwrap();
+    let response = to_value(from_str::<product_only::UploadResponse>(&response).unwrap()).unwrap();
+    assert!(response["product"][0]["Ok"]["rejected_columns"].is_null());
+    let accepted_last_synced_at = response["product"][0]["Ok"]["last_synced_at"].clone();
+
+    // Edited earlier on a device that was offline, but uploaded later
+    let response = backend
+        .process_product_only_upload_request(
+            ctx(),
+            upload_name("Floor lamp", "2025-01-01T00:00:00Z"),
+        )
+        .await
+        .unwrap();
+    let response = to_value(from_str::<product_only::UploadResponse>(&response).unwrap()).unwrap();
+    assert_eq!(
+        to_string(&response["product"][0]["Ok"]["rejected_columns"]).unwrap(),
+        r#"["name"]"#
+    );
+    // Nothing was applied, so the row is not downloaded again by the other clients
+    assert_eq!(
+        response["product"][0]["Ok"]["last_synced_at"],
+        accepted_last_synced_at
+    );
+
+    let download_response: product_only::DownloadResponse = from_str(
+        &backend
+            .process_product_only_download_request(ctx(), "null".to_string())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    let download_response = to_value(download_response).unwrap();
+    assert_eq!(
+        download_response["product"]["data"][0]["Update"]["name"],
+        "Desk lamp"
+    );
+}
+
+#[tokio::test]
+async fn test_backend_keeps_insert_newer_than_column_edit() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+
+    let response = backend
+        .process_product_only_upload_request(
+            ctx(),
+            r#"{"product": [{"Insert": {
+                "id": "product-1",
+                "name": "Lamp",
+                "stock": 0,
+                "views": 0,
+                "is_deleted": false,
+                "carburetor_edited_at": {"name": "2025-01-02T00:00:00Z"}
+            }}]}"#
+                .to_string(),
+        )
+        .await
+        .unwrap();
+    assert!(
+        from_str::<product_only::UploadResponse>(&response)
+            .unwrap()
+            .product[0]
+            .is_ok()
+    );
+
+    // Edited before the insert on a device that was offline
+    let response = backend
+        .process_product_only_upload_request(
+            ctx(),
+            r#"{"product": [{"Update": {
+                "id": "product-1",
+                "name": "Floor lamp",
+                "stock": null,
+                "views": null,
+                "carburetor_edited_at": {"name": "2025-01-01T00:00:00Z"}
+            }}]}"#
+                .to_string(),
+        )
+        .await
+        .unwrap();
+    let response = to_value(from_str::<product_only::UploadResponse>(&response).unwrap()).unwrap();
+    assert_eq!(
+        to_string(&response["product"][0]["Ok"]["rejected_columns"]).unwrap(),
+        r#"["name"]"#
+    );
+}
+
+#[tokio::test]
+async fn test_compaction_deletes_column_edits() {
+    use diesel::{Connection, QueryableByName, sql_query};
+
+    #[derive(QueryableByName)]
+    struct ColumnEditRow {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        column_name: String,
+    }
+
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let _db = get_clean_test_client_db();
+    let database_url = backend.test_helper_get_database_url(ctx()).await.unwrap();
+    let mut conn = diesel::PgConnection::establish(&database_url).unwrap();
+
+    let upload = async || {
+        let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+        let upload_response: product_only::UploadResponse = from_str(
+            &backend
+                .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        product_only::store_upload_response(cutoff, upload_response).unwrap();
+    };
+    let column_edits = |conn: &mut diesel::PgConnection, id: &str| {
+        sql_query("SELECT column_name FROM carburetor_column_edits WHERE row_id = $1")
+            .bind::<diesel::sql_types::Text, _>(id)
+            .load::<ColumnEditRow>(conn)
+            .unwrap()
+    };
+
+    let inserted = product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 0,
+        views: 0,
+    })
+    .unwrap();
+    upload().await;
+    assert!(
+        column_edits(&mut conn, &inserted.id)
+            .iter()
+            .any(|x| x.column_name == "name")
+    );
+
+    product_only::delete_product(inserted.id.clone()).unwrap();
+    upload().await;
+    assert_eq!(backend.compact_tombstones(ctx(), 0).await.unwrap(), 1);
+    assert!(column_edits(&mut conn, &inserted.id).is_empty());
+}
+
+#[tokio::test]
+async fn test_backend_clamps_column_edit_from_the_future() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+
+    let upload = async |request: String| {
+        let response = backend
+            .process_product_only_upload_request(ctx(), request)
+            .await
+            .unwrap();
+        to_value(from_str::<product_only::UploadResponse>(&response).unwrap()).unwrap()
+    };
+    upload(
+        r#"{"product": [{"Insert": {
+            "id": "product-1",
+            "name": "Lamp",
+            "stock": 0,
+            "views": 0,
+            "is_deleted": false,
+            "carburetor_edited_at": {"name": "2025-01-01T00:00:00Z"}
+        }}]}"#
+            .to_string(),
+    )
+    .await;
+
+    let upload_name = |name: &str, edited_at: &str| {
+        format!(
+            r#"{{"product": [{{"Update": {{
+                "id": "product-1",
+                "name": "{name}",
+                "stock": null,
+                "views": null,
+                "carburetor_edited_at": {{"name": "{edited_at}"}}
+            }}}}]}}"#
+        )
+    };
+
+    // Edited on a device whose clock is years ahead
+    let response = upload(upload_name("Desk lamp", "2099-01-01T00:00:00Z")).await;
+    assert!(response["product"][0]["Ok"]["rejected_columns"].is_null());
+
+    // A later edit on a device with the right clock still wins
+    let now = to_value(carburetor::helpers::get_utc_now()).unwrap();
+    let response = upload(upload_name("Floor lamp", now.as_str().unwrap())).await;
+    assert!(response["product"][0]["Ok"]["rejected_columns"].is_null());
+
+    let download_response: product_only::DownloadResponse = from_str(
+        &backend
+            .process_product_only_download_request(ctx(), "null".to_string())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    let download_response = to_value(download_response).unwrap();
+    assert_eq!(
+        download_response["product"]["data"][0]["Update"]["name"],
+        "Floor lamp"
+    );
+}
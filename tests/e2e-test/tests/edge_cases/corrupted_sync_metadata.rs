@@ -0,0 +1,224 @@
+use carburetor::chrono::NaiveDate;
+use carburetor::helpers::carburetor_offset::{OffsetKey, retrieve_offsets, upsert_offset};
+use carburetor::models::UploadTableResponseData;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::user_only;
+
+fn insert_test_user() -> user_only::FullUser {
+    user_only::insert_user(user_only::InsertUser {
+        username: "corrupted_user".to_string(),
+        first_name: Some("Corrupted".to_string()),
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: Some(carburetor::helpers::get_utc_now()),
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap()
+}
+
+fn mark_as_synced(conn: &mut diesel::SqliteConnection, id: &str) {
+    diesel::update(user_only::users::table.find(id))
+        .set((
+            user_only::users::dirty_flag.eq(None::<String>),
+            user_only::users::last_synced_at.eq(Some(carburetor::helpers::get_utc_now())),
+        ))
+        .execute(conn)
+        .unwrap();
+}
+
+fn corrupt_metadata(conn: &mut diesel::SqliteConnection, id: &str, metadata: &str) {
+    diesel::update(user_only::users::table.find(id))
+        .set(user_only::users::column_sync_metadata.eq(
+            carburetor::serde_json::from_str::<carburetor::serde_json::Value>(metadata).unwrap(),
+        ))
+        .execute(conn)
+        .unwrap();
+}
+
+fn find_user(conn: &mut diesel::SqliteConnection, id: &str) -> user_only::FullUser {
+    user_only::users::table
+        .find(id)
+        .select(user_only::FullUser::as_select())
+        .first(conn)
+        .unwrap()
+}
+
+fn upsert_users_offset(conn: &mut diesel::SqliteConnection, sync_group: &str) {
+    upsert_offset(
+        conn,
+        OffsetKey {
+            sync_group,
+            table_name: "users",
+            restriction: "",
+        },
+        carburetor::helpers::get_utc_now(),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_store_upload_response_with_corrupted_metadata_resets_record() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+    upsert_users_offset(&mut conn, "user_only");
+    upsert_users_offset(&mut conn, "all_clients");
+
+    // Corrupt the metadata while the upload is in flight
+    corrupt_metadata(&mut conn, &inserted.id, r#"{".insert_time": "not a date"}"#);
+
+    user_only::store_upload_response(
+        cutoff,
+        user_only::UploadResponse {
+            user: vec![Ok(UploadTableResponseData {
+                id: inserted.id.clone(),
+                last_synced_at: carburetor::helpers::get_utc_now(),
+                rejected_columns: Vec::new(),
+            })],
+        },
+    )
+    .unwrap();
+
+    // The record is replaced by the one of the backend on the next download
+    let user = find_user(&mut conn, &inserted.id);
+    assert_eq!(user.dirty_flag, None);
+    assert_eq!(user.last_synced_at, None);
+    assert!(
+        user.column_sync_metadata
+            .as_object()
+            .is_some_and(|x| x.is_empty())
+    );
+
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+
+    // Only the group of the upload downloads the table again
+    let offsets = retrieve_offsets(&mut conn).unwrap();
+    assert!(
+        !offsets
+            .iter()
+            .any(|x| x.sync_group == "user_only" && x.table_name == "users")
+    );
+    assert!(
+        offsets
+            .iter()
+            .any(|x| x.sync_group == "all_clients" && x.table_name == "users")
+    );
+}
+
+#[tokio::test]
+async fn test_retrieve_upload_request_with_corrupted_insert_metadata_uploads_insert_again() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    corrupt_metadata(&mut conn, &inserted.id, r#"{".insert_time": 0}"#);
+
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+
+    // The backend does not have the record yet, so it is inserted as a whole
+    let user = find_user(&mut conn, &inserted.id);
+    assert_eq!(user.dirty_flag.as_deref(), Some("insert"));
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+    match &upload_request.user[0] {
+        user_only::UploadRequestUser::Insert(insert_data) => {
+            assert_eq!(insert_data.id, inserted.id);
+            assert_eq!(insert_data.username, "corrupted_user");
+        }
+        _ => panic!("Expected Insert variant for the repaired user"),
+    }
+}
+
+#[tokio::test]
+async fn test_store_download_response_with_corrupted_metadata_replaces_record() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    mark_as_synced(&mut conn, &inserted.id);
+    diesel::update(user_only::users::table.find(&inserted.id))
+        .set(user_only::users::dirty_flag.eq(Some("update".to_string())))
+        .execute(&mut conn)
+        .unwrap();
+    corrupt_metadata(&mut conn, &inserted.id, r#"{".insert_time": "not a date"}"#);
+
+    let backend_now = carburetor::helpers::get_utc_now();
+    user_only::store_download_response(user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: backend_now,
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                user_only::DownloadUpdateUser {
+                    id: inserted.id.clone(),
+                    username: "downloaded".to_string(),
+                    first_name: None,
+                    joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    created_at: backend_now,
+                    nickname: None,
+                    priority: 0,
+                    preferences: None,
+                    last_synced_at: backend_now,
+                    is_deleted: false,
+                },
+            )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    let user = find_user(&mut conn, &inserted.id);
+    assert_eq!(user.username, "downloaded");
+    assert_eq!(user.first_name, None);
+    assert_eq!(user.dirty_flag, None);
+    assert_eq!(user.last_synced_at, Some(backend_now));
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+}
+
+#[tokio::test]
+async fn test_update_with_corrupted_metadata_uploads_the_edit_only() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    mark_as_synced(&mut conn, &inserted.id);
+    upsert_users_offset(&mut conn, "user_only");
+    corrupt_metadata(&mut conn, &inserted.id, r#"{".insert_time": "not a date"}"#);
+
+    user_only::update_user(user_only::UpdateUser {
+        id: inserted.id.clone(),
+        username: None,
+        first_name: Some(Some("Edited".to_string())),
+        nickname: None,
+        priority: None,
+        preferences: None,
+        joined_on: None,
+    })
+    .unwrap();
+
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+    match &upload_request.user[0] {
+        user_only::UploadRequestUser::Update(update_data) => {
+            assert_eq!(update_data.id, inserted.id);
+            assert_eq!(update_data.username, None);
+            assert_eq!(update_data.first_name, Some(Some("Edited".to_string())));
+        }
+        _ => panic!("Expected Update variant for the edited user"),
+    }
+
+    // The other columns are downloaded again
+    let offsets = retrieve_offsets(&mut conn).unwrap();
+    assert!(!offsets.iter().any(|x| x.table_name == "users"));
+}
@@ -0,0 +1,226 @@
+use carburetor::serde_json::{from_str, to_string, to_value};
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::product_only;
+use tarpc::context::current as ctx;
+
+fn get_product(conn: &mut diesel::SqliteConnection, id: &str) -> product_only::FullProduct {
+    product_only::products::table
+        .find(id)
+        .select(product_only::FullProduct::as_select())
+        .first(conn)
+        .unwrap()
+}
+
+/// Stores a downloaded row as if it was synced from the backend, and returns its ID.
+fn store_synced_product(stock: i32, last_synced_at: &str) -> String {
+    let response: product_only::DownloadResponse = from_str(&format!(
+        r#"{{"product": {{
+            "cutoff_at": "{last_synced_at}",
+            "data": [{{"Update": {{
+                "id": "product-1",
+                "name": "Lamp",
+                "stock": {stock},
+                "views": 0,
+                "last_synced_at": "{last_synced_at}",
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": false
+        }}}}"#
+    ))
+    .unwrap();
+    product_only::store_download_response(response).unwrap();
+    "product-1".to_string()
+}
+
+fn update_stock(id: &str, stock: i32) {
+    product_only::update_product(product_only::UpdateProduct {
+        id: id.to_string(),
+        name: None,
+        stock: Some(stock),
+        views: None,
+    })
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_upload_sends_increments() {
+    let _db = get_clean_test_client_db();
+    let id = store_synced_product(10, "2025-01-01T00:00:00Z");
+
+    update_stock(&id, 13);
+    update_stock(&id, 12);
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+
+    let request = to_value(&upload_request).unwrap();
+    let update = &request["product"][0]["Update"];
+    assert!(!update["stock"]["device_id"].as_str().unwrap().is_empty());
+    let deltas = update["stock"]["increments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["delta"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(deltas, vec![3, -1]);
+    assert!(update["views"].is_null());
+}
+
+#[tokio::test]
+async fn test_download_adds_pending_increments() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+    let id = store_synced_product(10, "2025-01-01T00:00:00Z");
+
+    update_stock(&id, 13);
+    // Another device added 10 in the meantime
+    store_synced_product(20, "2025-01-02T00:00:00Z");
+
+    assert_eq!(get_product(&mut conn, &id).stock, 23);
+}
+
+#[tokio::test]
+async fn test_download_older_than_accepted_upload_keeps_increments() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+    let id = store_synced_product(10, "2025-01-01T00:00:00Z");
+
+    update_stock(&id, 13);
+    let (cutoff, _) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(&format!(
+        r#"{{"product": [{{"Ok": {{"id": "{id}", "last_synced_at": "2025-01-03T00:00:00Z"}}}}]}}"#
+    ))
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    // Downloaded before the upload was applied on the backend
+    store_synced_product(10, "2025-01-02T00:00:00Z");
+    assert_eq!(get_product(&mut conn, &id).stock, 13);
+
+    // Downloaded after, with 5 more from another device
+    store_synced_product(18, "2025-01-04T00:00:00Z");
+    assert_eq!(get_product(&mut conn, &id).stock, 18);
+
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.product.is_empty());
+}
+
+#[tokio::test]
+async fn test_backend_sums_increments_from_devices() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 10,
+        views: 0,
+    })
+    .unwrap();
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    // Another device sells 3 while this one restocks 5
+    backend
+        .process_product_only_upload_request(
+            ctx(),
+            format!(
+                r#"{{"product": [{{"Update": {{"id": "{}", "name": null, "stock": {{
+                    "device_id": "other-device",
+                    "increments": [{{"at": "2025-01-01T00:00:00Z", "delta": -3}}]
+                }}, "views": null}}}}]}}"#,
+                inserted.id
+            ),
+        )
+        .await
+        .unwrap();
+    update_stock(&inserted.id, 15);
+
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let download_request = product_only::retrieve_download_request().unwrap();
+    let download_response: product_only::DownloadResponse = from_str(
+        &backend
+            .process_product_only_download_request(ctx(), to_string(&download_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_download_response(download_response).unwrap();
+
+    assert_eq!(get_product(&mut conn, &inserted.id).stock, 12);
+}
+
+#[tokio::test]
+async fn test_backend_adds_replayed_increments_once() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 10,
+        views: 0,
+    })
+    .unwrap();
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    // The response of the first upload is lost, so the same upload is sent again, and then once
+    // more along with a new increment
+    update_stock(&inserted.id, 15);
+    let (_, upload_request) = product_only::retrieve_upload_request().unwrap();
+    for _ in 0..2 {
+        backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap();
+    }
+    update_stock(&inserted.id, 16);
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let download_request = product_only::retrieve_download_request().unwrap();
+    let download_response: product_only::DownloadResponse = from_str(
+        &backend
+            .process_product_only_download_request(ctx(), to_string(&download_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_download_response(download_response).unwrap();
+
+    assert_eq!(get_product(&mut conn, &inserted.id).stock, 16);
+}
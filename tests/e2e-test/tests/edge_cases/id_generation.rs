@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use carburetor::models::UploadTableResponseErrorType;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::{all_clients, user_only};
+
+#[tokio::test]
+async fn test_default_ids_do_not_collide_in_tight_loop() {
+    let _db = get_clean_test_client_db();
+
+    let ids = (0..500)
+        .map(|i| {
+            user_only::insert_user(user_only::InsertUser {
+                username: format!("loop_user_{}", i),
+                first_name: None,
+                joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                created_at: None,
+                nickname: None,
+                priority: None,
+                preferences: None,
+            })
+            .unwrap()
+            .id
+        })
+        .collect::<HashSet<_>>();
+
+    assert_eq!(ids.len(), 500);
+    assert!(ids.iter().all(|x| x.starts_with("user-")));
+}
+
+#[tokio::test]
+async fn test_insert_uses_id_strategy_of_table() {
+    let _db = get_clean_test_client_db();
+
+    let message = all_clients::insert_message(all_clients::InsertMessage {
+        recipient_id: "user-1".to_string(),
+        subject: "Hello".to_string(),
+        body: "World".to_string(),
+        notes: None,
+    })
+    .unwrap();
+
+    // `message` is declared with `#[id(uuid_v7)]`
+    assert_eq!(message.id.len(), 36);
+    assert_eq!(message.id.chars().nth(14), Some('7'));
+}
+
+#[tokio::test]
+async fn test_rekey_uses_id_strategy_of_table() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let message = all_clients::insert_message(all_clients::InsertMessage {
+        recipient_id: "user-1".to_string(),
+        subject: "Hello".to_string(),
+        body: "World".to_string(),
+        notes: None,
+    })
+    .unwrap();
+
+    let (cutoff, _) = all_clients::retrieve_upload_request().unwrap();
+    all_clients::store_upload_response(
+        cutoff,
+        all_clients::UploadResponse {
+            user: vec![],
+            message: vec![Err(carburetor::models::UploadTableResponseError {
+                id: message.id.clone(),
+                code: UploadTableResponseErrorType::RecordAlreadyExists,
+            })],
+        },
+    )
+    .unwrap();
+
+    let messages: Vec<all_clients::FullMessage> = all_clients::messages::table
+        .select(all_clients::FullMessage::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_ne!(messages[0].id, message.id);
+    assert_eq!(messages[0].id.chars().nth(14), Some('7'));
+}
+
+#[tokio::test]
+async fn test_device_counter_ids() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let first = carburetor::helpers::id::device_counter(&mut conn).unwrap();
+    let second = carburetor::helpers::id::device_counter(&mut conn).unwrap();
+    // The counter is stored, so a new connection carries on from it
+    let third = carburetor::helpers::id::device_counter(&mut db.get_connection()).unwrap();
+
+    let (device, counter) = first.rsplit_once('-').unwrap();
+    assert_eq!(counter, "1");
+    assert_eq!(second, format!("{}-2", device));
+    assert_eq!(third, format!("{}-3", device));
+}
+
+#[tokio::test]
+async fn test_ulid_ids() {
+    let first = carburetor::helpers::id::ulid();
+    let second = carburetor::helpers::id::ulid();
+
+    assert_eq!(first.len(), 26);
+    assert_ne!(first, second);
+}
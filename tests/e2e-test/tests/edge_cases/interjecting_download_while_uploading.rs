@@ -50,7 +50,16 @@ async fn test_download_between_upload_process_and_store() {
         .values(&synced_user)
         .execute(&mut conn)
         .unwrap();
-    carburetor::helpers::carburetor_offset::upsert_offset(&mut conn, "users", before_seed).unwrap();
+    carburetor::helpers::carburetor_offset::upsert_offset(
+        &mut conn,
+        carburetor::helpers::carburetor_offset::OffsetKey {
+            sync_group: "user_only",
+            table_name: "users",
+            restriction: "",
+        },
+        before_seed,
+    )
+    .unwrap();
 
     // Client updates the user — dirty_flag="update"
     user_only::update_user(user_only::UpdateUser {
@@ -0,0 +1,172 @@
+use carburetor::serde_json::{Value, from_str, to_string, to_value};
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::preference_only;
+use tarpc::context::current as ctx;
+
+fn json(value: &str) -> Value {
+    from_str(value).unwrap()
+}
+
+fn get_settings(conn: &mut diesel::SqliteConnection, id: &str) -> Value {
+    preference_only::preferences::table
+        .find(id)
+        .select(preference_only::FullPreference::as_select())
+        .first(conn)
+        .unwrap()
+        .settings
+}
+
+/// Stores a downloaded row as if it was synced from the backend, and returns its ID.
+fn store_synced_preference(settings: &str, last_synced_at: &str) -> String {
+    let response: preference_only::DownloadResponse = from_str(&format!(
+        r#"{{"preference": {{
+            "cutoff_at": "{last_synced_at}",
+            "data": [{{"Update": {{
+                "id": "preference-1",
+                "settings": {settings},
+                "last_synced_at": "{last_synced_at}",
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": false
+        }}}}"#
+    ))
+    .unwrap();
+    preference_only::store_download_response(response).unwrap();
+    "preference-1".to_string()
+}
+
+#[tokio::test]
+async fn test_upload_sends_changed_keys_only() {
+    let _db = get_clean_test_client_db();
+    let id = store_synced_preference(
+        r#"{"theme": "dark", "font": 12, "lang": "en"}"#,
+        "2025-01-01T00:00:00Z",
+    );
+
+    preference_only::update_preference(preference_only::UpdatePreference {
+        id,
+        settings: Some(json(r#"{"theme": "light", "lang": "en"}"#)),
+    })
+    .unwrap();
+    let (_, upload_request) = preference_only::retrieve_upload_request().unwrap();
+
+    let request = to_value(&upload_request).unwrap();
+    assert_eq!(
+        request["preference"][0]["Update"]["settings"],
+        json(r#"{"set": {"theme": "light"}, "remove": ["font"]}"#)
+    );
+}
+
+#[tokio::test]
+async fn test_download_keeps_dirty_keys() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+    let id = store_synced_preference(r#"{"theme": "dark", "font": 12}"#, "2025-01-01T00:00:00Z");
+
+    preference_only::update_preference(preference_only::UpdatePreference {
+        id: id.clone(),
+        settings: Some(json(r#"{"theme": "light", "font": 12}"#)),
+    })
+    .unwrap();
+    // Another device changed the other keys in the meantime
+    store_synced_preference(
+        r#"{"theme": "dark", "font": 14, "lang": "fr"}"#,
+        "2025-01-02T00:00:00Z",
+    );
+
+    assert_eq!(
+        get_settings(&mut conn, &id),
+        json(r#"{"theme": "light", "font": 14, "lang": "fr"}"#)
+    );
+}
+
+#[tokio::test]
+async fn test_update_with_non_object_fails() {
+    let _db = get_clean_test_client_db();
+    let id = store_synced_preference(r#"{"theme": "dark"}"#, "2025-01-01T00:00:00Z");
+
+    let result = preference_only::update_preference(preference_only::UpdatePreference {
+        id,
+        settings: Some(json("[1, 2]")),
+    });
+
+    assert!(matches!(
+        result,
+        Err(carburetor::error::Error::Serialization { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_backend_merges_keys_from_devices() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = preference_only::insert_preference(preference_only::InsertPreference {
+        settings: json(r#"{"theme": "dark", "font": 12}"#),
+    })
+    .unwrap();
+    let (cutoff, upload_request) = preference_only::retrieve_upload_request().unwrap();
+    let upload_response: preference_only::UploadResponse = from_str(
+        &backend
+            .process_preference_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    preference_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    // Another device sets a new key and removes one
+    backend
+        .process_preference_only_upload_request(
+            ctx(),
+            format!(
+                r#"{{"preference": [{{"Update": {{
+                    "id": "{}",
+                    "settings": {{"set": {{"lang": "fr"}}, "remove": ["font"]}}
+                }}}}]}}"#,
+                inserted.id
+            ),
+        )
+        .await
+        .unwrap();
+
+    preference_only::update_preference(preference_only::UpdatePreference {
+        id: inserted.id.clone(),
+        settings: Some(json(r#"{"theme": "light", "font": 12}"#)),
+    })
+    .unwrap();
+    let (cutoff, upload_request) = preference_only::retrieve_upload_request().unwrap();
+    let upload_response: preference_only::UploadResponse = from_str(
+        &backend
+            .process_preference_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    preference_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let download_request = preference_only::retrieve_download_request().unwrap();
+    let download_response: preference_only::DownloadResponse = from_str(
+        &backend
+            .process_preference_only_download_request(ctx(), to_string(&download_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        download_response.preference.data.len(),
+        1,
+        "both uploads land in a single row"
+    );
+    preference_only::store_download_response(download_response).unwrap();
+
+    assert_eq!(
+        get_settings(&mut conn, &inserted.id),
+        json(r#"{"theme": "light", "lang": "fr"}"#)
+    );
+}
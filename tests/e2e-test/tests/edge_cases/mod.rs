@@ -1,4 +1,18 @@
+// The hybrid logical clock is covered by the `hlc` test target
+#[cfg(not(feature = "hlc"))]
+pub mod client_clock_behind_backend;
 pub mod client_migration_stale_tmp_table;
+pub mod concurrent_column_edits;
+pub mod corrupted_sync_metadata;
+pub mod counter_columns;
 pub mod dirty_while_upload;
+pub mod id_generation;
 pub mod interjecting_download_while_uploading;
+pub mod json_keys_merge;
+pub mod postgres_types;
+pub mod rejected_upload_rows;
+pub mod table_in_several_groups;
+pub mod upload_failures;
 pub mod upload_missing_default_columns;
+pub mod uuid_ids;
+pub mod xid_change_tracking;
@@ -0,0 +1,179 @@
+use carburetor::types::{Interval, Money};
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::{InvoiceStatus, invoice_only};
+
+fn sample_invoice() -> invoice_only::InsertInvoice {
+    invoice_only::InsertInvoice {
+        number: "INV-001".to_string(),
+        amount: "1250.50".to_string(),
+        fee: Money(1999),
+        due_in: Interval::from_microseconds(30 * 24 * 60 * 60 * 1_000_000),
+        attachment: Some(vec![0, 159, 146, 150]),
+        metadata: carburetor::serde_json::from_str(r#"{"tags": ["a", "b"]}"#).unwrap(),
+        client_ip: Some("192.168.0.1/32".to_string()),
+        tags: vec!["urgent".to_string(), "q1".to_string()].into(),
+        quantities: Some(vec![Some(3), None].into()),
+        status: InvoiceStatus::Paid,
+    }
+}
+
+#[tokio::test]
+async fn test_insert_and_load_all_column_types() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = invoice_only::insert_invoice(sample_invoice()).unwrap();
+
+    let stored: invoice_only::FullInvoice = invoice_only::invoices::table
+        .find(&inserted.id)
+        .select(invoice_only::FullInvoice::as_select())
+        .first(&mut conn)
+        .unwrap();
+    let expected = sample_invoice();
+    assert_eq!(stored.number, expected.number);
+    assert_eq!(stored.amount, expected.amount);
+    assert_eq!(stored.fee, expected.fee);
+    assert_eq!(stored.due_in, expected.due_in);
+    assert_eq!(stored.attachment, expected.attachment);
+    assert_eq!(stored.metadata, expected.metadata);
+    assert_eq!(stored.client_ip, expected.client_ip);
+    assert_eq!(stored.tags, expected.tags);
+    assert_eq!(stored.quantities, expected.quantities);
+    assert_eq!(stored.status, expected.status);
+}
+
+#[tokio::test]
+async fn test_upload_request_serializes_column_types() {
+    let _db = get_clean_test_client_db();
+
+    invoice_only::insert_invoice(sample_invoice()).unwrap();
+    let (_, upload_request) = invoice_only::retrieve_upload_request().unwrap();
+
+    let json = carburetor::serde_json::to_value(&upload_request).unwrap();
+    let invoice = &json["invoice"][0]["Insert"];
+    assert_eq!(invoice["number"], "INV-001");
+    assert_eq!(invoice["amount"], "1250.50");
+    assert_eq!(invoice["fee"], 1999);
+    assert_eq!(invoice["due_in"], 2_592_000_000_000i64);
+    assert_eq!(invoice["metadata"]["tags"][1], "b");
+    assert_eq!(invoice["client_ip"], "192.168.0.1/32");
+    // Arrays are sent as plain JSON arrays, as the backend expects for `Vec<T>`
+    assert_eq!(
+        invoice["tags"],
+        carburetor::serde_json::to_value(["urgent", "q1"]).unwrap()
+    );
+    assert_eq!(
+        invoice["quantities"],
+        carburetor::serde_json::to_value([Some(3), None]).unwrap()
+    );
+    assert_eq!(invoice["status"], "paid_in_full");
+}
+
+#[tokio::test]
+async fn test_store_download_response_with_column_types() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    // The backend sends `Numeric` and `Inet` in their text form, which the client keeps as is
+    let now = carburetor::serde_json::to_string(&carburetor::helpers::get_utc_now()).unwrap();
+    let response: invoice_only::DownloadResponse = carburetor::serde_json::from_str(&format!(
+        r#"{{"invoice": {{
+            "cutoff_at": {now},
+            "data": [{{"Update": {{
+                "id": "invoice-1",
+                "number": "INV-002",
+                "amount": "0.10000000000000000001",
+                "fee": -250,
+                "due_in": 3600000000,
+                "attachment": null,
+                "metadata": {{}},
+                "client_ip": "10.0.0.0/8",
+                "tags": ["paid"],
+                "quantities": null,
+                "status": "sent",
+                "last_synced_at": {now},
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": false
+        }}}}"#
+    ))
+    .unwrap();
+    invoice_only::store_download_response(response).unwrap();
+
+    let stored: invoice_only::FullInvoice = invoice_only::invoices::table
+        .find("invoice-1")
+        .select(invoice_only::FullInvoice::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(stored.number, "INV-002");
+    assert_eq!(stored.amount, "0.10000000000000000001");
+    assert_eq!(stored.fee, Money(-250));
+    assert_eq!(
+        stored.due_in,
+        Interval(carburetor::chrono::TimeDelta::hours(1))
+    );
+    assert_eq!(stored.attachment, None);
+    assert_eq!(stored.client_ip, Some("10.0.0.0/8".to_string()));
+    assert_eq!(*stored.tags, vec!["paid".to_string()]);
+    assert_eq!(stored.quantities, None);
+    assert_eq!(stored.status, InvoiceStatus::Sent);
+}
+
+#[tokio::test]
+async fn test_load_unknown_enum_variant_returns_error() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    // A newer version of the app may have stored a variant that this version does not know
+    let inserted = invoice_only::insert_invoice(sample_invoice()).unwrap();
+    diesel::sql_query("UPDATE invoices SET status = 'archived' WHERE id = ?")
+        .bind::<diesel::sql_types::Text, _>(&inserted.id)
+        .execute(&mut conn)
+        .unwrap();
+
+    let result = invoice_only::retrieve_upload_request();
+
+    match result {
+        Err(carburetor::error::Error::UnknownVariant(e)) => {
+            assert_eq!(e.value, "archived");
+            assert!(e.enum_name.ends_with("InvoiceStatus"));
+        }
+        other => panic!("expected an unknown variant error, got {:?}", other.err()),
+    }
+}
+
+#[tokio::test]
+async fn test_download_unknown_enum_variant_returns_error() {
+    let _db = get_clean_test_client_db();
+
+    let now = carburetor::serde_json::to_string(&carburetor::helpers::get_utc_now()).unwrap();
+    let result = carburetor::serde_json::from_str::<invoice_only::DownloadResponse>(&format!(
+        r#"{{"invoice": {{
+            "cutoff_at": {now},
+            "data": [{{"Update": {{
+                "id": "invoice-1",
+                "number": "INV-002",
+                "amount": "1",
+                "fee": 0,
+                "due_in": 0,
+                "attachment": null,
+                "metadata": {{}},
+                "client_ip": null,
+                "tags": [],
+                "quantities": null,
+                "status": "archived",
+                "last_synced_at": {now},
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": false
+        }}}}"#
+    ));
+
+    let err = result.err().unwrap().to_string();
+    assert!(err.contains("Unknown variant `archived`"));
+}
@@ -0,0 +1,165 @@
+use carburetor::models::{UploadTableResponseError, UploadTableResponseErrorType};
+use diesel::{OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::user_only;
+
+fn insert_test_user() -> user_only::FullUser {
+    user_only::insert_user(user_only::InsertUser {
+        username: "rejected_user".to_string(),
+        first_name: Some("Rejected".to_string()),
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: Some(carburetor::helpers::get_utc_now()),
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap()
+}
+
+fn mark_as_synced_update(conn: &mut diesel::SqliteConnection, id: &str) {
+    use diesel::ExpressionMethods;
+
+    diesel::update(user_only::users::table.find(id))
+        .set((
+            user_only::users::dirty_flag.eq(Some("update".to_string())),
+            user_only::users::last_synced_at.eq(Some(carburetor::helpers::get_utc_now())),
+            user_only::users::column_sync_metadata.eq(carburetor::serde_json::from_str::<
+                carburetor::serde_json::Value,
+            >(&format!(
+                r#"{{"username": {{"dirty_at": "{}"}}}}"#,
+                carburetor::helpers::get_utc_now().to_rfc3339()
+            ))
+            .unwrap()),
+        ))
+        .execute(conn)
+        .unwrap();
+}
+
+fn error_response(id: String, code: UploadTableResponseErrorType) -> user_only::UploadResponse {
+    user_only::UploadResponse {
+        user: vec![Err(UploadTableResponseError { id, code })],
+    }
+}
+
+#[tokio::test]
+async fn test_store_record_already_exists_response_rekeys_insert() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+
+    user_only::store_upload_response(
+        cutoff,
+        error_response(
+            inserted.id.clone(),
+            UploadTableResponseErrorType::RecordAlreadyExists,
+        ),
+    )
+    .unwrap();
+
+    let users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(users.len(), 1);
+    assert_ne!(users[0].id, inserted.id, "Record should be given a new ID");
+    assert!(users[0].id.starts_with("user"));
+    assert_eq!(users[0].username, "rejected_user");
+    assert_eq!(users[0].dirty_flag.as_deref(), Some("insert"));
+
+    // The re-keyed record is uploaded again on the next attempt
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+}
+
+#[tokio::test]
+async fn test_store_record_not_found_response_drops_updated_record() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    mark_as_synced_update(&mut conn, &inserted.id);
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+
+    user_only::store_upload_response(
+        cutoff,
+        error_response(
+            inserted.id.clone(),
+            UploadTableResponseErrorType::RecordNotFound,
+        ),
+    )
+    .unwrap();
+
+    // Deleted and compacted on the backend, so it must not come back
+    let user: Option<user_only::FullUser> = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .optional()
+        .unwrap();
+    assert!(user.is_none(), "Updated record should be dropped locally");
+
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+}
+
+#[tokio::test]
+async fn test_store_record_not_found_response_drops_deleted_record() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    mark_as_synced_update(&mut conn, &inserted.id);
+    user_only::delete_user(inserted.id.clone()).unwrap();
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+
+    user_only::store_upload_response(
+        cutoff,
+        error_response(
+            inserted.id.clone(),
+            UploadTableResponseErrorType::RecordNotFound,
+        ),
+    )
+    .unwrap();
+
+    let user: Option<user_only::FullUser> = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .optional()
+        .unwrap();
+    assert!(user.is_none(), "Deleted record should be dropped locally");
+}
+
+#[tokio::test]
+async fn test_store_insufficient_permission_response_stops_upload() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    let (cutoff, _) = user_only::retrieve_upload_request().unwrap();
+
+    user_only::store_upload_response(
+        cutoff,
+        error_response(
+            inserted.id.clone(),
+            UploadTableResponseErrorType::InsufficientPermission,
+        ),
+    )
+    .unwrap();
+
+    let user: user_only::FullUser = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(user.dirty_flag.as_deref(), Some("rejected"));
+    assert_eq!(user.username, "rejected_user");
+
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+}
@@ -0,0 +1,104 @@
+use carburetor::serde_json::from_str;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::{all_clients, user_only};
+
+fn table_response(cutoff_at: &str, username: &str, reset: bool) -> String {
+    format!(
+        r#"{{
+            "cutoff_at": "{cutoff_at}",
+            "data": [{{"Update": {{
+                "id": "user-1",
+                "username": "{username}",
+                "first_name": null,
+                "joined_on": "2025-01-01",
+                "created_at": "2025-01-01T00:00:00Z",
+                "nickname": null,
+                "priority": 0,
+                "preferences": null,
+                "last_synced_at": "{cutoff_at}",
+                "is_deleted": false
+            }}}}],
+            "cursor": null,
+            "has_more": false,
+            "reset": {reset}
+        }}"#
+    )
+}
+
+fn store_user_only(cutoff_at: &str, username: &str, reset: bool) {
+    let response: user_only::DownloadResponse = from_str(&format!(
+        r#"{{"user": {}}}"#,
+        table_response(cutoff_at, username, reset)
+    ))
+    .unwrap();
+    user_only::store_download_response(response).unwrap();
+}
+
+fn store_all_clients(cutoff_at: &str, username: &str, reset: bool) {
+    let response: all_clients::DownloadResponse = from_str(&format!(
+        r#"{{
+            "user": {},
+            "message": {{
+                "cutoff_at": "{cutoff_at}",
+                "data": [],
+                "cursor": null,
+                "has_more": false,
+                "reset": false
+            }}
+        }}"#,
+        table_response(cutoff_at, username, reset)
+    ))
+    .unwrap();
+    all_clients::store_download_response(response).unwrap();
+}
+
+#[tokio::test]
+async fn test_each_group_keeps_its_own_offset() {
+    let _db = get_clean_test_client_db();
+
+    store_user_only("2025-01-01T00:00:00Z", "first", false);
+    // Downloading through one group does not move the offset of the other
+    assert!(all_clients::retrieve_download_request().unwrap().is_none());
+
+    store_all_clients("2025-01-03T00:00:00Z", "second", false);
+    let user_only_request = user_only::retrieve_download_request().unwrap().unwrap();
+    let all_clients_request = all_clients::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(
+        user_only_request.user_offset.unwrap().to_rfc3339(),
+        "2025-01-01T00:00:00+00:00"
+    );
+    assert_eq!(
+        all_clients_request.user_offset.unwrap().to_rfc3339(),
+        "2025-01-03T00:00:00+00:00"
+    );
+}
+
+#[tokio::test]
+async fn test_groups_share_the_client_table() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    store_user_only("2025-01-01T00:00:00Z", "first", false);
+    store_all_clients("2025-01-03T00:00:00Z", "second", false);
+
+    let users: Vec<user_only::FullUser> = all_clients::users::table
+        .select(all_clients::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].username, "second");
+}
+
+#[tokio::test]
+async fn test_reset_downloads_table_again_in_every_group() {
+    let _db = get_clean_test_client_db();
+
+    store_user_only("2025-01-01T00:00:00Z", "first", false);
+    store_all_clients("2025-01-03T00:00:00Z", "second", true);
+
+    let user_only_request = user_only::retrieve_download_request().unwrap();
+    assert!(user_only_request.is_none());
+    let all_clients_request = all_clients::retrieve_download_request().unwrap().unwrap();
+    assert!(all_clients_request.user_offset.is_some());
+}
@@ -0,0 +1,154 @@
+use carburetor::helpers::upload_failure::UploadRetryPolicy;
+use carburetor::models::{
+    UploadTableResponseData, UploadTableResponseError, UploadTableResponseErrorType,
+};
+use diesel::{OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::user_only;
+
+fn insert_test_user() -> user_only::FullUser {
+    user_only::insert_user(user_only::InsertUser {
+        username: "failing_user".to_string(),
+        first_name: Some("Failing".to_string()),
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: Some(carburetor::helpers::get_utc_now()),
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap()
+}
+
+fn store_error(id: &str, code: UploadTableResponseErrorType) {
+    let (cutoff, _) = user_only::retrieve_upload_request().unwrap();
+    user_only::store_upload_response(
+        cutoff,
+        user_only::UploadResponse {
+            user: vec![Err(UploadTableResponseError {
+                id: id.to_string(),
+                code,
+            })],
+        },
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_unknown_upload_error_is_recorded_and_backed_off() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    store_error(&inserted.id, UploadTableResponseErrorType::Unknown);
+    store_error(&inserted.id, UploadTableResponseErrorType::Unknown);
+
+    let failures = user_only::list_upload_failures().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].table_name, "users");
+    assert_eq!(failures[0].id, inserted.id);
+    assert_eq!(failures[0].code, UploadTableResponseErrorType::Unknown);
+    assert_eq!(failures[0].attempts, 2);
+
+    // Held back while waiting for the backoff
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+
+    let no_backoff = UploadRetryPolicy {
+        max_attempts: 3,
+        initial_backoff: std::time::Duration::ZERO,
+        max_backoff: std::time::Duration::ZERO,
+    };
+    let (_, upload_request) =
+        user_only::retrieve_upload_request_with_connection(&mut conn, &no_backoff).unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+
+    // Held back for good once attempts run out
+    let exhausted = UploadRetryPolicy {
+        max_attempts: 2,
+        ..no_backoff
+    };
+    let (_, upload_request) =
+        user_only::retrieve_upload_request_with_connection(&mut conn, &exhausted).unwrap();
+    assert!(upload_request.user.is_empty());
+}
+
+#[tokio::test]
+async fn test_successful_upload_clears_failure() {
+    let _db = get_clean_test_client_db();
+
+    let inserted = insert_test_user();
+    store_error(&inserted.id, UploadTableResponseErrorType::Unknown);
+    assert_eq!(user_only::list_upload_failures().unwrap().len(), 1);
+
+    let (cutoff, _) = user_only::retrieve_upload_request().unwrap();
+    user_only::store_upload_response(
+        cutoff,
+        user_only::UploadResponse {
+            user: vec![Ok(UploadTableResponseData {
+                id: inserted.id.clone(),
+                last_synced_at: carburetor::helpers::get_utc_now(),
+                rejected_columns: Vec::new(),
+            })],
+        },
+    )
+    .unwrap();
+
+    assert!(user_only::list_upload_failures().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_retry_upload_failure_uploads_record_again() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    store_error(
+        &inserted.id,
+        UploadTableResponseErrorType::InsufficientPermission,
+    );
+    let failures = user_only::list_upload_failures().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(
+        failures[0].code,
+        UploadTableResponseErrorType::InsufficientPermission
+    );
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+
+    user_only::retry_upload_failure(&failures[0]).unwrap();
+
+    assert!(user_only::list_upload_failures().unwrap().is_empty());
+    let user: user_only::FullUser = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(user.dirty_flag.as_deref(), Some("insert"));
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+}
+
+#[tokio::test]
+async fn test_discard_upload_failure_drops_local_record() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    store_error(
+        &inserted.id,
+        UploadTableResponseErrorType::InsufficientPermission,
+    );
+    let failures = user_only::list_upload_failures().unwrap();
+    assert_eq!(failures.len(), 1);
+
+    user_only::discard_upload_failure(&failures[0]).unwrap();
+
+    assert!(user_only::list_upload_failures().unwrap().is_empty());
+    let user: Option<user_only::FullUser> = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .optional()
+        .unwrap();
+    assert!(user.is_none());
+}
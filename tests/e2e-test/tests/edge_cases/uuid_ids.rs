@@ -0,0 +1,96 @@
+use carburetor::models::{DownloadCursor, UploadTableResponseData};
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::bookmark_only;
+
+#[tokio::test]
+async fn test_insert_generates_uuid_for_uuid_id() {
+    let _db = get_clean_test_client_db();
+
+    let bookmark = bookmark_only::insert_bookmark(bookmark_only::InsertBookmark {
+        url: "https://example.com".to_string(),
+    })
+    .unwrap();
+
+    // `Uuid` IDs are generated with `uuid_v7` unless another strategy is given
+    let id = carburetor::uuid::Uuid::parse_str(&bookmark.id).unwrap();
+    assert_eq!(id.get_version_num(), 7);
+    assert_eq!(bookmark.id, id.hyphenated().to_string());
+}
+
+#[tokio::test]
+async fn test_store_backend_upload_response_with_uuid_id() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let bookmark = bookmark_only::insert_bookmark(bookmark_only::InsertBookmark {
+        url: "https://example.com".to_string(),
+    })
+    .unwrap();
+    let (cutoff, upload_request) = bookmark_only::retrieve_upload_request().unwrap();
+    assert_eq!(upload_request.bookmark.len(), 1);
+
+    // The backend answers with its own ID type, which is sent as text
+    let backend_response: Vec<Result<UploadTableResponseData<carburetor::uuid::Uuid>, ()>> =
+        vec![Ok(UploadTableResponseData {
+            id: carburetor::uuid::Uuid::parse_str(&bookmark.id).unwrap(),
+            last_synced_at: carburetor::helpers::get_utc_now(),
+            rejected_columns: Vec::new(),
+        })];
+    let response: bookmark_only::UploadResponse = carburetor::serde_json::from_str(&format!(
+        r#"{{"bookmark": {}}}"#,
+        carburetor::serde_json::to_string(&backend_response).unwrap()
+    ))
+    .unwrap();
+    bookmark_only::store_upload_response(cutoff, response).unwrap();
+
+    let stored: bookmark_only::FullBookmark = bookmark_only::bookmarks::table
+        .find(&bookmark.id)
+        .select(bookmark_only::FullBookmark::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(stored.dirty_flag, None);
+}
+
+#[tokio::test]
+async fn test_store_download_response_with_uuid_cursor() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let id = carburetor::uuid::Uuid::now_v7().to_string();
+    let now = carburetor::helpers::get_utc_now();
+    bookmark_only::store_download_response(bookmark_only::DownloadResponse {
+        bookmark: carburetor::models::DownloadTableResponse {
+            cutoff_at: now,
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                bookmark_only::DownloadUpdateBookmark {
+                    id: id.clone(),
+                    url: "https://example.com".to_string(),
+                    last_synced_at: now,
+                    is_deleted: false,
+                },
+            )],
+            cursor: Some(DownloadCursor {
+                last_synced_at: now,
+                id: id.clone(),
+                xmin: None,
+            }),
+            has_more: true,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    let stored: Vec<bookmark_only::FullBookmark> = bookmark_only::bookmarks::table
+        .select(bookmark_only::FullBookmark::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, id);
+
+    let request = bookmark_only::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(request.bookmark_cursor.map(|x| x.id), Some(id));
+}
@@ -0,0 +1,134 @@
+use carburetor::serde_json::{from_str, to_string};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::product_only;
+use tarpc::context::current as ctx;
+
+fn download_response(cursor: &str, has_more: bool, xmin: &str) -> product_only::DownloadResponse {
+    from_str(&format!(
+        r#"{{"product": {{
+            "cutoff_at": "2025-01-02T00:00:00Z",
+            "data": [{{"Update": {{
+                "id": "product-1",
+                "name": "Lamp",
+                "stock": 0,
+                "views": 0,
+                "last_synced_at": "2025-01-01T00:00:00Z",
+                "is_deleted": false
+            }}}}],
+            "cursor": {cursor},
+            "has_more": {has_more},
+            "reset": false,
+            "xmin": {xmin}
+        }}}}"#
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_download_request_sends_stored_xmin() {
+    let _db = get_clean_test_client_db();
+
+    product_only::store_download_response(download_response("null", false, "42")).unwrap();
+
+    let request = product_only::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(request.product_xmin, Some(42));
+    assert!(request.product_offset.is_some());
+}
+
+#[tokio::test]
+async fn test_xmin_of_first_page_is_kept_until_last_page() {
+    let _db = get_clean_test_client_db();
+
+    product_only::store_download_response(download_response(
+        r#"{"last_synced_at": "2025-01-01T00:00:00Z", "id": "product-1", "xmin": 42}"#,
+        true,
+        "42",
+    ))
+    .unwrap();
+
+    let request = product_only::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(request.product_cursor.and_then(|x| x.xmin), Some(42));
+    // The offset is only moved once every page is downloaded
+    assert_eq!(request.product_xmin, None);
+
+    product_only::store_download_response(download_response("null", false, "42")).unwrap();
+
+    let request = product_only::retrieve_download_request().unwrap().unwrap();
+    assert!(request.product_cursor.is_none());
+    assert_eq!(request.product_xmin, Some(42));
+}
+
+#[tokio::test]
+async fn test_backend_downloads_rows_written_after_snapshot() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let _db = get_clean_test_client_db();
+
+    let download = async |request: String| -> product_only::DownloadResponse {
+        from_str(
+            &backend
+                .process_product_only_download_request(ctx(), request)
+                .await
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let response = download("null".to_string()).await;
+    assert!(response.product.xmin.is_some());
+    product_only::store_download_response(response).unwrap();
+
+    product_only::insert_product(product_only::InsertProduct {
+        name: "Lamp".to_string(),
+        stock: 0,
+        views: 0,
+    })
+    .unwrap();
+    let (cutoff, upload_request) = product_only::retrieve_upload_request().unwrap();
+    let upload_response: product_only::UploadResponse = from_str(
+        &backend
+            .process_product_only_upload_request(ctx(), to_string(&upload_request).unwrap())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    product_only::store_upload_response(cutoff, upload_response).unwrap();
+
+    let request = product_only::retrieve_download_request().unwrap();
+    let response = download(to_string(&request).unwrap()).await;
+    assert_eq!(response.product.data.len(), 1);
+}
+
+#[tokio::test]
+async fn test_migration_indexes_xid_column() {
+    use diesel::{Connection, QueryableByName, RunQueryDsl, sql_query};
+
+    #[derive(QueryableByName)]
+    struct IndexRow {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        indexdef: String,
+    }
+
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let database_url = backend.test_helper_get_database_url(ctx()).await.unwrap();
+    let mut conn = diesel::PgConnection::establish(&database_url).unwrap();
+
+    let indexes: Vec<IndexRow> = sql_query(
+        "SELECT indexdef FROM pg_indexes \
+         WHERE tablename = 'products' AND indexname = 'products_carburetor_xid_idx'",
+    )
+    .load(&mut conn)
+    .unwrap();
+    assert_eq!(indexes.len(), 1);
+    assert!(indexes[0].indexdef.ends_with("(carburetor_xid, id)"));
+
+    // Migrating again keeps the index as is
+    assert!(
+        backend
+            .test_helper_rerun_migrations(ctx())
+            .await
+            .unwrap()
+            .is_ok()
+    );
+}
@@ -0,0 +1,134 @@
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::{all_clients, user_only};
+use tarpc::context::current as ctx;
+
+#[tokio::test]
+async fn test_async_upload_insert_then_download() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted_user = user_only::insert_user(user_only::InsertUser {
+        username: "async_user".to_string(),
+        first_name: None,
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    // Upload through the backend's async variant
+    let (upload_cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    let upload_response: user_only::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_user_only_upload_request_async(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(upload_response.user.len(), 1);
+    match &upload_response.user[0] {
+        Ok(r) => assert_eq!(r.id, inserted_user.id),
+        Err(e) => panic!("Expected success, got error: {:?}", e),
+    }
+    user_only::store_upload_response(upload_cutoff, upload_response).unwrap();
+
+    // Download through the backend's async variant
+    let download_request = user_only::retrieve_download_request().unwrap();
+    let download_response: user_only::DownloadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_user_only_download_request_async(
+                ctx(),
+                carburetor::serde_json::to_string(&download_request).unwrap(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(download_response.user.data.len(), 1);
+    user_only::store_download_response(download_response).unwrap();
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored_users.len(), 1);
+    assert_eq!(stored_users[0].id, inserted_user.id);
+    assert_eq!(stored_users[0].dirty_flag, None);
+    assert!(stored_users[0].last_synced_at.is_some());
+}
+
+#[tokio::test]
+async fn test_async_upload_and_download_respect_context() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    all_clients::insert_message(all_clients::InsertMessage {
+        recipient_id: "user-1".to_string(),
+        subject: "Hello".to_string(),
+        body: "World".to_string(),
+        notes: None,
+    })
+    .unwrap();
+    all_clients::insert_message(all_clients::InsertMessage {
+        recipient_id: "user-2".to_string(),
+        subject: "Hello".to_string(),
+        body: "Someone else".to_string(),
+        notes: None,
+    })
+    .unwrap();
+
+    let (cutoff, upload_request) = all_clients::retrieve_upload_request().unwrap();
+    let upload_response: all_clients::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_all_clients_upload_request_async(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+                "user-1".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(upload_response.message.len(), 2);
+    assert_eq!(
+        upload_response.message.iter().filter(|x| x.is_ok()).count(),
+        1
+    );
+    assert!(upload_response.message.iter().any(|x| matches!(
+        x,
+        Err(e) if e.code == carburetor::models::UploadTableResponseErrorType::InsufficientPermission
+    )));
+    all_clients::store_upload_response(cutoff, upload_response).unwrap();
+
+    let download_response: all_clients::DownloadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_all_clients_download_request_async(
+                ctx(),
+                carburetor::serde_json::to_string(&None::<all_clients::DownloadRequest>).unwrap(),
+                "user-1".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(download_response.message.data.len(), 1);
+    all_clients::store_download_response(download_response).unwrap();
+
+    let synced_messages: Vec<all_clients::FullMessage> = all_clients::messages::table
+        .select(all_clients::FullMessage::as_select())
+        .filter(all_clients::messages::last_synced_at.is_not_null())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(synced_messages.len(), 1);
+    assert_eq!(synced_messages[0].recipient_id, "user-1");
+}
@@ -0,0 +1,68 @@
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::user_only;
+use tarpc::context::current as ctx;
+
+#[tokio::test]
+async fn test_router_upload_then_download() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted_user = user_only::insert_user(user_only::InsertUser {
+        username: "router_user".to_string(),
+        first_name: None,
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    let (upload_cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    let (status, body) = backend
+        .call_user_only_router(
+            ctx(),
+            "/upload".to_string(),
+            carburetor::serde_json::to_string(&upload_request).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let upload_response: user_only::UploadResponse =
+        carburetor::serde_json::from_str(&body).unwrap();
+    assert_eq!(upload_response.user.len(), 1);
+    match &upload_response.user[0] {
+        Ok(r) => assert_eq!(r.id, inserted_user.id),
+        Err(e) => panic!("Expected success, got error: {:?}", e),
+    }
+    user_only::store_upload_response(upload_cutoff, upload_response).unwrap();
+
+    let download_request = user_only::retrieve_download_request().unwrap();
+    let (status, body) = backend
+        .call_user_only_router(
+            ctx(),
+            "/download".to_string(),
+            carburetor::serde_json::to_string(&download_request).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let download_response: user_only::DownloadResponse =
+        carburetor::serde_json::from_str(&body).unwrap();
+    assert_eq!(download_response.user.data.len(), 1);
+    user_only::store_download_response(download_response).unwrap();
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored_users.len(), 1);
+    assert_eq!(stored_users[0].id, inserted_user.id);
+    assert_eq!(stored_users[0].dirty_flag, None);
+    assert!(stored_users[0].last_synced_at.is_some());
+}
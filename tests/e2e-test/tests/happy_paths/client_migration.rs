@@ -32,7 +32,7 @@ fn assert_column(
     let col = columns.iter().find(|c| c.name == name).unwrap();
     assert_eq!(col.col_type, col_type, "column {} type", name);
     assert_eq!(col.notnull, notnull as i32, "column {} notnull", name);
-    assert_eq!(col.pk, pk as i32, "column {} pk", name);
+    assert_eq!(col.pk > 0, pk, "column {} pk", name);
     assert_eq!(
         col.dflt_value.as_deref(),
         default,
@@ -87,10 +87,69 @@ async fn test_clean_migration_creates_all_tables() {
     assert_column(&messages, "dirty_flag", "TEXT", false, false, None);
     assert_column(&messages, "column_sync_metadata", "JSON", true, false, None);
 
+    // SQLite has no UUID type
+    let bookmarks = get_columns(&mut conn, "bookmarks");
+    assert_column(&bookmarks, "id", "TEXT", true, true, None);
+
+    // Types without a SQLite counterpart are stored as their client representation
+    let invoices = get_columns(&mut conn, "invoices");
+    assert_column(&invoices, "number", "TEXT", true, false, None);
+    assert_column(&invoices, "amount", "TEXT", true, false, None);
+    assert_column(&invoices, "fee", "BIGINT", true, false, None);
+    assert_column(&invoices, "due_in", "BIGINT", true, false, None);
+    assert_column(&invoices, "attachment", "BLOB", false, false, None);
+    assert_column(&invoices, "metadata", "JSON", true, false, None);
+    assert_column(&invoices, "client_ip", "TEXT", false, false, None);
+    assert_column(&invoices, "tags", "JSON", true, false, None);
+    assert_column(&invoices, "quantities", "JSON", false, false, None);
+    assert_column(&invoices, "status", "TEXT", true, false, None);
+
     let offsets = get_columns(&mut conn, "carburetor_offsets");
-    assert_eq!(offsets.len(), 2);
+    assert_eq!(offsets.len(), 5);
+    assert_column(&offsets, "sync_group", "TEXT", true, true, None);
     assert_column(&offsets, "table_name", "TEXT", true, true, None);
+    assert_column(&offsets, "restriction", "TEXT", true, true, None);
     assert_column(&offsets, "cutoff_at", "TIMESTAMPTZ", true, false, None);
+    assert_column(&offsets, "xmin", "BIGINT", false, false, None);
+
+    let cursors = get_columns(&mut conn, "carburetor_download_cursors");
+    assert_eq!(cursors.len(), 6);
+    assert_column(&cursors, "sync_group", "TEXT", true, true, None);
+    assert_column(&cursors, "table_name", "TEXT", true, true, None);
+    assert_column(&cursors, "restriction", "TEXT", true, true, None);
+    assert_column(&cursors, "last_synced_at", "TIMESTAMPTZ", true, false, None);
+    assert_column(&cursors, "id", "TEXT", true, false, None);
+    assert_column(&cursors, "xmin", "BIGINT", false, false, None);
+
+    let clock = get_columns(&mut conn, "carburetor_clock");
+    assert_eq!(clock.len(), 2);
+    assert_column(&clock, "id", "INTEGER", true, true, None);
+    assert_column(&clock, "last_at", "TIMESTAMPTZ", true, false, None);
+
+    let device = get_columns(&mut conn, "carburetor_device");
+    assert_eq!(device.len(), 3);
+    assert_column(&device, "id", "INTEGER", true, true, None);
+    assert_column(&device, "device_id", "TEXT", true, false, None);
+    assert_column(&device, "counter", "BIGINT", true, false, None);
+
+    let failures = get_columns(&mut conn, "carburetor_upload_failures");
+    assert_eq!(failures.len(), 5);
+    assert_column(&failures, "table_name", "TEXT", true, true, None);
+    // Second column of the composite primary key
+    let failure_id = failures.iter().find(|c| c.name == "id").unwrap();
+    assert_eq!(failure_id.col_type, "TEXT");
+    assert_eq!(failure_id.notnull, 1);
+    assert_eq!(failure_id.pk, 2);
+    assert_column(&failures, "code", "TEXT", true, false, None);
+    assert_column(&failures, "attempts", "INTEGER", true, false, None);
+    assert_column(
+        &failures,
+        "last_attempt_at",
+        "TIMESTAMPTZ",
+        true,
+        false,
+        None,
+    );
 }
 
 /// Recreate `users` with only NOT NULL no-default columns, omitting every
@@ -144,6 +203,44 @@ async fn test_existing_table_missing_columns_gets_added() {
     assert_column(&after, "dirty_flag", "TEXT", false, false, None);
 }
 
+/// Offsets stored before they were kept per sync group are kept for tables synced by a single
+/// group. The others may have been moved by any of their groups, so they are dropped.
+#[tokio::test]
+async fn test_existing_offsets_gets_sync_group_added() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    diesel::sql_query("DROP TABLE carburetor_offsets")
+        .execute(&mut conn)
+        .unwrap();
+    diesel::sql_query(
+        "CREATE TABLE carburetor_offsets (\
+         table_name TEXT PRIMARY KEY NOT NULL, \
+         cutoff_at TIMESTAMPTZ NOT NULL, \
+         xmin BIGINT)",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query(
+        "INSERT INTO carburetor_offsets (table_name, cutoff_at) VALUES \
+         ('users', '2025-01-01T00:00:00Z'), \
+         ('bookmarks', '2025-01-01T00:00:00Z')",
+    )
+    .execute(&mut conn)
+    .unwrap();
+
+    sample_test_core::schema::run_migrations(&mut conn).unwrap();
+
+    let after = get_columns(&mut conn, "carburetor_offsets");
+    assert_eq!(after.len(), 5);
+    assert_column(&after, "sync_group", "TEXT", true, true, None);
+    let offsets = carburetor::helpers::carburetor_offset::retrieve_offsets(&mut conn).unwrap();
+    assert_eq!(offsets.len(), 1);
+    assert_eq!(offsets[0].sync_group, "bookmark_only");
+    assert_eq!(offsets[0].table_name, "bookmarks");
+    assert_eq!(offsets[0].restriction, "");
+}
+
 #[derive(Debug, QueryableByName)]
 struct UserRow {
     #[diesel(sql_type = diesel::sql_types::Text)]
@@ -273,3 +273,232 @@ async fn test_update_user() {
     assert_eq!(stored_users[0].is_deleted, false);
     assert_eq!(stored_users[0].dirty_flag.as_ref().unwrap(), "update");
 }
+
+#[tokio::test]
+async fn test_operations_with_pooled_connection() {
+    let db = get_clean_test_client_db();
+    let pool = carburetor::helpers::build_connection_pool(&db.database_path()).unwrap();
+    let mut pooled_conn = pool.get().unwrap();
+
+    // Run every generated local operation against a connection checked out from the pool
+    let inserted_user = user_only::insert_user_with_connection(
+        &mut pooled_conn,
+        user_only::InsertUser {
+            username: "pooled_username".to_string(),
+            first_name: None,
+            joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            created_at: None,
+            nickname: None,
+            priority: None,
+            preferences: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(inserted_user.dirty_flag.as_deref(), Some("insert"));
+
+    let updated_user = user_only::update_user_with_connection(
+        &mut pooled_conn,
+        user_only::UpdateUser {
+            username: Some("updated_pooled_username".to_string()),
+            first_name: None,
+            nickname: None,
+            priority: None,
+            preferences: None,
+            joined_on: None,
+            id: inserted_user.id.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(updated_user.username, "updated_pooled_username");
+
+    let deleted_user =
+        user_only::delete_user_with_connection(&mut pooled_conn, inserted_user.id.clone()).unwrap();
+    assert!(deleted_user.is_deleted);
+
+    // Changes are visible from a separately established connection
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut db.get_connection())
+        .unwrap();
+    assert_eq!(stored_users.len(), 1);
+    assert_eq!(stored_users[0].username, "updated_pooled_username");
+    assert!(stored_users[0].is_deleted);
+
+    // Upload request built from the same connection picks up the pending row
+    let (_, upload_request) =
+        user_only::retrieve_upload_request_with_connection(&mut pooled_conn, &Default::default())
+            .unwrap();
+    assert_eq!(upload_request.user.len(), 1);
+}
+
+#[tokio::test]
+async fn test_sync_through_configured_connection_pool() {
+    let db = get_clean_test_client_db();
+    let pool = carburetor::helpers::build_connection_pool(&db.database_path()).unwrap();
+    let user_only = user_only::SyncGroup::new(carburetor::config::Carburetor::new(
+        carburetor::config::CarburetorConfig {
+            // Never opened, every connection comes from the pool
+            database_path: "/nonexistent/carburetor.db".to_string(),
+            connection_pool: Some(pool.clone()),
+            ..Default::default()
+        },
+    ));
+
+    let inserted_user = user_only
+        .insert_user(user_only::InsertUser {
+            username: "pooled_username".to_string(),
+            first_name: None,
+            joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            created_at: None,
+            nickname: None,
+            priority: None,
+            preferences: None,
+        })
+        .unwrap();
+
+    let transport = carburetor::client::InProcessTransport::new(
+        |_, _| {
+            let response = user_only::UploadResponse {
+                user: vec![Ok(carburetor::models::UploadTableResponseData {
+                    id: inserted_user.id.clone(),
+                    last_synced_at: carburetor::helpers::get_utc_now(),
+                    rejected_columns: vec![],
+                })],
+            };
+            async move { Ok(carburetor::serde_json::to_string(&response).unwrap()) }
+        },
+        |_, _| async {
+            Ok(r#"{"user": {"cutoff_at": "2025-01-01T00:00:00Z", "data": []}}"#.to_string())
+        },
+    );
+    let summary = user_only.sync(&transport).await.unwrap();
+    assert_eq!(summary.sent, 1);
+    assert_eq!(summary.rejected, 0);
+    assert!(pool.state().connections > 0);
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut db.get_connection())
+        .unwrap();
+    assert_eq!(stored_users.len(), 1);
+    assert_eq!(stored_users[0].dirty_flag, None);
+    let (_, upload_request) = user_only.retrieve_upload_request().unwrap();
+    assert!(upload_request.user.is_empty());
+}
+
+#[tokio::test]
+async fn test_operations_with_separate_carburetor_instances() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let handles = ["account_a.db", "account_b.db"].map(|file_name| {
+        let carburetor =
+            carburetor::config::Carburetor::new(carburetor::config::CarburetorConfig {
+                database_path: temp_dir
+                    .path()
+                    .join(file_name)
+                    .to_string_lossy()
+                    .to_string(),
+                ..Default::default()
+            });
+        sample_test_core::schema::run_migrations(&mut carburetor.get_connection().unwrap())
+            .unwrap();
+        user_only::SyncGroup::new(carburetor)
+    });
+
+    let inserted_user = handles[0]
+        .insert_user(user_only::InsertUser {
+            username: "account_a_username".to_string(),
+            first_name: None,
+            joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            created_at: None,
+            nickname: None,
+            priority: None,
+            preferences: None,
+        })
+        .unwrap();
+    handles[0]
+        .update_user(user_only::UpdateUser {
+            username: Some("updated_account_a_username".to_string()),
+            first_name: None,
+            nickname: None,
+            priority: None,
+            preferences: None,
+            joined_on: None,
+            id: inserted_user.id.clone(),
+        })
+        .unwrap();
+
+    // Each handle only sees the database it was created with
+    let users_a: Vec<user_only::FullUser> = user_only::active_users()
+        .select(user_only::FullUser::as_select())
+        .load(&mut handles[0].carburetor().get_connection().unwrap())
+        .unwrap();
+    let users_b: Vec<user_only::FullUser> = user_only::active_users()
+        .select(user_only::FullUser::as_select())
+        .load(&mut handles[1].carburetor().get_connection().unwrap())
+        .unwrap();
+    assert_eq!(users_a.len(), 1);
+    assert_eq!(users_a[0].username, "updated_account_a_username");
+    assert!(users_b.is_empty());
+
+    let (_, upload_request_a) = handles[0].retrieve_upload_request().unwrap();
+    let (_, upload_request_b) = handles[1].retrieve_upload_request().unwrap();
+    assert_eq!(upload_request_a.user.len(), 1);
+    assert!(upload_request_b.user.is_empty());
+
+    handles[0].delete_user(inserted_user.id).unwrap();
+    let users_a: Vec<user_only::FullUser> = user_only::active_users()
+        .select(user_only::FullUser::as_select())
+        .load(&mut handles[0].carburetor().get_connection().unwrap())
+        .unwrap();
+    assert!(users_a.is_empty());
+
+    // Nothing downloaded yet, so neither database has offsets to resume from
+    assert!(handles[0].retrieve_download_request().unwrap().is_none());
+    assert!(handles[1].retrieve_download_request().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_purge_deleted_users() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let user = |id: &str, is_deleted: bool, synced: bool, dirty: bool| user_only::InsertableUser {
+        username: "username".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: carburetor::helpers::get_utc_now(),
+        nickname: None,
+        priority: None,
+        preferences: None,
+        id: id.to_string(),
+        last_synced_at: synced.then(carburetor::helpers::get_utc_now),
+        is_deleted,
+        dirty_flag: dirty.then(|| "update".to_string()),
+        column_sync_metadata: carburetor::serde_json::from_str("{}").unwrap(),
+    };
+    diesel::insert_into(user_only::users::table)
+        .values(&vec![
+            user("deleted-synced", true, true, false),
+            user("deleted-dirty", true, true, true),
+            user("deleted-never-synced", true, false, false),
+            user("active-synced", false, true, false),
+        ])
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(user_only::purge_deleted_users().unwrap(), 1);
+    assert_eq!(user_only::purge_synced_deletions().unwrap(), 0);
+
+    let mut stored_ids = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    stored_ids.sort();
+    assert_eq!(
+        stored_ids,
+        vec!["active-synced", "deleted-dirty", "deleted-never-synced"]
+    );
+}
@@ -160,3 +160,541 @@ async fn test_download_only_returns_messages_matching_context() {
         |m| matches!(m, carburetor::models::DownloadTableResponseData::Update(u) if u.id == "msg-b")
     ));
 }
+
+#[tokio::test]
+async fn test_download_starts_over_when_restriction_value_changes() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    insert_dummy_message(&backend, "msg-a", "user-1", false).await;
+    insert_dummy_message(&backend, "msg-b", "user-2", false).await;
+
+    let download = |user_id: &str| {
+        let backend = backend.clone();
+        let user_id = user_id.to_string();
+        async move {
+            let req = all_clients::retrieve_download_request().unwrap();
+            let res: all_clients::DownloadResponse = carburetor::serde_json::from_str(
+                &backend
+                    .process_all_clients_download_request(
+                        ctx(),
+                        carburetor::serde_json::to_string(&req).unwrap(),
+                        user_id,
+                    )
+                    .await
+                    .unwrap(),
+            )
+            .unwrap();
+            res
+        }
+    };
+
+    let res = download("user-1").await;
+    assert!(!res.message.reset);
+    assert!(res.message.restriction.is_some());
+    all_clients::store_download_response(res).unwrap();
+    let req = all_clients::retrieve_download_request().unwrap().unwrap();
+    assert!(req.message_restriction.is_some());
+
+    // Another user signs in on the same device, the offset of the first one does not apply
+    let res = download("user-2").await;
+    assert!(res.message.reset);
+    assert_ne!(res.message.restriction, req.message_restriction);
+    all_clients::store_download_response(res).unwrap();
+
+    let stored_messages: Vec<all_clients::FullMessage> = all_clients::messages::table
+        .select(all_clients::FullMessage::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored_messages.len(), 1);
+    assert_eq!(stored_messages[0].id, "msg-b");
+
+    // The same user keeps downloading from the offset
+    let res = download("user-2").await;
+    assert!(!res.message.reset);
+    assert!(res.message.data.is_empty());
+}
+
+#[tokio::test]
+async fn test_group_download_shares_single_cutoff() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let _db = get_clean_test_client_db();
+
+    insert_dummy_user(&backend, "user-1", false).await;
+    insert_dummy_message(&backend, "msg-a", "user-1", false).await;
+
+    for use_async in [false, true] {
+        let request_json =
+            carburetor::serde_json::to_string(&None::<all_clients::DownloadRequest>).unwrap();
+        let response_json = if use_async {
+            backend
+                .process_all_clients_download_request_async(
+                    ctx(),
+                    request_json,
+                    "user-1".to_string(),
+                )
+                .await
+                .unwrap()
+        } else {
+            backend
+                .process_all_clients_download_request(ctx(), request_json, "user-1".to_string())
+                .await
+                .unwrap()
+        };
+        let res: all_clients::DownloadResponse =
+            carburetor::serde_json::from_str(&response_json).unwrap();
+
+        assert_eq!(res.user.data.len(), 1);
+        assert_eq!(res.message.data.len(), 1);
+        assert_eq!(
+            res.user.cutoff_at, res.message.cutoff_at,
+            "every table of the group should be read with the same cutoff"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_paginated_download_until_caught_up() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    for id in ["a", "b", "c", "d", "e"] {
+        insert_dummy_user(&backend, id, false).await;
+    }
+
+    // A single page only advances the cursor, not the offset
+    let req = user_only::DownloadRequest {
+        user_limit: Some(2),
+        ..Default::default()
+    };
+    let res: user_only::DownloadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_user_only_download_request(
+                ctx(),
+                carburetor::serde_json::to_string(&Some(req)).unwrap(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.user.data.len(), 2);
+    assert!(res.user.has_more);
+    assert_eq!(res.user.cursor.as_ref().unwrap().id, "b");
+    user_only::store_download_response(res).unwrap();
+
+    let req = user_only::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(req.user_offset, None);
+    assert_eq!(req.user_cursor.unwrap().id, "b");
+
+    let pages = std::cell::Cell::new(0);
+    user_only::download_until_caught_up(Some(2), |request| {
+        pages.set(pages.get() + 1);
+        let backend = backend.clone();
+        async move {
+            let response = backend
+                .process_user_only_download_request(
+                    ctx(),
+                    carburetor::serde_json::to_string(&Some(request)).unwrap(),
+                )
+                .await
+                .unwrap();
+            Ok(carburetor::serde_json::from_str(&response).unwrap())
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(pages.get(), 2);
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored_users.len(), 5);
+
+    let req = user_only::retrieve_download_request().unwrap().unwrap();
+    assert!(req.user_offset.is_some());
+    assert_eq!(req.user_cursor, None);
+}
+
+#[tokio::test]
+async fn test_deletion_between_pages_of_first_download() {
+    use diesel::Connection;
+
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    for id in ["a", "b"] {
+        insert_dummy_user(&backend, id, false).await;
+    }
+
+    let download = |request: user_only::DownloadRequest| {
+        let backend = backend.clone();
+        async move {
+            let response = backend
+                .process_user_only_download_request(
+                    ctx(),
+                    carburetor::serde_json::to_string(&Some(request)).unwrap(),
+                )
+                .await
+                .unwrap();
+            Ok(carburetor::serde_json::from_str(&response).unwrap())
+        }
+    };
+
+    let res: user_only::DownloadResponse = download(user_only::DownloadRequest {
+        user_limit: Some(1),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(res.user.cursor.as_ref().unwrap().id, "a");
+    user_only::store_download_response(res).unwrap();
+
+    // Deleted on the backend before the next page is downloaded
+    let database_url = backend.test_helper_get_database_url(ctx()).await.unwrap();
+    let mut backend_conn = diesel::PgConnection::establish(&database_url).unwrap();
+    diesel::sql_query("UPDATE users SET is_deleted = true, last_synced_at = now() WHERE id = 'a'")
+        .execute(&mut backend_conn)
+        .unwrap();
+
+    user_only::download_until_caught_up(Some(1), download)
+        .await
+        .unwrap();
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    let deleted_user = stored_users.iter().find(|x| x.id == "a").unwrap();
+    assert!(
+        deleted_user.is_deleted,
+        "the deletion should be downloaded before the offset moves past it"
+    );
+}
+
+#[tokio::test]
+async fn test_download_until_caught_up_fails_when_cursor_does_not_move() {
+    let _db = get_clean_test_client_db();
+
+    let last_synced_at = carburetor::helpers::get_utc_now();
+    let mut download_count = 0;
+    let error = user_only::download_until_caught_up(Some(1), |_| {
+        download_count += 1;
+        // Claims there is more to come from the same position every time
+        let response = user_only::DownloadResponse {
+            user: carburetor::models::DownloadTableResponse {
+                cutoff_at: last_synced_at,
+                data: vec![],
+                cursor: Some(carburetor::models::DownloadCursor {
+                    last_synced_at,
+                    id: "a".to_string(),
+                    xmin: None,
+                }),
+                has_more: true,
+                reset: false,
+                reset_before: None,
+                xmin: None,
+                restriction: None,
+            },
+        };
+        async move { Ok(response) }
+    })
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        error,
+        carburetor::error::Error::DownloadStalled(_)
+    ));
+    assert_eq!(download_count, 2);
+}
+
+#[tokio::test]
+async fn test_store_paginated_download_response_keeps_offset() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let offset = carburetor::helpers::get_utc_now();
+    carburetor::helpers::carburetor_offset::upsert_offset(
+        &mut conn,
+        carburetor::helpers::carburetor_offset::OffsetKey {
+            sync_group: "user_only",
+            table_name: "users",
+            restriction: "",
+        },
+        offset,
+    )
+    .unwrap();
+
+    let last_synced_at = carburetor::helpers::get_utc_now();
+    let cursor = carburetor::models::DownloadCursor {
+        last_synced_at,
+        id: "a".to_string(),
+        xmin: None,
+    };
+    user_only::store_download_response(user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: carburetor::helpers::get_utc_now(),
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                user_only::DownloadUpdateUser {
+                    id: "a".to_string(),
+                    username: "username".to_string(),
+                    first_name: None,
+                    joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    created_at: last_synced_at,
+                    nickname: None,
+                    priority: 0,
+                    preferences: None,
+                    last_synced_at,
+                    is_deleted: false,
+                },
+            )],
+            cursor: Some(cursor.clone()),
+            has_more: true,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    let req = user_only::retrieve_download_request().unwrap().unwrap();
+    assert_eq!(req.user_offset, Some(offset));
+    assert_eq!(req.user_cursor, Some(cursor));
+}
+
+#[tokio::test]
+async fn test_download_after_tombstone_compaction_resets_table() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let download = || async {
+        let req = user_only::retrieve_download_request().unwrap();
+        let res: user_only::DownloadResponse = carburetor::serde_json::from_str(
+            &backend
+                .process_user_only_download_request(
+                    ctx(),
+                    carburetor::serde_json::to_string(&req).unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        res
+    };
+
+    insert_dummy_user(&backend, "a", false).await;
+    user_only::store_download_response(download().await).unwrap();
+
+    // Delete `a` on the backend, then compact it away
+    user_only::delete_user("a".to_string()).unwrap();
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    let upload_response: user_only::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_user_only_upload_request(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    user_only::store_upload_response(cutoff, upload_response).unwrap();
+    assert_eq!(backend.compact_tombstones(ctx(), 0).await.unwrap(), 1);
+
+    // Local changes are kept through the reset
+    let local_user = user_only::insert_user(user_only::InsertUser {
+        username: "local".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    let res = download().await;
+    assert!(res.user.reset);
+    assert!(res.user.reset_before.is_some());
+    assert!(res.user.data.is_empty());
+    user_only::store_download_response(res).unwrap();
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stored_users.len(), 1);
+    assert_eq!(stored_users[0].id, local_user.id);
+
+    assert!(!download().await.user.reset);
+}
+
+#[tokio::test]
+async fn test_store_reset_download_response_drops_unmodified_rows() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let download_update_user = |id: &str| user_only::DownloadUpdateUser {
+        id: id.to_string(),
+        username: "username".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: carburetor::helpers::get_utc_now(),
+        nickname: None,
+        priority: 0,
+        preferences: None,
+        last_synced_at: carburetor::helpers::get_utc_now(),
+        is_deleted: false,
+    };
+    let download_response = |ids: &[&str], reset: bool| user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: carburetor::helpers::get_utc_now(),
+            data: ids
+                .iter()
+                .map(|id| {
+                    carburetor::models::DownloadTableResponseData::Update(download_update_user(id))
+                })
+                .collect(),
+            cursor: None,
+            has_more: false,
+            reset,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    };
+
+    user_only::store_download_response(download_response(&["a", "b"], false)).unwrap();
+    let modified_user = user_only::insert_user(user_only::InsertUser {
+        username: "local".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    // `b` was deleted and compacted away on the backend
+    user_only::store_download_response(download_response(&["a"], true)).unwrap();
+
+    let mut stored_ids = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    stored_ids.sort();
+    let mut expected_ids = vec!["a".to_string(), modified_user.id];
+    expected_ids.sort();
+    assert_eq!(stored_ids, expected_ids);
+}
+
+#[tokio::test]
+async fn test_store_reset_download_response_keeps_rows_synced_after_compaction() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let compacted_before = carburetor::helpers::get_utc_now();
+    let download_update_user = |id: &str, last_synced_at| {
+        carburetor::models::DownloadTableResponseData::Update(user_only::DownloadUpdateUser {
+            id: id.to_string(),
+            username: "username".to_string(),
+            first_name: None,
+            joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            created_at: last_synced_at,
+            nickname: None,
+            priority: 0,
+            preferences: None,
+            last_synced_at,
+            is_deleted: false,
+        })
+    };
+    let download_response =
+        |data, reset_before: Option<carburetor::chrono::DateTimeUtc>| user_only::DownloadResponse {
+            user: carburetor::models::DownloadTableResponse {
+                cutoff_at: carburetor::helpers::get_utc_now(),
+                data,
+                cursor: None,
+                has_more: false,
+                reset: reset_before.is_some(),
+                reset_before,
+                xmin: None,
+                restriction: None,
+            },
+        };
+
+    user_only::store_download_response(download_response(
+        vec![
+            download_update_user("old", compacted_before - std::time::Duration::from_secs(60)),
+            download_update_user("new", compacted_before + std::time::Duration::from_secs(60)),
+        ],
+        None,
+    ))
+    .unwrap();
+
+    // Only `old` may have been deleted and compacted away on the backend
+    user_only::store_download_response(download_response(vec![], Some(compacted_before))).unwrap();
+
+    let stored_ids = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    assert_eq!(stored_ids, vec!["new".to_string()]);
+}
+
+#[tokio::test]
+async fn test_store_download_response_skips_purged_deletions() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let deleted_user = user_only::DownloadUpdateUser {
+        id: "purged".to_string(),
+        username: "username".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: carburetor::helpers::get_utc_now(),
+        nickname: None,
+        priority: 0,
+        preferences: None,
+        last_synced_at: carburetor::helpers::get_utc_now(),
+        is_deleted: true,
+    };
+    user_only::store_download_response(user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: carburetor::helpers::get_utc_now(),
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                deleted_user,
+            )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    let stored_users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert!(stored_users.is_empty());
+}
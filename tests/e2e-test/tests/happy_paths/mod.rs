@@ -1,7 +1,10 @@
+pub mod async_backend;
+pub mod axum_router;
 pub mod backend_migration;
 pub mod backend_operation;
 pub mod client_migration;
 pub mod client_operation;
 pub mod download;
 pub mod sync;
+pub mod sync_driver;
 pub mod upload;
@@ -127,8 +127,16 @@ async fn test_upload_update_then_download() {
         .execute(&mut conn)
         .unwrap();
 
-    carburetor::helpers::carburetor_offset::upsert_offset(&mut conn, "users", before_insert)
-        .unwrap();
+    carburetor::helpers::carburetor_offset::upsert_offset(
+        &mut conn,
+        carburetor::helpers::carburetor_offset::OffsetKey {
+            sync_group: "user_only",
+            table_name: "users",
+            restriction: "",
+        },
+        before_insert,
+    )
+    .unwrap();
 
     // Update the user on the client using the generated function
     let updated_user = user_only::update_user(user_only::UpdateUser {
@@ -0,0 +1,324 @@
+use std::cell::RefCell;
+
+use carburetor::client::{InProcessTransport, SyncSummary};
+use carburetor::error::Error;
+use carburetor::models::{UploadTableResponseError, UploadTableResponseErrorType};
+use carburetor::serde_json::to_string;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::{TestBackendHandle, get_clean_test_client_db};
+use sample_test_core::schema::{all_clients, user_only};
+use tarpc::context::current as ctx;
+
+fn insert_test_user() -> user_only::FullUser {
+    user_only::insert_user(user_only::InsertUser {
+        username: "sync_driver_user".to_string(),
+        first_name: None,
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: Some(carburetor::helpers::get_utc_now()),
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap()
+}
+
+fn download_response(id: &str, cursor: Option<&str>) -> String {
+    let cursor = match cursor {
+        Some(last_synced_at) => {
+            format!(r#"{{"last_synced_at": "{last_synced_at}", "id": "{id}"}}"#)
+        }
+        None => "null".to_string(),
+    };
+    format!(
+        r#"{{"user": {{
+            "cutoff_at": "2025-01-02T00:00:00Z",
+            "data": [{{"Update": {{
+                "id": "{id}",
+                "username": "downloaded",
+                "first_name": null,
+                "joined_on": "2025-01-01",
+                "created_at": "2025-01-01T00:00:00Z",
+                "nickname": null,
+                "priority": 0,
+                "preferences": null,
+                "last_synced_at": "2025-01-01T00:00:00Z",
+                "is_deleted": false
+            }}}}],
+            "cursor": {cursor},
+            "has_more": {has_more},
+            "reset": false
+        }}}}"#,
+        has_more = cursor != "null"
+    )
+}
+
+#[tokio::test]
+async fn test_sync_uploads_then_downloads() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+
+    let transport = InProcessTransport::new(
+        |sync_group, request| {
+            let backend = backend.clone();
+            async move {
+                assert_eq!(sync_group, "user_only");
+                backend
+                    .process_user_only_upload_request(ctx(), request)
+                    .await
+                    .map_err(|e| Error::Transport {
+                        message: "Upload failed".to_string(),
+                        source: e.into(),
+                    })
+            }
+        },
+        |sync_group, request| {
+            let backend = backend.clone();
+            async move {
+                assert_eq!(sync_group, "user_only");
+                backend
+                    .process_user_only_download_request(ctx(), request)
+                    .await
+                    .map_err(|e| Error::Transport {
+                        message: "Download failed".to_string(),
+                        source: e.into(),
+                    })
+            }
+        },
+    );
+
+    let summary = user_only::sync(&transport).await.unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            sent: 1,
+            received: 1,
+            rejected: 0,
+        }
+    );
+
+    let users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, inserted.id);
+    assert_eq!(users[0].dirty_flag, None);
+    assert!(users[0].last_synced_at.is_some());
+
+    // Nothing is left to upload
+    let summary = user_only::sync(&transport).await.unwrap();
+    assert_eq!(summary.sent, 0);
+}
+
+#[tokio::test]
+async fn test_sync_counts_rows_rejected_by_backend() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    for recipient_id in ["user-1", "user-2"] {
+        all_clients::insert_message(all_clients::InsertMessage {
+            recipient_id: recipient_id.to_string(),
+            subject: "Hello".to_string(),
+            body: "World".to_string(),
+            notes: None,
+        })
+        .unwrap();
+    }
+
+    let transport = InProcessTransport::new(
+        |sync_group, request| {
+            let backend = backend.clone();
+            async move {
+                assert_eq!(sync_group, "all_clients");
+                backend
+                    .process_all_clients_upload_request(ctx(), request, "user-1".to_string())
+                    .await
+                    .map_err(|e| Error::Transport {
+                        message: "Upload failed".to_string(),
+                        source: e.into(),
+                    })
+            }
+        },
+        |sync_group, request| {
+            let backend = backend.clone();
+            async move {
+                assert_eq!(sync_group, "all_clients");
+                backend
+                    .process_all_clients_download_request(ctx(), request, "user-1".to_string())
+                    .await
+                    .map_err(|e| Error::Transport {
+                        message: "Download failed".to_string(),
+                        source: e.into(),
+                    })
+            }
+        },
+    );
+
+    // The message for another user is rejected, the accepted one comes back with the download
+    let summary = all_clients::sync(&transport).await.unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            sent: 2,
+            received: 1,
+            rejected: 1,
+        }
+    );
+
+    let messages: Vec<all_clients::FullMessage> = all_clients::messages::table
+        .select(all_clients::FullMessage::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(messages.len(), 2);
+    let accepted = messages
+        .iter()
+        .find(|x| x.recipient_id == "user-1")
+        .unwrap();
+    assert_eq!(accepted.dirty_flag, None);
+    assert!(accepted.last_synced_at.is_some());
+    let rejected = messages
+        .iter()
+        .find(|x| x.recipient_id == "user-2")
+        .unwrap();
+    assert_eq!(rejected.dirty_flag.as_deref(), Some("rejected"));
+}
+
+#[tokio::test]
+async fn test_sync_counts_rejected_rows() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let inserted = insert_test_user();
+    let calls = RefCell::new(vec![]);
+    let calls = &calls;
+
+    let transport = InProcessTransport::new(
+        |_, _| {
+            calls.borrow_mut().push("upload");
+            let response = user_only::UploadResponse {
+                user: vec![Err(UploadTableResponseError {
+                    id: inserted.id.clone(),
+                    code: UploadTableResponseErrorType::InsufficientPermission,
+                })],
+            };
+            async move { Ok(to_string(&response).unwrap()) }
+        },
+        |_, _| {
+            calls.borrow_mut().push("download");
+            async { Ok(download_response("user-1", None)) }
+        },
+    );
+
+    let summary = user_only::sync(&transport).await.unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            sent: 1,
+            received: 1,
+            rejected: 1,
+        }
+    );
+    assert_eq!(*calls.borrow(), vec!["upload", "download"]);
+
+    // The rejected row is kept on the client next to the downloaded one
+    let rejected = user_only::users::table
+        .find(&inserted.id)
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(rejected.dirty_flag.as_deref(), Some("rejected"));
+    let downloaded = user_only::users::table
+        .find("user-1")
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(downloaded.username, "downloaded");
+}
+
+#[tokio::test]
+async fn test_sync_downloads_every_page_without_upload() {
+    let _db = get_clean_test_client_db();
+
+    let download_requests = RefCell::new(vec![]);
+    let download_requests = &download_requests;
+
+    let transport = InProcessTransport::new(
+        |_, _| async { panic!("Nothing should be uploaded") },
+        |_, request| {
+            let mut requests = download_requests.borrow_mut();
+            requests.push(request);
+            let response = if requests.len() == 1 {
+                download_response("user-1", Some("2025-01-01T00:00:00Z"))
+            } else {
+                download_response("user-2", None)
+            };
+            async move { Ok(response) }
+        },
+    );
+
+    let summary = user_only::sync(&transport).await.unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            sent: 0,
+            received: 2,
+            rejected: 0,
+        }
+    );
+
+    let download_requests = download_requests.borrow();
+    assert_eq!(download_requests.len(), 2);
+    // The first page starts from the beginning
+    assert!(download_requests[0].contains(r#""user_offset":null"#));
+    assert!(download_requests[0].contains(r#""user_cursor":null"#));
+    // The second page resumes from the cursor of the first one
+    assert!(download_requests[1].contains(r#""user_cursor":{"#));
+}
+
+#[tokio::test]
+async fn test_sync_returns_transport_error() {
+    let _db = get_clean_test_client_db();
+
+    let transport = InProcessTransport::new(
+        |_, _| async { panic!("Nothing should be uploaded") },
+        |_, _| async {
+            Err(Error::Transport {
+                message: "Backend unreachable".to_string(),
+                source: std::io::Error::other("connection refused").into(),
+            })
+        },
+    );
+
+    let error = user_only::sync(&transport).await.unwrap_err();
+    assert!(matches!(error, Error::Transport { .. }));
+    assert!(error.is_retryable());
+    assert!(user_only::retrieve_download_request().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_sync_fails_when_download_does_not_move() {
+    let _db = get_clean_test_client_db();
+
+    let download_count = RefCell::new(0);
+    let download_count = &download_count;
+
+    // Every page claims there is more to come from the same position
+    let transport = InProcessTransport::new(
+        |_, _| async { panic!("Nothing should be uploaded") },
+        |_, _| {
+            *download_count.borrow_mut() += 1;
+            async { Ok(download_response("user-1", Some("2025-01-01T00:00:00Z"))) }
+        },
+    );
+
+    let error = user_only::sync(&transport).await.unwrap_err();
+    assert!(matches!(error, Error::DownloadStalled(_)));
+    assert!(!error.is_retryable());
+    assert_eq!(*download_count.borrow(), 2);
+}
@@ -1,7 +1,8 @@
+use carburetor::chrono::NaiveDate;
 use carburetor::helpers::client_sync_metadata::ClientSyncMetadata;
-use diesel::{RunQueryDsl, SelectableHelper, query_dsl::methods::SelectDsl};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
 use e2e_test::{TestBackendHandle, get_clean_test_client_db};
-use sample_test_core::schema::{all_clients, user_only};
+use sample_test_core::schema::{all_clients, checkout, user_only};
 use tarpc::context::current as ctx;
 
 #[tokio::test]
@@ -438,3 +439,56 @@ async fn test_upload_insert_message_matching_context() {
     assert_eq!(stored.len(), 1);
     assert_eq!(stored[0].dirty_flag, None);
 }
+
+#[tokio::test]
+async fn test_atomic_upload_applies_all_rows() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    checkout::insert_user(checkout::InsertUser {
+        username: "buyer".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+    for subject in ["order", "line item"] {
+        checkout::insert_message(checkout::InsertMessage {
+            recipient_id: "user-1".to_string(),
+            subject: subject.to_string(),
+            body: "body".to_string(),
+            notes: None,
+        })
+        .unwrap();
+    }
+
+    let (cutoff, upload_request) = checkout::retrieve_upload_request().unwrap();
+    let upload_response: checkout::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_checkout_upload_request_async(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+                "user-1".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(upload_response.user.len(), 1);
+    assert_eq!(upload_response.message.len(), 2);
+    assert!(upload_response.user.iter().all(|x| x.is_ok()));
+    assert!(upload_response.message.iter().all(|x| x.is_ok()));
+
+    checkout::store_upload_response(cutoff, upload_response).unwrap();
+    let dirty_messages: Vec<checkout::FullMessage> = checkout::messages::table
+        .select(checkout::FullMessage::as_select())
+        .filter(checkout::messages::dirty_flag.is_not_null())
+        .load(&mut conn)
+        .unwrap();
+    assert!(dirty_messages.is_empty());
+}
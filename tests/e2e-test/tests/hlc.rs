@@ -0,0 +1,139 @@
+//! Hybrid logical clock of the client, see `carburetor::helpers::clock`. Built with the `hlc`
+//! feature only, as the wall clock is tested by the other targets.
+
+use carburetor::chrono::NaiveDate;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+use e2e_test::get_clean_test_client_db;
+use sample_test_core::schema::user_only;
+
+#[tokio::test]
+async fn test_local_changes_are_ordered_after_downloaded_data() {
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    // The backend clock is an hour ahead of the device clock
+    let backend_now = carburetor::helpers::get_utc_now() + std::time::Duration::from_secs(60 * 60);
+    user_only::store_download_response(user_only::DownloadResponse {
+        user: carburetor::models::DownloadTableResponse {
+            cutoff_at: backend_now,
+            data: vec![carburetor::models::DownloadTableResponseData::Update(
+                user_only::DownloadUpdateUser {
+                    id: "user-skewed-1".to_string(),
+                    username: "downloaded".to_string(),
+                    first_name: None,
+                    joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                    created_at: backend_now,
+                    nickname: None,
+                    priority: 0,
+                    preferences: None,
+                    last_synced_at: backend_now,
+                    is_deleted: false,
+                },
+            )],
+            cursor: None,
+            has_more: false,
+            reset: false,
+            reset_before: None,
+            xmin: None,
+            restriction: None,
+        },
+    })
+    .unwrap();
+
+    user_only::update_user(user_only::UpdateUser {
+        id: "user-skewed-1".to_string(),
+        username: Some("edited".to_string()),
+        first_name: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+        joined_on: None,
+    })
+    .unwrap();
+    let inserted = user_only::insert_user(user_only::InsertUser {
+        username: "inserted".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    let updated: user_only::FullUser = user_only::users::table
+        .find("user-skewed-1")
+        .select(user_only::FullUser::as_select())
+        .first(&mut conn)
+        .unwrap();
+    let dirty_at = updated.column_sync_metadata["username"]["dirty_at"]
+        .as_str()
+        .unwrap()
+        .parse::<carburetor::chrono::DateTimeUtc>()
+        .unwrap();
+    assert!(
+        dirty_at > backend_now,
+        "update should be after the download"
+    );
+    let insert_time = inserted.column_sync_metadata[".insert_time"]
+        .as_str()
+        .unwrap()
+        .parse::<carburetor::chrono::DateTimeUtc>()
+        .unwrap();
+    assert!(insert_time > dirty_at, "clock should never go backward");
+
+    let (cutoff, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(cutoff > insert_time);
+    assert_eq!(upload_request.user.len(), 2);
+}
+
+#[tokio::test]
+async fn test_failed_upload_is_backed_off_on_the_hybrid_clock() {
+    let _db = get_clean_test_client_db();
+
+    let inserted = user_only::insert_user(user_only::InsertUser {
+        username: "failing".to_string(),
+        first_name: None,
+        joined_on: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+
+    // The clock moves an hour ahead of the device clock by observing the backend
+    let backend_now = carburetor::helpers::get_utc_now() + std::time::Duration::from_secs(60 * 60);
+    let (cutoff, _) = user_only::retrieve_upload_request().unwrap();
+    user_only::store_upload_response(
+        cutoff,
+        user_only::UploadResponse {
+            user: vec![Ok(carburetor::models::UploadTableResponseData {
+                id: "user-unknown-1".to_string(),
+                last_synced_at: backend_now,
+                rejected_columns: Vec::new(),
+            })],
+        },
+    )
+    .unwrap();
+
+    let (cutoff, _) = user_only::retrieve_upload_request().unwrap();
+    user_only::store_upload_response(
+        cutoff,
+        user_only::UploadResponse {
+            user: vec![Err(carburetor::models::UploadTableResponseError {
+                id: inserted.id.clone(),
+                code: carburetor::models::UploadTableResponseErrorType::Unknown,
+            })],
+        },
+    )
+    .unwrap();
+
+    let failures = user_only::list_upload_failures().unwrap();
+    assert!(failures[0].last_attempt_at > backend_now);
+    let (_, upload_request) = user_only::retrieve_upload_request().unwrap();
+    assert!(
+        upload_request.user.is_empty(),
+        "failed row should be held back by the backoff"
+    );
+}
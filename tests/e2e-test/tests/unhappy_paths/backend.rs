@@ -1,7 +1,7 @@
 use carburetor::models::UploadTableResponseErrorType;
-use diesel::RunQueryDsl;
+use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
 use e2e_test::{TestBackendHandle, get_clean_test_client_db};
-use sample_test_core::schema::{all_clients, user_only};
+use sample_test_core::schema::{all_clients, checkout, user_only};
 use tarpc::context::current as ctx;
 
 #[tokio::test]
@@ -61,10 +61,15 @@ async fn test_upload_update_record_not_on_backend() {
     }
 
     user_only::store_upload_response(cutoff, upload_response).unwrap();
+    let count: i64 = user_only::users::table
+        .count()
+        .get_result(&mut conn)
+        .unwrap();
+    assert_eq!(count, 0, "Record missing on the backend should be dropped");
 }
 
 #[tokio::test]
-async fn test_upload_insert_record_already_exists_on_backend() {
+async fn test_upload_insert_record_already_on_backend_is_applied_again() {
     let backend_server = TestBackendHandle::start();
     let backend = backend_server.client().await;
     let db = get_clean_test_client_db();
@@ -87,10 +92,10 @@ async fn test_upload_insert_record_already_exists_on_backend() {
         .await
         .unwrap();
 
-    // Now try to insert the same record from client
+    // The same insert uploaded again after its response was lost, with a later local edit
     let dirty_user = user_only::InsertableUser {
         id: "user-duplicate-1".to_string(),
-        username: "existing_user".to_string(),
+        username: "edited_user".to_string(),
         first_name: Some("Existing".to_string()),
         joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
         created_at: carburetor::helpers::get_utc_now(),
@@ -126,15 +131,103 @@ async fn test_upload_insert_record_already_exists_on_backend() {
     .unwrap();
 
     assert_eq!(upload_response.user.len(), 1);
-    match &upload_response.user[0] {
+    assert!(upload_response.user[0].is_ok());
+
+    user_only::store_upload_response(cutoff, upload_response).unwrap();
+    let users: Vec<user_only::FullUser> = user_only::users::table
+        .select(user_only::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(users.len(), 1, "Record should not be duplicated");
+    assert_eq!(users[0].id, dirty_user.id);
+    assert_eq!(users[0].dirty_flag, None);
+
+    let download_response: user_only::DownloadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_user_only_download_request(ctx(), "null".to_string())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(download_response.user.data.len(), 1);
+    assert!(matches!(
+        &download_response.user.data[0],
+        carburetor::models::DownloadTableResponseData::Update(x) if x.username == "edited_user"
+    ));
+}
+
+#[tokio::test]
+async fn test_upload_insert_message_id_taken_by_other_recipient() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    backend
+        .test_helper_insert_message(
+            ctx(),
+            "msg-taken-1".to_string(),
+            "user-1".to_string(),
+            "subject".to_string(),
+            "body".to_string(),
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let dirty_message = all_clients::InsertableMessage {
+        id: "msg-taken-1".to_string(),
+        recipient_id: "user-2".to_string(),
+        subject: "Hello".to_string(),
+        body: "World".to_string(),
+        notes: None,
+        last_synced_at: None,
+        is_deleted: false,
+        dirty_flag: Some("insert".to_string()),
+        column_sync_metadata: carburetor::serde_json::from_str(&format!(
+            r#"{{".insert_time": "{}"}}"#,
+            carburetor::helpers::get_utc_now().to_rfc3339()
+        ))
+        .unwrap(),
+    };
+    diesel::insert_into(all_clients::messages::table)
+        .values(&dirty_message)
+        .execute(&mut conn)
+        .unwrap();
+
+    let (cutoff, upload_request) = all_clients::retrieve_upload_request().unwrap();
+    let upload_response: all_clients::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_all_clients_upload_request(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+                "user-2".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(upload_response.message.len(), 1);
+    match &upload_response.message[0] {
         Err(e) => {
-            assert_eq!(e.id, dirty_user.id);
+            assert_eq!(e.id, dirty_message.id);
             assert_eq!(e.code, UploadTableResponseErrorType::RecordAlreadyExists);
         }
-        Ok(_) => panic!("Expected error for inserting already-existing backend record"),
+        Ok(_) => panic!("Expected error for an ID taken by another recipient"),
     }
 
-    user_only::store_upload_response(cutoff, upload_response).unwrap();
+    all_clients::store_upload_response(cutoff, upload_response).unwrap();
+    let messages: Vec<all_clients::FullMessage> = all_clients::messages::table
+        .select(all_clients::FullMessage::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_ne!(
+        messages[0].id, dirty_message.id,
+        "Record should be given a new ID"
+    );
 }
 
 #[tokio::test]
@@ -254,3 +347,111 @@ async fn test_upload_update_message_mismatching_context() {
 
     all_clients::store_upload_response(cutoff, upload_response).unwrap();
 }
+
+#[tokio::test]
+async fn test_atomic_upload_rolls_back_when_a_row_fails() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+    let db = get_clean_test_client_db();
+    let mut conn = db.get_connection();
+
+    let user = checkout::insert_user(checkout::InsertUser {
+        username: "buyer".to_string(),
+        first_name: None,
+        joined_on: carburetor::chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        created_at: None,
+        nickname: None,
+        priority: None,
+        preferences: None,
+    })
+    .unwrap();
+    let allowed_message = checkout::insert_message(checkout::InsertMessage {
+        recipient_id: "user-1".to_string(),
+        subject: "order".to_string(),
+        body: "body".to_string(),
+        notes: None,
+    })
+    .unwrap();
+    // Not allowed for the `user-1` context, which aborts the whole upload
+    let rejected_message = checkout::insert_message(checkout::InsertMessage {
+        recipient_id: "user-2".to_string(),
+        subject: "line item".to_string(),
+        body: "body".to_string(),
+        notes: None,
+    })
+    .unwrap();
+
+    let (cutoff, upload_request) = checkout::retrieve_upload_request().unwrap();
+    let upload_response: checkout::UploadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_checkout_upload_request(
+                ctx(),
+                carburetor::serde_json::to_string(&upload_request).unwrap(),
+                "user-1".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    let error_code_of = |id: &str| {
+        upload_response
+            .user
+            .iter()
+            .chain(upload_response.message.iter())
+            .find_map(|x| match x {
+                Err(e) if e.id == id => Some(e.code.clone()),
+                _ => None,
+            })
+            .expect("every row should be reported as failed")
+    };
+    assert_eq!(
+        error_code_of(&user.id),
+        UploadTableResponseErrorType::Aborted
+    );
+    assert_eq!(
+        error_code_of(&allowed_message.id),
+        UploadTableResponseErrorType::Aborted
+    );
+    assert_eq!(
+        error_code_of(&rejected_message.id),
+        UploadTableResponseErrorType::InsufficientPermission
+    );
+
+    // Nothing was applied on the backend
+    let download_response: all_clients::DownloadResponse = carburetor::serde_json::from_str(
+        &backend
+            .process_all_clients_download_request(
+                ctx(),
+                carburetor::serde_json::to_string(&None::<all_clients::DownloadRequest>).unwrap(),
+                "user-1".to_string(),
+            )
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(download_response.user.data.is_empty());
+    assert!(download_response.message.data.is_empty());
+
+    // Aborted rows stay dirty on the client to be uploaded again
+    checkout::store_upload_response(cutoff, upload_response).unwrap();
+    let users: Vec<checkout::FullUser> = checkout::users::table
+        .select(checkout::FullUser::as_select())
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(users[0].dirty_flag.as_deref(), Some("insert"));
+}
+
+#[tokio::test]
+async fn test_router_returns_service_unavailable_without_database() {
+    let backend_server = TestBackendHandle::start();
+    let backend = backend_server.client().await;
+
+    let (status, body) = backend
+        .call_user_only_router(ctx(), "/download".to_string(), "null".to_string(), false)
+        .await
+        .unwrap();
+    assert_eq!(status, 503);
+    // The connection error is not described to the caller
+    assert_eq!(body, "Service Unavailable");
+}
@@ -93,7 +93,15 @@ async fn test_type_mismatch_affinity_fails() {
     let result = sample_test_core::schema::run_migrations(&mut conn);
     assert!(result.is_err(), "migration should fail");
 
-    let err_msg = result.unwrap_err().to_string();
+    let err = result.unwrap_err();
+    assert!(
+        matches!(err, carburetor::error::Error::SchemaMismatch(_)),
+        "error should be a schema mismatch: {:?}",
+        err
+    );
+    assert!(err.is_schema_error());
+    assert!(!err.is_retryable());
+    let err_msg = err.to_string();
     assert!(
         err_msg.contains("type mismatch"),
         "error should mention type mismatch: {}",
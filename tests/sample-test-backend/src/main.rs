@@ -1,7 +1,10 @@
 mod test_database;
 mod test_service;
 
-use carburetor::config::{CarburetorGlobalConfig, initialize_carburetor_global_config};
+use carburetor::{
+    config::{CarburetorGlobalConfig, initialize_carburetor_global_config},
+    helpers::build_async_connection_pool,
+};
 use test_database::TestDatabase;
 
 use crate::test_service::TestService;
@@ -15,8 +18,14 @@ async fn main() {
 
     let test_db = TestDatabase::start().await;
 
+    // The async functions check out their connection from the pool, so the e2e tests of the
+    // async backend run on pooled connections
+    let async_connection_pool = build_async_connection_pool(&test_db.database_url)
+        .await
+        .expect("Failed to build async connection pool");
     initialize_carburetor_global_config(CarburetorGlobalConfig {
         database_url: test_db.database_url.clone(),
+        async_connection_pool: Some(async_connection_pool),
     });
 
     TestService::start(port, test_db.database_url).await;
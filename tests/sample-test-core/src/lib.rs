@@ -18,6 +18,10 @@ pub mod backend_service {
         // Backend functions
         async fn process_user_only_download_request(request_json: String) -> String;
         async fn process_user_only_upload_request(request_json: String) -> String;
+        async fn process_preference_only_download_request(request_json: String) -> String;
+        async fn process_preference_only_upload_request(request_json: String) -> String;
+        async fn process_product_only_download_request(request_json: String) -> String;
+        async fn process_product_only_upload_request(request_json: String) -> String;
         async fn process_all_clients_download_request(
             request_json: String,
             context_user_id: String,
@@ -26,6 +30,33 @@ pub mod backend_service {
             request_json: String,
             context_user_id: String,
         ) -> String;
+        async fn process_checkout_upload_request(
+            request_json: String,
+            context_user_id: String,
+        ) -> String;
+        async fn process_user_only_download_request_async(request_json: String) -> String;
+        async fn process_user_only_upload_request_async(request_json: String) -> String;
+        async fn process_all_clients_download_request_async(
+            request_json: String,
+            context_user_id: String,
+        ) -> String;
+        async fn process_all_clients_upload_request_async(
+            request_json: String,
+            context_user_id: String,
+        ) -> String;
+        async fn process_checkout_upload_request_async(
+            request_json: String,
+            context_user_id: String,
+        ) -> String;
+        /// POSTs `body` to `path` of `user_only::router()`, returning the status code and body of
+        /// the response. Without `database_available`, the router of a handle to a database that
+        /// cannot be reached is called instead.
+        async fn call_user_only_router(
+            path: String,
+            body: String,
+            database_available: bool,
+        ) -> (u16, String);
+        async fn compact_tombstones(older_than_secs: u64) -> usize;
 
         // Test helper functions
         async fn test_helper_insert_user(
@@ -57,6 +88,15 @@ pub mod backend_service {
 pub mod schema {
     use carburetor::prelude::*;
 
+    #[sync_enum]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InvoiceStatus {
+        Draft,
+        Sent,
+        #[sync_enum(rename = "paid_in_full")]
+        Paid,
+    }
+
     carburetor_sync_config! {
         tables {
             user {
@@ -74,12 +114,42 @@ pub mod schema {
                 preferences -> Nullable<Text>,
             }
             message {
+                #[id(uuid_v7)]
+                id -> Text,
                 #[immutable]
                 recipient_id -> Text,
                 subject -> Text,
                 body -> Text,
                 notes -> Nullable<Text>,
             }
+            bookmark {
+                #[id]
+                id -> Uuid,
+                url -> Text,
+            }
+            invoice {
+                number -> Varchar,
+                amount -> Numeric,
+                fee -> Money,
+                due_in -> Interval,
+                attachment -> Nullable<Bytea>,
+                metadata -> Json,
+                client_ip -> Nullable<Inet>,
+                tags -> Array<Text>,
+                quantities -> Nullable<Array<Nullable<Integer>>>,
+                status -> Enum<crate::schema::InvoiceStatus>,
+            }
+            preference {
+                #[merge(json_keys)]
+                settings -> Jsonb,
+            }
+            product(change_tracking = "xid") {
+                name -> Text,
+                #[crdt(counter)]
+                stock -> Integer,
+                #[crdt(counter)]
+                views -> BigInt,
+            }
         }
         sync_groups {
             user_only {
@@ -92,6 +162,25 @@ pub mod schema {
                     restrict_to_column = recipient_id,
                 )
             }
+            checkout(atomic) {
+                user,
+                message(
+                    restrict_to = $user_id,
+                    restrict_to_column = recipient_id,
+                )
+            }
+            bookmark_only {
+                bookmark
+            }
+            invoice_only {
+                invoice
+            }
+            preference_only {
+                preference
+            }
+            product_only {
+                product
+            }
         }
     }
 }